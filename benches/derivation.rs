@@ -0,0 +1,134 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use hdwallet::{
+    ChainPath, DefaultKeyChain, ExtendedPrivKey, ExtendedPubKey, KeyChain, KeyIndex, KeySeed,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use std::time::Duration;
+
+const SEED: [u8; 16] = [
+    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+];
+const SIBLINGS: u32 = 20;
+
+fn master_key(c: &mut Criterion) {
+    let mut group = c.benchmark_group("master_key");
+    group.bench_function("with_seed", |b| {
+        b.iter(|| ExtendedPrivKey::with_seed(black_box(&SEED)).unwrap())
+    });
+    // a seeded rng keeps the generated keys identical between runs
+    let mut rng = StdRng::seed_from_u64(0);
+    group.bench_function("random_s256", |b| {
+        b.iter(|| ExtendedPrivKey::random_with_seed_size(&mut rng, KeySeed::S256).unwrap())
+    });
+    group.finish();
+}
+
+fn private_derivation(c: &mut Criterion) {
+    let parent = ExtendedPrivKey::with_seed(&SEED).expect("master key");
+    let mut group = c.benchmark_group("private_derivation");
+    group.bench_function("hardened", |b| {
+        let index = KeyIndex::hardened_from_normalize_index(0).unwrap();
+        b.iter(|| parent.derive_private_key(black_box(index)).unwrap())
+    });
+    group.bench_function("normal", |b| {
+        b.iter(|| {
+            parent
+                .derive_private_key(black_box(KeyIndex::Normal(0)))
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+fn public_derivation(c: &mut Criterion) {
+    let parent =
+        ExtendedPubKey::from_private_key(&ExtendedPrivKey::with_seed(&SEED).expect("master key"));
+    let mut group = c.benchmark_group("public_derivation");
+    group.bench_function("normal", |b| {
+        b.iter(|| {
+            parent
+                .derive_public_key(black_box(KeyIndex::Normal(0)))
+                .unwrap()
+        })
+    });
+    group.bench_function("from_private_key", |b| {
+        b.iter_batched(
+            || ExtendedPrivKey::with_seed(&SEED).expect("master key"),
+            |key| ExtendedPubKey::from_private_key(&key),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn key_chain(c: &mut Criterion) {
+    let key_chain = DefaultKeyChain::new(ExtendedPrivKey::with_seed(&SEED).expect("master key"));
+    let mut group = c.benchmark_group("key_chain");
+    for (name, path) in &[
+        ("depth_5", "m/44H/0H/0H/0/0"),
+        ("depth_10", "m/0H/1/2H/2/1000000000/0H/1/2H/2/1000000000"),
+    ] {
+        group.bench_function(*name, |b| {
+            b.iter(|| {
+                key_chain
+                    .derive_private_key(ChainPath::from(black_box(*path)))
+                    .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn sibling_derivation(c: &mut Criterion) {
+    let parent = ExtendedPrivKey::with_seed(&SEED).expect("master key");
+    let mut group = c.benchmark_group("sibling_derivation");
+    // a freshly constructed key has to compute its public key for every child,
+    // which is what every normal derivation did before the parent state was cached
+    group.bench_function("uncached_parent", |b| {
+        b.iter_batched(
+            || {
+                (0..SIBLINGS)
+                    .map(|_| {
+                        ExtendedPrivKey::new(parent.private_key(), parent.chain_code().to_vec())
+                    })
+                    .collect::<Vec<_>>()
+            },
+            |parents| {
+                for (i, parent) in parents.iter().enumerate() {
+                    black_box(
+                        parent
+                            .derive_private_key(KeyIndex::Normal(i as u32))
+                            .unwrap(),
+                    );
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("cached_parent", |b| {
+        b.iter_batched(
+            || ExtendedPrivKey::new(parent.private_key(), parent.chain_code().to_vec()),
+            |parent| {
+                for i in 0..SIBLINGS {
+                    black_box(parent.derive_private_key(KeyIndex::Normal(i)).unwrap());
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn config() -> Criterion {
+    Criterion::default()
+        .sample_size(200)
+        .measurement_time(Duration::from_secs(5))
+        .noise_threshold(0.03)
+}
+
+criterion_group! {
+    name = benches;
+    config = config();
+    targets = master_key, private_derivation, public_derivation, key_chain, sibling_derivation
+}
+criterion_main!(benches);
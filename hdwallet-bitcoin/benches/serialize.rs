@@ -0,0 +1,65 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use hdwallet::{
+    traits::{Deserialize, Serialize},
+    DefaultKeyChain, ExtendedPrivKey, KeyChain,
+};
+use hdwallet_bitcoin::{Network, PrivKey, PubKey};
+use std::time::Duration;
+
+const SEED: [u8; 16] = [
+    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+];
+
+fn fetch_key() -> PrivKey {
+    let key_chain = DefaultKeyChain::new(ExtendedPrivKey::with_seed(&SEED).expect("master key"));
+    let (extended_key, derivation) = key_chain
+        .derive_private_key("m/0H/1/2H/2/1000000000".into())
+        .expect("fetch key");
+    PrivKey {
+        network: Network::MainNet,
+        script_type: Default::default(),
+        derivation,
+        parent_fingerprint: None,
+        extended_key,
+    }
+}
+
+fn xprv(c: &mut Criterion) {
+    let key = fetch_key();
+    let encoded: String = key.serialize();
+    let mut group = c.benchmark_group("xprv");
+    group.bench_function("encode", |b| {
+        b.iter(|| Serialize::<String>::serialize(black_box(&key)))
+    });
+    group.bench_function("decode", |b| {
+        b.iter(|| PrivKey::deserialize(black_box(encoded.clone())).unwrap())
+    });
+    group.finish();
+}
+
+fn xpub(c: &mut Criterion) {
+    let key = PubKey::from_private_key(&fetch_key()).unwrap();
+    let encoded: String = key.serialize();
+    let mut group = c.benchmark_group("xpub");
+    group.bench_function("encode", |b| {
+        b.iter(|| Serialize::<String>::serialize(black_box(&key)))
+    });
+    group.bench_function("decode", |b| {
+        b.iter(|| PubKey::deserialize(black_box(encoded.clone())).unwrap())
+    });
+    group.finish();
+}
+
+fn config() -> Criterion {
+    Criterion::default()
+        .sample_size(200)
+        .measurement_time(Duration::from_secs(5))
+        .noise_threshold(0.03)
+}
+
+criterion_group! {
+    name = benches;
+    config = config();
+    targets = xprv, xpub
+}
+criterion_main!(benches);
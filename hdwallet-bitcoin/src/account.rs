@@ -0,0 +1,405 @@
+use crate::{
+    serialize::parent_fingerprint, Address, AddressType, Error, Network, PrivKey, PubKey,
+    ScriptType,
+};
+use hdwallet::{secp256k1::SecretKey, zeroize::Zeroize, Derivation, KeyChain, KeyIndex};
+
+/// Gap limit of BIP-44 account discovery, the number of consecutive unused addresses after
+/// which a chain is considered unused.
+pub const DEFAULT_GAP_LIMIT: u32 = 20;
+
+/// BIP-43 purpose of a single-key account, which determines its address type.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Purpose {
+    /// BIP-44, P2PKH.
+    Bip44,
+    /// BIP-49, P2WPKH nested in P2SH.
+    Bip49,
+    /// BIP-84, P2WPKH.
+    Bip84,
+    /// BIP-86, P2TR key path spend.
+    Bip86,
+}
+
+impl Purpose {
+    /// The hardened index of the first derivation step.
+    pub fn index(self) -> u32 {
+        match self {
+            Purpose::Bip44 => 44,
+            Purpose::Bip49 => 49,
+            Purpose::Bip84 => 84,
+            Purpose::Bip86 => 86,
+        }
+    }
+
+    pub fn address_type(self) -> AddressType {
+        match self {
+            Purpose::Bip44 => AddressType::P2pkh,
+            Purpose::Bip49 => AddressType::P2shP2wpkh,
+            Purpose::Bip84 => AddressType::P2wpkh,
+            Purpose::Bip86 => AddressType::P2tr,
+        }
+    }
+
+    /// SLIP-132 script type of the account key, BIP-86 keys have no version of their own.
+    pub fn script_type(self) -> ScriptType {
+        match self {
+            Purpose::Bip44 | Purpose::Bip86 => ScriptType::Legacy,
+            Purpose::Bip49 => ScriptType::P2shP2wpkh,
+            Purpose::Bip84 => ScriptType::P2wpkh,
+        }
+    }
+}
+
+/// Chain of an account, the derivation step after the account key.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Chain {
+    /// Receive addresses, `0`.
+    External,
+    /// Change addresses, `1`.
+    Internal,
+}
+
+impl Chain {
+    fn index(self) -> usize {
+        match self {
+            Chain::External => 0,
+            Chain::Internal => 1,
+        }
+    }
+}
+
+/// Source of address usage, e.g. a blockchain index, consulted by account discovery.
+pub trait UsageOracle {
+    type Error: From<Error>;
+
+    /// Whether `address` has received funds in any transaction.
+    fn is_used(&self, address: &Address) -> Result<bool, Self::Error>;
+}
+
+/// Account of a BIP-44 style wallet at `m/purpose'/coin_type'/account'`.
+///
+/// Only the account public key is kept, addresses are derived from it. The account tracks the
+/// next index to hand out on each chain, [`Account::sync`] and [`Account::discover`] move them
+/// past the used addresses.
+///
+/// ```rust
+/// use hdwallet::{DefaultKeyChain, ExtendedPrivKey};
+/// use hdwallet_bitcoin::{Account, Chain, Network, Purpose};
+///
+/// let master_key = ExtendedPrivKey::random(&mut rand::thread_rng()).expect("master key");
+/// let key_chain = DefaultKeyChain::new(master_key);
+/// let mut account = Account::new(&key_chain, Purpose::Bip84, 0, 0, Network::MainNet).unwrap();
+/// assert_eq!(account.path(), "m/84H/0H/0H");
+/// let address = account.address(Chain::External, 0).unwrap();
+/// assert!(address.to_string().starts_with("bc1q"));
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Account {
+    pub purpose: Purpose,
+    pub coin_type: u32,
+    /// Account number, the hardened index of the third derivation step.
+    pub index: u32,
+    /// Account key, its network is the network of the addresses.
+    pub key: PubKey,
+    next_index: [u32; 2],
+}
+
+impl Account {
+    /// Derive account `index` of `coin_type` from `key_chain`, e.g. coin type 0 for Bitcoin and 1
+    /// for all test networks.
+    pub fn new(
+        key_chain: &impl KeyChain,
+        purpose: Purpose,
+        coin_type: u32,
+        index: u32,
+        network: Network,
+    ) -> Result<Self, Error> {
+        let path = format!("m/{}H/{}H/{}H", purpose.index(), coin_type, index);
+        let key = derive_public_key(key_chain, &path, purpose.script_type(), network)?;
+        Ok(Account {
+            purpose,
+            coin_type,
+            index,
+            key,
+            next_index: [0; 2],
+        })
+    }
+
+    /// BIP-44 account discovery, derive accounts from 0 until one has no used address within
+    /// `gap_limit` addresses of its external chain.
+    ///
+    /// The returned accounts are synced.
+    pub fn discover<O: UsageOracle>(
+        key_chain: &impl KeyChain,
+        purpose: Purpose,
+        coin_type: u32,
+        network: Network,
+        gap_limit: u32,
+        oracle: &O,
+    ) -> Result<Vec<Account>, O::Error> {
+        let mut accounts = Vec::new();
+        for index in 0.. {
+            let mut account = Account::new(key_chain, purpose, coin_type, index, network)?;
+            if account
+                .last_used(Chain::External, gap_limit, oracle)?
+                .is_none()
+            {
+                break;
+            }
+            account.sync(gap_limit, oracle)?;
+            accounts.push(account);
+        }
+        Ok(accounts)
+    }
+
+    /// Account path, e.g. `m/84H/0H/0H`.
+    pub fn path(&self) -> String {
+        format!(
+            "m/{}H/{}H/{}H",
+            self.purpose.index(),
+            self.coin_type,
+            self.index
+        )
+    }
+
+    /// Address at `index` of `chain`.
+    pub fn address(&self, chain: Chain, index: u32) -> Result<Address, Error> {
+        self.address_of(&self.chain_key(chain)?, index)
+    }
+
+    /// Index of the next address of `chain` to hand out.
+    pub fn next_index(&self, chain: Chain) -> u32 {
+        self.next_index[chain.index()]
+    }
+
+    /// Hand out the first address of `chain` from the next index that `oracle` reports unused,
+    /// later calls continue after it.
+    pub fn next_unused<O: UsageOracle>(
+        &mut self,
+        chain: Chain,
+        oracle: &O,
+    ) -> Result<(u32, Address), O::Error> {
+        let chain_key = self.chain_key(chain)?;
+        let mut index = self.next_index(chain);
+        loop {
+            let address = self.address_of(&chain_key, index)?;
+            let next = index.checked_add(1).ok_or(Error::KeyIndexOutOfRange)?;
+            if !oracle.is_used(&address)? {
+                self.next_index[chain.index()] = next;
+                return Ok((index, address));
+            }
+            index = next;
+        }
+    }
+
+    /// Scan both chains up to `gap_limit` consecutive unused addresses, and move the next index
+    /// of each chain after its last used address.
+    pub fn sync<O: UsageOracle>(&mut self, gap_limit: u32, oracle: &O) -> Result<(), O::Error> {
+        for chain in [Chain::External, Chain::Internal] {
+            if let Some(last_used) = self.last_used(chain, gap_limit, oracle)? {
+                let next_index = &mut self.next_index[chain.index()];
+                *next_index = (*next_index).max(last_used + 1);
+            }
+        }
+        Ok(())
+    }
+
+    /// Index of the last used address of `chain` before `gap_limit` consecutive unused ones.
+    fn last_used<O: UsageOracle>(
+        &self,
+        chain: Chain,
+        gap_limit: u32,
+        oracle: &O,
+    ) -> Result<Option<u32>, O::Error> {
+        let chain_key = self.chain_key(chain)?;
+        let mut last_used = None;
+        let mut index = 0;
+        while index - last_used.map_or(0, |last_used| last_used + 1) < gap_limit {
+            if oracle.is_used(&self.address_of(&chain_key, index)?)? {
+                last_used = Some(index);
+            }
+            index += 1;
+        }
+        Ok(last_used)
+    }
+
+    fn chain_key(&self, chain: Chain) -> Result<PubKey, Error> {
+        self.key
+            .derive_public_key(KeyIndex::Normal(chain.index() as u32))
+    }
+
+    fn address_of(&self, chain_key: &PubKey, index: u32) -> Result<Address, Error> {
+        if index >= 1 << 31 {
+            return Err(Error::KeyIndexOutOfRange);
+        }
+        let key = chain_key.derive_public_key(KeyIndex::Normal(index))?;
+        key.address(self.purpose.address_type())
+    }
+}
+
+/// Public key at `path` of `key_chain`, the private keys met on the way are zeroized.
+pub(crate) fn derive_public_key(
+    key_chain: &impl KeyChain,
+    path: &str,
+    script_type: ScriptType,
+    network: Network,
+) -> Result<PubKey, Error> {
+    let (extended_key, mut derivation) = key_chain.derive_private_key(path.into())?;
+    let mut priv_key = PrivKey {
+        network,
+        script_type,
+        // keep the parent fingerprint only
+        derivation: Derivation {
+            depth: derivation.depth,
+            parent_key: None,
+            key_index: derivation.key_index,
+        },
+        parent_fingerprint: Some(parent_fingerprint(&derivation, None)),
+        extended_key,
+    };
+    let key = PubKey::from_private_key(&priv_key);
+    derivation.zeroize();
+    priv_key.zeroize();
+    key
+}
+
+/// Private key at `path` of `key_chain`, the extended keys met on the way are zeroized.
+pub(crate) fn derive_secret_key(key_chain: &impl KeyChain, path: &str) -> Result<SecretKey, Error> {
+    let (mut extended_key, mut derivation) = key_chain.derive_private_key(path.into())?;
+    let secret_key = extended_key.private_key();
+    derivation.zeroize();
+    extended_key.zeroize();
+    Ok(secret_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hdwallet::{traits::Serialize, DefaultKeyChain, ExtendedPrivKey};
+    use std::collections::HashSet;
+
+    /// Key chain of BIP-39 mnemonic `abandon abandon ... about` without passphrase.
+    fn key_chain() -> DefaultKeyChain {
+        let seed = hex::decode("5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc19a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4").expect("decode");
+        DefaultKeyChain::new(ExtendedPrivKey::with_seed(&seed).expect("master key"))
+    }
+
+    /// Used addresses kept in memory.
+    #[derive(Default)]
+    struct UsedAddresses(HashSet<Address>);
+
+    impl UsageOracle for UsedAddresses {
+        type Error = Error;
+
+        fn is_used(&self, address: &Address) -> Result<bool, Error> {
+            Ok(self.0.contains(address))
+        }
+    }
+
+    #[test]
+    fn test_account_addresses() {
+        let key_chain = key_chain();
+        for (purpose, external, internal) in &[
+            (
+                Purpose::Bip44,
+                "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA",
+                "1J3J6EvPrv8q6AC3VCjWV45Uf3nssNMRtH",
+            ),
+            (
+                Purpose::Bip49,
+                "37VucYSaXLCAsxYyAPfbSi9eh4iEcbShgf",
+                "34K56kSjgUCUSD8GTtuF7c9Zzwokbs6uZ7",
+            ),
+            (
+                Purpose::Bip84,
+                "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu",
+                "bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el",
+            ),
+            (
+                Purpose::Bip86,
+                "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+                "bc1p3qkhfews2uk44qtvauqyr2ttdsw7svhkl9nkm9s9c3x4ax5h60wqwruhk7",
+            ),
+        ] {
+            let account = Account::new(&key_chain, *purpose, 0, 0, Network::MainNet).unwrap();
+            assert_eq!(
+                account.address(Chain::External, 0).unwrap().to_string(),
+                *external
+            );
+            assert_eq!(
+                account.address(Chain::Internal, 0).unwrap().to_string(),
+                *internal
+            );
+        }
+        let account = Account::new(&key_chain, Purpose::Bip84, 0, 0, Network::MainNet).unwrap();
+        assert_eq!(account.path(), "m/84H/0H/0H");
+        assert_eq!(account.key.derivation.parent_key, None);
+        assert_eq!(
+            Serialize::<String>::serialize(&account.key),
+            "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs"
+        );
+        assert_eq!(
+            account.address(Chain::External, 1 << 31),
+            Err(Error::KeyIndexOutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_discovery() {
+        let key_chain = key_chain();
+        let address = |account, chain, index| {
+            Account::new(&key_chain, Purpose::Bip84, 1, account, Network::TestNet)
+                .unwrap()
+                .address(chain, index)
+                .unwrap()
+        };
+        let mut oracle = UsedAddresses::default();
+        for (account, chain, index) in &[
+            (0, Chain::External, 0),
+            (0, Chain::External, 5),
+            (0, Chain::Internal, 0),
+            (1, Chain::External, 19),
+            // beyond the gap limit of account 2
+            (2, Chain::External, 20),
+        ] {
+            oracle.0.insert(address(*account, *chain, *index));
+        }
+
+        let accounts = Account::discover(
+            &key_chain,
+            Purpose::Bip84,
+            1,
+            Network::TestNet,
+            DEFAULT_GAP_LIMIT,
+            &oracle,
+        )
+        .unwrap();
+        assert_eq!(accounts.len(), 2);
+        assert_eq!(accounts[0].next_index(Chain::External), 6);
+        assert_eq!(accounts[0].next_index(Chain::Internal), 1);
+        assert_eq!(accounts[1].next_index(Chain::External), 20);
+        assert_eq!(accounts[1].next_index(Chain::Internal), 0);
+        let accounts =
+            Account::discover(&key_chain, Purpose::Bip84, 1, Network::TestNet, 10, &oracle)
+                .unwrap();
+        assert_eq!(accounts.len(), 1);
+
+        // hand out fresh receive addresses, skipping the used ones
+        let mut account = accounts[0].clone();
+        oracle.0.insert(address(0, Chain::External, 7));
+        assert_eq!(
+            account.next_unused(Chain::External, &oracle),
+            Ok((6, address(0, Chain::External, 6)))
+        );
+        assert_eq!(
+            account.next_unused(Chain::External, &oracle),
+            Ok((8, address(0, Chain::External, 8)))
+        );
+        assert_eq!(
+            account.next_unused(Chain::Internal, &oracle),
+            Ok((1, address(0, Chain::Internal, 1)))
+        );
+        assert_eq!(account.next_index(Chain::External), 9);
+    }
+}
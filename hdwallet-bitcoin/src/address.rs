@@ -0,0 +1,581 @@
+use crate::{
+    hash::{hash160, sha256},
+    serialize::{encode_checksum, verify_checksum},
+    taproot::output_key,
+    Error, Network, PubKey,
+};
+use base58::{FromBase58, ToBase58};
+use bech32::{u5, Bech32Writer, FromBase32, ToBase32, Variant, WriteBase32};
+use hdwallet::secp256k1::PublicKey;
+use std::{fmt, str::FromStr};
+
+/// Maximum length of a bech32 string, BIP-173.
+const BECH32_MAX_LEN: usize = 90;
+
+/// Standard single-key address types.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum AddressType {
+    /// Legacy pay-to-pubkey-hash, BIP-44.
+    P2pkh,
+    /// Pay-to-witness-pubkey-hash nested in P2SH, BIP-49.
+    P2shP2wpkh,
+    /// Native SegWit v0, BIP-84.
+    P2wpkh,
+    /// Taproot key path spend without script tree, BIP-86.
+    P2tr,
+}
+
+/// Data encoded in an address, which determines its `scriptPubKey`.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Payload {
+    PubkeyHash([u8; 20]),
+    ScriptHash([u8; 20]),
+    WitnessProgram { version: u8, program: Vec<u8> },
+}
+
+/// Bitcoin address.
+///
+/// Parsing accepts any witness version, so the address of a future SegWit version is valid as
+/// long as it is encoded per BIP-350.
+///
+/// ```rust
+/// use hdwallet_bitcoin::{Address, Network, Payload};
+///
+/// let address: Address = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4".parse().unwrap();
+/// assert_eq!(address.network(), Network::MainNet);
+/// assert!(matches!(address.payload(), Payload::WitnessProgram { version: 0, .. }));
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Address {
+    pub(crate) network: Network,
+    pub(crate) payload: Payload,
+}
+
+impl Address {
+    /// Address of `payload` on `network`, [`Error::InvalidAddress`] for a witness program of a
+    /// version above 16 or of an invalid length and [`Error::UnknownVersion`] for a
+    /// [`Network::Unknown`] network, which has no address prefixes.
+    pub fn new(network: Network, payload: Payload) -> Result<Self, Error> {
+        network.params()?;
+        if let Payload::WitnessProgram { version, program } = &payload {
+            if !valid_witness_program(*version, program) {
+                return Err(Error::InvalidAddress);
+            }
+        }
+        Ok(Address { network, payload })
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    pub fn payload(&self) -> &Payload {
+        &self.payload
+    }
+
+    /// Legacy pay-to-pubkey-hash address of the compressed public key.
+    pub fn p2pkh(public_key: &PublicKey, network: Network) -> Result<Self, Error> {
+        Address::new(
+            network,
+            Payload::PubkeyHash(hash160(&public_key.serialize())),
+        )
+    }
+
+    /// P2WPKH nested in P2SH, the redeem script is `0 <hash160(public_key)>`.
+    pub fn p2sh_p2wpkh(public_key: &PublicKey, network: Network) -> Result<Self, Error> {
+        let mut redeem_script = vec![0x00, 0x14];
+        redeem_script.extend_from_slice(&hash160(&public_key.serialize()));
+        Address::p2sh(&redeem_script, network)
+    }
+
+    /// Native SegWit v0 pay-to-witness-pubkey-hash.
+    pub fn p2wpkh(public_key: &PublicKey, network: Network) -> Result<Self, Error> {
+        Address::new(
+            network,
+            Payload::WitnessProgram {
+                version: 0,
+                program: hash160(&public_key.serialize()).to_vec(),
+            },
+        )
+    }
+
+    /// Taproot output of `internal_key` tweaked without script tree, per BIP-86.
+    pub fn p2tr(internal_key: &PublicKey, network: Network) -> Result<Self, Error> {
+        let (internal_key, _) = internal_key.x_only_public_key();
+        let (output_key, _) = output_key(&internal_key, None).expect("tweaked key is valid");
+        Address::new(
+            network,
+            Payload::WitnessProgram {
+                version: 1,
+                program: output_key.serialize().to_vec(),
+            },
+        )
+    }
+
+    /// Pay-to-script-hash address of `redeem_script`.
+    pub fn p2sh(redeem_script: &[u8], network: Network) -> Result<Self, Error> {
+        Address::new(network, Payload::ScriptHash(hash160(redeem_script)))
+    }
+
+    /// Native SegWit v0 pay-to-witness-script-hash address of `witness_script`.
+    pub fn p2wsh(witness_script: &[u8], network: Network) -> Result<Self, Error> {
+        Address::new(
+            network,
+            Payload::WitnessProgram {
+                version: 0,
+                program: sha256(witness_script).to_vec(),
+            },
+        )
+    }
+
+    /// P2WSH nested in P2SH, the redeem script is `0 <sha256(witness_script)>`.
+    pub fn p2sh_p2wsh(witness_script: &[u8], network: Network) -> Result<Self, Error> {
+        let mut redeem_script = vec![0x00, 0x20];
+        redeem_script.extend_from_slice(&sha256(witness_script));
+        Address::p2sh(&redeem_script, network)
+    }
+
+    /// Address of `address_type` paying to `public_key`.
+    pub fn from_public_key(
+        public_key: &PublicKey,
+        address_type: AddressType,
+        network: Network,
+    ) -> Result<Self, Error> {
+        match address_type {
+            AddressType::P2pkh => Address::p2pkh(public_key, network),
+            AddressType::P2shP2wpkh => Address::p2sh_p2wpkh(public_key, network),
+            AddressType::P2wpkh => Address::p2wpkh(public_key, network),
+            AddressType::P2tr => Address::p2tr(public_key, network),
+        }
+    }
+
+    /// Parse an address and check it belongs to `network`, which may be a custom network.
+    pub fn parse(s: &str, network: Network) -> Result<Self, Error> {
+        Address::decode(s, &[network]).map_err(|err| match err {
+            Error::UnknownVersion => Error::InvalidAddress,
+            err => err,
+        })
+    }
+
+    /// Serialized `scriptPubKey` locking funds to the address.
+    pub fn script_pubkey(&self) -> Vec<u8> {
+        match &self.payload {
+            Payload::PubkeyHash(hash) => {
+                // OP_DUP OP_HASH160 <hash> OP_EQUALVERIFY OP_CHECKSIG
+                let mut script = vec![0x76, 0xa9, 0x14];
+                script.extend_from_slice(hash);
+                script.extend_from_slice(&[0x88, 0xac]);
+                script
+            }
+            Payload::ScriptHash(hash) => {
+                // OP_HASH160 <hash> OP_EQUAL
+                let mut script = vec![0xa9, 0x14];
+                script.extend_from_slice(hash);
+                script.push(0x87);
+                script
+            }
+            Payload::WitnessProgram { version, program } => {
+                // OP_0 or OP_1..OP_16, then the program push
+                let op_version = if *version == 0 { 0 } else { 0x50 + version };
+                let mut script = vec![op_version, program.len() as u8];
+                script.extend_from_slice(program);
+                script
+            }
+        }
+    }
+
+    /// Decode an address of one of `networks`, the first match wins.
+    fn decode(s: &str, networks: &[Network]) -> Result<Self, Error> {
+        // the separator `1` is also a base58 digit, so check the human-readable part first
+        let lower = s.to_ascii_lowercase();
+        let segwit_network = networks.iter().find(|network| match network.params() {
+            Ok(params) => {
+                let hrp = params.bech32_hrp;
+                lower.starts_with(hrp) && lower[hrp.len()..].starts_with('1')
+            }
+            Err(_) => false,
+        });
+        match segwit_network {
+            Some(network) => Address::from_bech32(s, *network),
+            None => Address::from_base58(s, networks),
+        }
+    }
+
+    fn from_base58(s: &str, networks: &[Network]) -> Result<Self, Error> {
+        let data = s.from_base58().map_err(|_| Error::InvalidBase58)?;
+        if data.len() != 25 {
+            return Err(Error::InvalidLength);
+        }
+        verify_checksum(&data)?;
+        let mut hash = [0u8; 20];
+        hash.copy_from_slice(&data[1..21]);
+        for network in networks {
+            let params = match network.params() {
+                Ok(params) => params,
+                Err(_) => continue,
+            };
+            let payload = if data[0] == params.p2pkh_prefix {
+                Payload::PubkeyHash(hash)
+            } else if data[0] == params.p2sh_prefix {
+                Payload::ScriptHash(hash)
+            } else {
+                continue;
+            };
+            return Address::new(*network, payload);
+        }
+        Err(Error::UnknownVersion)
+    }
+
+    fn from_bech32(s: &str, network: Network) -> Result<Self, Error> {
+        if s.len() > BECH32_MAX_LEN {
+            return Err(Error::InvalidLength);
+        }
+        let (hrp, data, variant) = bech32::decode(s).map_err(|_| Error::InvalidAddress)?;
+        if hrp != network.params()?.bech32_hrp {
+            return Err(Error::UnknownVersion);
+        }
+        let (version, program) = data.split_first().ok_or(Error::InvalidAddress)?;
+        let version = version.to_u8();
+        let program = Vec::<u8>::from_base32(program).map_err(|_| Error::InvalidAddress)?;
+        if variant != witness_variant(version) {
+            return Err(Error::InvalidAddress);
+        }
+        Address::new(network, Payload::WitnessProgram { version, program })
+    }
+}
+
+/// Witness versions 0 to 16 with a program length valid for the version, BIP-141.
+fn valid_witness_program(version: u8, program: &[u8]) -> bool {
+    match version {
+        0 => program.len() == 20 || program.len() == 32,
+        1..=16 => (2..=40).contains(&program.len()),
+        _ => false,
+    }
+}
+
+/// Checksum variant of a witness version, BIP-350.
+fn witness_variant(version: u8) -> Variant {
+    if version == 0 {
+        Variant::Bech32
+    } else {
+        Variant::Bech32m
+    }
+}
+
+impl FromStr for Address {
+    type Err = Error;
+
+    /// Parse an address of a registered network, signet addresses are reported as testnet ones.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Address::decode(s, &[Network::MainNet, Network::TestNet, Network::Regtest])
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // checked on construction
+        let params = self.network.params().expect("network parameters");
+        let (prefix, hash) = match &self.payload {
+            Payload::PubkeyHash(hash) => (params.p2pkh_prefix, hash),
+            Payload::ScriptHash(hash) => (params.p2sh_prefix, hash),
+            Payload::WitnessProgram { version, program } => {
+                // the program is checked on construction, but the HRP of a custom network may
+                // not be valid bech32, so encode it as is instead of failing
+                let hrp = params.bech32_hrp.to_ascii_lowercase();
+                let mut s = String::new();
+                let mut writer = Bech32Writer::new(&hrp, witness_variant(*version), &mut s)?;
+                writer.write_u5(u5::try_from_u8(*version).expect("witness version"))?;
+                writer.write(&program.to_base32())?;
+                writer.finalize()?;
+                return f.write_str(&s);
+            }
+        };
+        let mut buf = Vec::with_capacity(25);
+        buf.push(prefix);
+        buf.extend_from_slice(hash);
+        encode_checksum(&mut buf);
+        f.write_str(&buf.to_base58())
+    }
+}
+
+impl PubKey {
+    /// Address of `address_type` paying to this key, on the key's network.
+    ///
+    /// [`Error::UnknownVersion`] for a key decoded with unregistered version bytes, set its
+    /// network to a custom one first.
+    pub fn address(&self, address_type: AddressType) -> Result<Address, Error> {
+        Address::from_public_key(&self.extended_key.public_key, address_type, self.network)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NetworkParams, PrivKey};
+    use hdwallet::{traits::Deserialize, ExtendedPrivKey};
+
+    fn script_pubkey(s: &str) -> String {
+        let address: Address = s.parse().expect(s);
+        hex::encode(address.script_pubkey())
+    }
+
+    #[test]
+    fn test_bip173_vectors() {
+        for (address, script) in &[
+            (
+                "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4",
+                "0014751e76e8199196d454941c45d1b3a323f1433bd6",
+            ),
+            (
+                "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7",
+                "00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262",
+            ),
+            (
+                "tb1qqqqqp399et2xygdj5xreqhjjvcmzhxw4aywxecjdzew6hylgvsesrxh6hy",
+                "0020000000c4a5cad46221b2a187905e5266362b99d5e91c6ce24d165dab93e86433",
+            ),
+        ] {
+            assert_eq!(&script_pubkey(address), script);
+            let parsed: Address = address.parse().unwrap();
+            assert_eq!(parsed.to_string(), address.to_lowercase());
+        }
+        for invalid in &[
+            // invalid human-readable part
+            "tc1qw508d6qejxtdg4y5r3zarvary0c5xw7kg3g4ty",
+            // invalid checksum
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5",
+            // invalid witness version
+            "BC13W508D6QEJXTDG4Y5R3ZARVARY0C5XW7KN40WF2",
+            // invalid program length
+            "bc1rw5uspcuh",
+            "bc10w508d6qejxtdg4y5r3zarvary0c5xw7kw508d6qejxtdg4y5r3zarvary0c5xw7kw5rljs90",
+            // invalid program length for witness version 0
+            "BC1QR508D6QEJXTDG4Y5R3ZARVARYV98GJ9P",
+            // mixed case
+            "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sL5k7",
+            // zero padding of more than 4 bits
+            "bc1zw508d6qejxtdg4y5r3zarvaryvqyzf3du",
+            // non-zero padding in 8-to-5 conversion
+            "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3pjxtptv",
+            // empty data section
+            "bc1gmk9yu",
+        ] {
+            assert!(invalid.parse::<Address>().is_err(), "{}", invalid);
+        }
+        // P2WSH of `<generator point> OP_CHECKSIG`
+        let witness_script =
+            hex::decode("210279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798ac")
+                .unwrap();
+        assert_eq!(
+            Address::p2wsh(&witness_script, Network::TestNet)
+                .unwrap()
+                .to_string(),
+            "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7"
+        );
+    }
+
+    #[test]
+    fn test_bip350_vectors() {
+        for (address, script) in &[
+            ("bc1pw508d6qejxtdg4y5r3zarvary0c5xw7kw508d6qejxtdg4y5r3zarvary0c5xw7kt5nd6y", "5128751e76e8199196d454941c45d1b3a323f1433bd6751e76e8199196d454941c45d1b3a323f1433bd6"),
+            ("BC1SW50QGDZ25J", "6002751e"),
+            ("bc1zw508d6qejxtdg4y5r3zarvaryvaxxpcs", "5210751e76e8199196d454941c45d1b3a323"),
+            ("tb1pqqqqp399et2xygdj5xreqhjjvcmzhxw4aywxecjdzew6hylgvsesf3hn0c", "5120000000c4a5cad46221b2a187905e5266362b99d5e91c6ce24d165dab93e86433"),
+            ("bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0", "512079be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"),
+        ] {
+            assert_eq!(&script_pubkey(address), script);
+            let parsed: Address = address.parse().unwrap();
+            assert_eq!(parsed.to_string(), address.to_lowercase());
+        }
+        for invalid in &[
+            // invalid human-readable part
+            "tc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq5zuyut",
+            // bech32 checksum for witness version 1+
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd",
+            "tb1z0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqglt7rf",
+            "BC1S0XLXVLHEMJA6C4DQV22UAPCTQUPFHLXM9H8Z3K2E72Q4K9HCZ7VQ54WELL",
+            // bech32m checksum for witness version 0
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kemeawh",
+            "tb1q0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq24jc47",
+            // invalid character in checksum
+            "bc1p38j9r5y49hruaue7wxjce0updqjuyyx0kh56v8s25huc6995vvpql3jow4",
+            // invalid witness version
+            "BC130XLXVLHEMJA6C4DQV22UAPCTQUPFHLXM9H8Z3K2E72Q4K9HCZ7VQ7ZWS8R",
+            // invalid program length
+            "bc1pw5dgrnzv",
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7v8n0nx0muaewav253zgeav",
+            // invalid program length for witness version 0
+            "BC1QR508D6QEJXTDG4Y5R3ZARVARYV98GJ9P",
+            // mixed case
+            "tb1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq47Zagq",
+            // zero padding of more than 4 bits
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7v07qwwzcrf",
+            // non-zero padding in 8-to-5 conversion
+            "tb1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vpggkg4j",
+            // empty data section
+            "bc1gmk9yu",
+        ] {
+            assert!(invalid.parse::<Address>().is_err(), "{}", invalid);
+        }
+        for (version, length) in &[(17, 32), (0, 21), (1, 41)] {
+            let payload = Payload::WitnessProgram {
+                version: *version,
+                program: vec![0; *length],
+            };
+            assert_eq!(
+                Address::new(Network::MainNet, payload),
+                Err(Error::InvalidAddress)
+            );
+        }
+    }
+
+    /// Master key of BIP-39 mnemonic `abandon abandon ... about` without passphrase.
+    fn bip39_master_key(network: Network) -> PrivKey {
+        let seed = hex::decode("5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc19a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4").expect("decode");
+        PrivKey::from_master_key(
+            ExtendedPrivKey::with_seed(&seed).expect("master key"),
+            network,
+        )
+    }
+
+    fn derive_address(network: Network, path: &str, address_type: AddressType) -> String {
+        let key = bip39_master_key(network)
+            .derive_path(path.into())
+            .expect("derive");
+        PubKey::from_private_key(&key)
+            .unwrap()
+            .address(address_type)
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_bip44_bip49_addresses() {
+        assert_eq!(
+            derive_address(Network::MainNet, "m/44H/0H/0H/0/0", AddressType::P2pkh),
+            "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA"
+        );
+        assert_eq!(
+            derive_address(Network::TestNet, "m/49H/1H/0H/0/0", AddressType::P2shP2wpkh),
+            "2Mww8dCYPUpKHofjgcXcBCEGmniw9CoaiD2"
+        );
+        // BIP-32 test vector 1, m and m/0H
+        for (xpub, address) in &[
+            ("xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8", "15mKKb2eos1hWa6tisdPwwDC1a5J1y9nma"),
+            ("xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw", "19Q2WoS5hSS6T8GjhK8KZLMgmWaq4neXrh"),
+        ] {
+            let pub_key = PubKey::deserialize(xpub.to_string()).expect("deserialize");
+            let expected = pub_key.address(AddressType::P2pkh).expect("address");
+            assert_eq!(&expected.to_string(), address);
+            assert_eq!(Address::parse(address, Network::MainNet), Ok(expected));
+        }
+        assert_eq!(
+            Address::parse("2Mww8dCYPUpKHofjgcXcBCEGmniw9CoaiD2", Network::MainNet),
+            Err(Error::InvalidAddress)
+        );
+        assert_eq!(
+            "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabB".parse::<Address>(),
+            Err(Error::MisChecksum)
+        );
+    }
+
+    #[test]
+    fn test_bip84_addresses() {
+        for (path, address) in &[
+            (
+                "m/84H/0H/0H/0/0",
+                "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu",
+            ),
+            (
+                "m/84H/0H/0H/0/1",
+                "bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g",
+            ),
+            (
+                "m/84H/0H/0H/1/0",
+                "bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el",
+            ),
+        ] {
+            assert_eq!(
+                &derive_address(Network::MainNet, path, AddressType::P2wpkh),
+                address
+            );
+        }
+    }
+
+    #[test]
+    fn test_bip86_addresses() {
+        for (path, address, output_key) in &[
+            (
+                "m/86H/0H/0H/0/0",
+                "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+                "a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c",
+            ),
+            (
+                "m/86H/0H/0H/0/1",
+                "bc1p4qhjn9zdvkux4e44uhx8tc55attvtyu358kutcqkudyccelu0was9fqzwh",
+                "a82f29944d65b86ae6b5e5cc75e294ead6c59391a1edc5e016e3498c67fc7bbb",
+            ),
+            (
+                "m/86H/0H/0H/1/0",
+                "bc1p3qkhfews2uk44qtvauqyr2ttdsw7svhkl9nkm9s9c3x4ax5h60wqwruhk7",
+                "882d74e5d0572d5a816cef0041a96b6c1de832f6f9676d9605c44d5e9a97d3dc",
+            ),
+        ] {
+            assert_eq!(
+                &derive_address(Network::MainNet, path, AddressType::P2tr),
+                address
+            );
+            assert_eq!(script_pubkey(address), format!("5120{}", output_key));
+        }
+    }
+
+    #[test]
+    fn test_network_addresses() {
+        let key = bip39_master_key(Network::Regtest)
+            .derive_path("m/84H/1H/0H/0/0".into())
+            .expect("derive");
+        let key = PubKey::from_private_key(&key).expect("public key");
+        let address = key.address(AddressType::P2wpkh).expect("address");
+        let encoded = address.to_string();
+        assert!(encoded.starts_with("bcrt1q"), "{}", encoded);
+        assert_eq!(encoded.parse::<Address>(), Ok(address.clone()));
+        assert!(Address::parse(&encoded, Network::TestNet).is_err());
+
+        // signet shares the testnet encoding
+        let testnet = Address::new(Network::TestNet, address.payload().clone()).expect("address");
+        let signet = Address::parse(&testnet.to_string(), Network::Signet).expect("parse");
+        assert_eq!(signet.network, Network::Signet);
+        assert_eq!(signet.to_string(), testnet.to_string());
+
+        let litecoin = Network::Custom(NetworkParams {
+            p2pkh_prefix: 0x30,
+            p2sh_prefix: 0x32,
+            bech32_hrp: "ltc",
+            ..NetworkParams::MAINNET
+        });
+        for (address_type, prefix) in &[
+            (AddressType::P2pkh, "L"),
+            (AddressType::P2shP2wpkh, "M"),
+            (AddressType::P2wpkh, "ltc1q"),
+            (AddressType::P2tr, "ltc1p"),
+        ] {
+            let address =
+                Address::from_public_key(&key.extended_key.public_key, *address_type, litecoin)
+                    .expect("address");
+            let encoded = address.to_string();
+            assert!(encoded.starts_with(prefix), "{}", encoded);
+            assert_eq!(Address::parse(&encoded, litecoin), Ok(address));
+            assert!(encoded.parse::<Address>().is_err());
+        }
+
+        // a custom HRP that isn't valid bech32 still encodes
+        let invalid_hrp = Network::Custom(NetworkParams {
+            bech32_hrp: "",
+            ..NetworkParams::MAINNET
+        });
+        let encoded = Address::p2wpkh(&key.extended_key.public_key, invalid_hrp)
+            .expect("address")
+            .to_string();
+        assert!(encoded.starts_with("1q"), "{}", encoded);
+    }
+}
@@ -0,0 +1,73 @@
+//! Standard padded base64, the text encoding of PSBTs and message signatures.
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub(crate) fn encode(data: &[u8]) -> String {
+    let mut s = String::with_capacity(data.len() / 3 * 4 + 4);
+    for chunk in data.chunks(3) {
+        let mut buf = [0u8; 3];
+        buf[..chunk.len()].copy_from_slice(chunk);
+        let n = u32::from(buf[0]) << 16 | u32::from(buf[1]) << 8 | u32::from(buf[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                s.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                s.push('=');
+            }
+        }
+    }
+    s
+}
+
+/// Decode padded base64, non-zero trailing bits are rejected.
+pub(crate) fn decode(s: &str) -> Option<Vec<u8>> {
+    let chunks = s.as_bytes().chunks_exact(4);
+    if !chunks.remainder().is_empty() {
+        return None;
+    }
+    let len = chunks.len();
+    let mut data = Vec::with_capacity(len * 3);
+    for (i, chunk) in chunks.enumerate() {
+        let padding = chunk.iter().rev().take_while(|c| **c == b'=').count();
+        if padding > 2 || (padding > 0 && i + 1 != len) {
+            return None;
+        }
+        let mut n = 0u32;
+        for c in &chunk[..4 - padding] {
+            let value = ALPHABET.iter().position(|a| a == c)?;
+            n = n << 6 | value as u32;
+        }
+        n <<= 6 * padding;
+        let bytes = [(n >> 16) as u8, (n >> 8) as u8, n as u8];
+        if bytes[3 - padding..].iter().any(|b| *b != 0) {
+            return None;
+        }
+        data.extend_from_slice(&bytes[..3 - padding]);
+    }
+    Some(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64() {
+        // RFC 4648 test vectors
+        for (data, encoded) in &[
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(&encode(data.as_bytes()), encoded);
+            assert_eq!(decode(encoded), Some(data.as_bytes().to_vec()));
+        }
+        for s in &["Zg", "Zg=a", "Z===", "Zh==", "Zm9v\n"] {
+            assert!(decode(s).is_none(), "{}", s);
+        }
+    }
+}
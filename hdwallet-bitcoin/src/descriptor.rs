@@ -0,0 +1,528 @@
+mod checksum;
+mod key;
+mod policy;
+
+use crate::{hash::hash160, Address, Error, Network, Payload};
+use checksum::checksum;
+use key::Context;
+use std::{fmt, str::FromStr};
+
+pub use key::{DescriptorKey, ExtendedKey, SingleKey, Wildcard};
+pub use policy::WalletPolicy;
+
+/// Maximum size of a P2SH redeem script.
+pub(crate) const MAX_REDEEM_SCRIPT_SIZE: usize = 520;
+/// Maximum number of keys of `multi()` in `wsh()`.
+pub(crate) const MAX_WSH_MULTI_KEYS: usize = 20;
+/// Maximum number of keys of a bare `multi()`.
+const MAX_BARE_MULTI_KEYS: usize = 3;
+
+/// Output script descriptor, BIP-380 to BIP-386 without script trees.
+///
+/// `tr()` with a script tree is [`Error::UnsupportedDescriptor`].
+///
+/// Parsing checks the `#checksum` suffix when present, printing always appends it unless
+/// formatted with `{:#}`. Printed hardened steps use `h`.
+///
+/// ```rust
+/// use hdwallet_bitcoin::{Descriptor, Network};
+///
+/// let descriptor: Descriptor = "wpkh([73c5da0a/84h/0h/0h]xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V/0/*)"
+///     .parse()
+///     .unwrap();
+/// let address = descriptor.address(0, Network::MainNet).unwrap();
+/// assert_eq!(address.to_string(), "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu");
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Descriptor {
+    /// `pk(KEY)`, pay to a bare public key.
+    Pk(DescriptorKey),
+    /// `pkh(KEY)`
+    Pkh(DescriptorKey),
+    /// `wpkh(KEY)`
+    Wpkh(DescriptorKey),
+    /// `sh(wpkh(KEY))`
+    ShWpkh(DescriptorKey),
+    /// `tr(KEY)`, key path spend only.
+    Tr(DescriptorKey),
+    /// Bare `multi(...)` or `sortedmulti(...)`.
+    Multi(Multi),
+    /// `sh(multi(...))`
+    Sh(Multi),
+    /// `wsh(multi(...))`
+    Wsh(Multi),
+    /// `sh(wsh(multi(...)))`
+    ShWsh(Multi),
+}
+
+/// `multi(k,KEY_1,...,KEY_n)` or `sortedmulti(k,KEY_1,...,KEY_n)`, BIP-383.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Multi {
+    pub threshold: usize,
+    pub keys: Vec<DescriptorKey>,
+    /// `sortedmulti`, the public keys are sorted per BIP-67 in the script.
+    pub sorted: bool,
+}
+
+impl Multi {
+    /// `OP_k <keys> OP_n OP_CHECKMULTISIG` at `index`.
+    pub fn script(&self, index: u32) -> Result<Vec<u8>, Error> {
+        let mut keys = self
+            .keys
+            .iter()
+            .map(|key| key.serialize_public_key(index))
+            .collect::<Result<Vec<_>, _>>()?;
+        if self.sorted {
+            keys.sort();
+        }
+        Ok(multisig_script(self.threshold, &keys))
+    }
+
+    fn parse(s: &str, context: Context) -> Result<Self, Error> {
+        let (name, args) = split_call(s)?;
+        let sorted = match name {
+            "multi" => false,
+            "sortedmulti" => true,
+            _ => return Err(Error::InvalidDescriptor),
+        };
+        let (threshold, keys) = args.split_first().ok_or(Error::InvalidDescriptor)?;
+        let multi = Multi {
+            threshold: threshold.parse().map_err(|_| Error::InvalidDescriptor)?,
+            keys: keys
+                .iter()
+                .map(|key| DescriptorKey::parse(key, context))
+                .collect::<Result<Vec<_>, _>>()?,
+            sorted,
+        };
+        if multi.threshold == 0 || multi.threshold > multi.keys.len() {
+            return Err(Error::InvalidDescriptor);
+        }
+        Ok(multi)
+    }
+
+    /// Script size, which doesn't depend on the index.
+    fn script_size(&self) -> usize {
+        let keys: usize = self
+            .keys
+            .iter()
+            .map(|key| if key.is_compressed() { 34 } else { 66 })
+            .sum();
+        keys + 3
+    }
+}
+
+/// `OP_k <keys> OP_n OP_CHECKMULTISIG` with the serialized `keys` in the given order.
+pub(crate) fn multisig_script(threshold: usize, keys: &[Vec<u8>]) -> Vec<u8> {
+    let mut script = Vec::new();
+    push_int(&mut script, threshold);
+    for key in keys {
+        script.push(key.len() as u8);
+        script.extend_from_slice(key);
+    }
+    push_int(&mut script, keys.len());
+    script.push(0xae);
+    script
+}
+
+/// Push a number of at most 20 as `OP_1`..`OP_16` or a one byte push.
+fn push_int(script: &mut Vec<u8>, n: usize) {
+    if n <= 16 {
+        script.push(0x50 + n as u8);
+    } else {
+        script.extend_from_slice(&[0x01, n as u8]);
+    }
+}
+
+/// Split `name(arg,...)` into the name and the top level arguments.
+fn split_call(s: &str) -> Result<(&str, Vec<&str>), Error> {
+    let (name, args) = s
+        .strip_suffix(')')
+        .and_then(|s| s.split_once('('))
+        .ok_or(Error::InvalidDescriptor)?;
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut split = Vec::new();
+    for (i, c) in args.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.checked_sub(1).ok_or(Error::InvalidDescriptor)?,
+            ',' if depth == 0 => {
+                split.push(&args[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return Err(Error::InvalidDescriptor);
+    }
+    split.push(&args[start..]);
+    Ok((name, split))
+}
+
+/// Parse the argument of a single argument call.
+fn single_arg(args: Vec<&str>) -> Result<&str, Error> {
+    match args.as_slice() {
+        [arg] => Ok(arg),
+        _ => Err(Error::InvalidDescriptor),
+    }
+}
+
+impl Descriptor {
+    /// Whether any key ends with a wildcard, i.e. the descriptor describes a range of scripts.
+    pub fn is_ranged(&self) -> bool {
+        self.keys().iter().any(|key| key.is_ranged())
+    }
+
+    /// Key expressions in the order of the descriptor.
+    pub fn keys(&self) -> Vec<&DescriptorKey> {
+        match self {
+            Descriptor::Pk(key)
+            | Descriptor::Pkh(key)
+            | Descriptor::Wpkh(key)
+            | Descriptor::ShWpkh(key)
+            | Descriptor::Tr(key) => vec![key],
+            Descriptor::Multi(multi)
+            | Descriptor::Sh(multi)
+            | Descriptor::Wsh(multi)
+            | Descriptor::ShWsh(multi) => multi.keys.iter().collect(),
+        }
+    }
+
+    /// `scriptPubKey` at `index`, the index is ignored if the descriptor isn't ranged.
+    pub fn script_pubkey(&self, index: u32) -> Result<Vec<u8>, Error> {
+        match self {
+            Descriptor::Pk(key) => {
+                // <key> OP_CHECKSIG
+                let key = key.serialize_public_key(index)?;
+                let mut script = vec![key.len() as u8];
+                script.extend_from_slice(&key);
+                script.push(0xac);
+                Ok(script)
+            }
+            Descriptor::Multi(multi) => multi.script(index),
+            // the scriptPubKey doesn't depend on the network
+            _ => Ok(self.address(index, Network::MainNet)?.script_pubkey()),
+        }
+    }
+
+    /// Address at `index` on `network`, [`Error::InvalidAddress`] for `pk()` and bare `multi()`
+    /// which have no address.
+    pub fn address(&self, index: u32, network: Network) -> Result<Address, Error> {
+        match self {
+            Descriptor::Pk(_) | Descriptor::Multi(_) => Err(Error::InvalidAddress),
+            // the key may be uncompressed
+            Descriptor::Pkh(key) => Address::new(
+                network,
+                Payload::PubkeyHash(hash160(&key.serialize_public_key(index)?)),
+            ),
+            Descriptor::Wpkh(key) => Address::p2wpkh(&key.public_key(index)?, network),
+            Descriptor::ShWpkh(key) => Address::p2sh_p2wpkh(&key.public_key(index)?, network),
+            Descriptor::Tr(key) => Address::p2tr(&key.public_key(index)?, network),
+            Descriptor::Sh(multi) => Address::p2sh(&multi.script(index)?, network),
+            Descriptor::Wsh(multi) => Address::p2wsh(&multi.script(index)?, network),
+            Descriptor::ShWsh(multi) => Address::p2sh_p2wsh(&multi.script(index)?, network),
+        }
+    }
+
+    fn parse(s: &str) -> Result<Self, Error> {
+        let (name, args) = split_call(s)?;
+        let descriptor = match name {
+            "pk" => Descriptor::Pk(DescriptorKey::parse(single_arg(args)?, Context::Legacy)?),
+            "pkh" => Descriptor::Pkh(DescriptorKey::parse(single_arg(args)?, Context::Legacy)?),
+            "wpkh" => Descriptor::Wpkh(DescriptorKey::parse(single_arg(args)?, Context::Segwit)?),
+            "tr" => match args.as_slice() {
+                [key] => Descriptor::Tr(DescriptorKey::parse(key, Context::Taproot)?),
+                // script trees are not supported
+                [key, tree @ ..] if tree.iter().all(|arg| !arg.is_empty()) => {
+                    DescriptorKey::parse(key, Context::Taproot)?;
+                    return Err(Error::UnsupportedDescriptor);
+                }
+                _ => return Err(Error::InvalidDescriptor),
+            },
+            "multi" | "sortedmulti" => Descriptor::Multi(Multi::parse(s, Context::Legacy)?),
+            "wsh" => Descriptor::Wsh(Multi::parse(single_arg(args)?, Context::Segwit)?),
+            "sh" => {
+                let inner = single_arg(args)?;
+                let (name, args) = split_call(inner)?;
+                match name {
+                    "wpkh" => Descriptor::ShWpkh(DescriptorKey::parse(
+                        single_arg(args)?,
+                        Context::Segwit,
+                    )?),
+                    "wsh" => Descriptor::ShWsh(Multi::parse(single_arg(args)?, Context::Segwit)?),
+                    _ => Descriptor::Sh(Multi::parse(inner, Context::Legacy)?),
+                }
+            }
+            _ => return Err(Error::InvalidDescriptor),
+        };
+        let valid_size = match &descriptor {
+            Descriptor::Multi(multi) => multi.keys.len() <= MAX_BARE_MULTI_KEYS,
+            Descriptor::Sh(multi) => multi.script_size() <= MAX_REDEEM_SCRIPT_SIZE,
+            Descriptor::Wsh(multi) | Descriptor::ShWsh(multi) => {
+                multi.keys.len() <= MAX_WSH_MULTI_KEYS
+            }
+            _ => true,
+        };
+        if !valid_size {
+            return Err(Error::InvalidDescriptor);
+        }
+        Ok(descriptor)
+    }
+}
+
+impl FromStr for Descriptor {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let s = match s.split_once('#') {
+            Some((descriptor, expected)) => {
+                if checksum(descriptor)? != expected {
+                    return Err(Error::MisChecksum);
+                }
+                descriptor
+            }
+            None => s,
+        };
+        Descriptor::parse(s)
+    }
+}
+
+impl fmt::Display for Multi {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = if self.sorted { "sortedmulti" } else { "multi" };
+        write!(f, "{}({}", name, self.threshold)?;
+        for key in &self.keys {
+            write!(f, ",{}", key)?;
+        }
+        f.write_str(")")
+    }
+}
+
+impl fmt::Display for Descriptor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let descriptor = match self {
+            Descriptor::Pk(key) => format!("pk({})", key),
+            Descriptor::Pkh(key) => format!("pkh({})", key),
+            Descriptor::Wpkh(key) => format!("wpkh({})", key),
+            Descriptor::ShWpkh(key) => format!("sh(wpkh({}))", key),
+            Descriptor::Tr(key) => format!("tr({})", key),
+            Descriptor::Multi(multi) => multi.to_string(),
+            Descriptor::Sh(multi) => format!("sh({})", multi),
+            Descriptor::Wsh(multi) => format!("wsh({})", multi),
+            Descriptor::ShWsh(multi) => format!("sh(wsh({}))", multi),
+        };
+        f.write_str(&descriptor)?;
+        if !f.alternate() {
+            write!(f, "#{}", checksum(&descriptor).map_err(|_| fmt::Error)?)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIF: &str = "L4rK1yDtCWekvXuE6oXD9jCYfFNV2cWRpVuPLBcCU2z8TrisoyY1";
+    const UNCOMPRESSED_WIF: &str = "5KYZdUEo39z3FPrtuX2QbbwGnNP5zTd7yyr2SC1j299sBCnWjss";
+    const PUBLIC_KEY: &str = "03a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd";
+    const UNCOMPRESSED_PUBLIC_KEY: &str = "04a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd5b8dec5235a0fa8722476c7709c02559e3aa73aa03918ba2d492eea75abea235";
+
+    fn scripts(descriptor: &str, count: u32) -> Vec<String> {
+        let descriptor: Descriptor = descriptor.parse().expect(descriptor);
+        (0..count)
+            .map(|i| hex::encode(descriptor.script_pubkey(i).expect("script")))
+            .collect()
+    }
+
+    #[test]
+    fn test_single_key_vectors() {
+        for (descriptor, script) in &[
+            (format!("pk({})", WIF), format!("21{}ac", PUBLIC_KEY)),
+            (format!("pk({})", PUBLIC_KEY), format!("21{}ac", PUBLIC_KEY)),
+            (
+                format!("pkh([deadbeef/1/2'/3/4']{})", WIF),
+                "76a9149a1c78a507689f6f54b847ad1cef1e614ee23f1e88ac".to_string(),
+            ),
+            (
+                format!("pkh([deadbeef/1/2'/3/4']{})", PUBLIC_KEY),
+                "76a9149a1c78a507689f6f54b847ad1cef1e614ee23f1e88ac".to_string(),
+            ),
+            (
+                format!("wpkh({})", WIF),
+                "00149a1c78a507689f6f54b847ad1cef1e614ee23f1e".to_string(),
+            ),
+            (
+                format!("sh(wpkh({}))", PUBLIC_KEY),
+                "a91484ab21b1b2fd065d4504ff693d832434b6108d7b87".to_string(),
+            ),
+            (
+                format!("tr({})", &PUBLIC_KEY[2..]),
+                "512077aab6e066f8a7419c5ab714c12c67d25007ed55a43cadcacb4d7a970a093f11".to_string(),
+            ),
+            (
+                format!("tr({})", WIF),
+                "512077aab6e066f8a7419c5ab714c12c67d25007ed55a43cadcacb4d7a970a093f11".to_string(),
+            ),
+        ] {
+            assert_eq!(&scripts(descriptor, 1)[0], script, "{}", descriptor);
+        }
+    }
+
+    #[test]
+    fn test_extended_key_vectors() {
+        assert_eq!(
+            scripts("wpkh([ffffffff/13']xprv9vHkqa6EV4sPZHYqZznhT2NPtPCjKuDKGY38FBWLvgaDx45zo9WQRUT3dKYnjwih2yJD9mkrocEZXo1ex8G81dwSM1fwqWpWkeS3v86pgKt/1/2/*)", 3),
+            vec![
+                "0014326b2249e3a25d5dc60935f044ee835d090ba859",
+                "0014af0bd98abc2f2cae66e36896a39ffe2d32984fb7",
+                "00141fa798efd1cbf95cebf912c031b8a4a6e9fb9f27",
+            ]
+        );
+        assert_eq!(
+            scripts("sh(wpkh(xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi/10/20/30/40/*'))", 3),
+            vec![
+                "a9149a4d9901d6af519b2a23d4a2f51650fcba87ce7b87",
+                "a914bed59fc0024fae941d6e20a3b44a109ae740129287",
+                "a9148483aa1116eb9c05c482a72bada4b1db24af654387",
+            ]
+        );
+
+        // BIP-84 and BIP-86 accounts of the "abandon ... about" mnemonic
+        let wpkh: Descriptor = "wpkh([73c5da0a/84h/0h/0h]xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V/0/*)".parse().expect("parse");
+        assert!(wpkh.is_ranged());
+        assert_eq!(
+            wpkh.address(1, Network::MainNet).map(|a| a.to_string()),
+            Ok("bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g".to_string())
+        );
+        let tr: Descriptor = "tr([73c5da0a/86h/0h/0h]xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ/0/*)".parse().expect("parse");
+        assert_eq!(
+            tr.address(0, Network::MainNet).map(|a| a.to_string()),
+            Ok("bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr".to_string())
+        );
+        assert_eq!(
+            tr.address(1 << 31, Network::MainNet),
+            Err(Error::KeyIndexOutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_multi_vectors() {
+        let script = format!("5121{}41{}52ae", PUBLIC_KEY, UNCOMPRESSED_PUBLIC_KEY);
+        assert_eq!(
+            scripts(&format!("multi(1,{},{})", WIF, UNCOMPRESSED_WIF), 1),
+            vec![script.clone()]
+        );
+        assert_eq!(
+            scripts(
+                &format!("sortedmulti(1,{},{})", UNCOMPRESSED_PUBLIC_KEY, PUBLIC_KEY),
+                1
+            ),
+            vec![script]
+        );
+        assert_eq!(
+            scripts("sh(multi(2,[00000000/111'/222]xprvA1RpRA33e1JQ7ifknakTFpgNXPmW2YvmhqLQYMmrj4xJXXWYpDPS3xz7iAxn8L39njGVyuoseXzU6rcxFLJ8HFsTjSyQbLYnMpCqE2VbFWc,xprv9uPDJpEQgRQfDcW7BkF7eTya6RPxXeJCqCJGHuCJ4GiRVLzkTXBAJMu2qaMWPrS7AANYqdq6vcBcBUdJCVVFceUvJFjaPdGZ2y9WACViL4L/0))", 1),
+            vec!["a91445a9a622a8b0a1269944be477640eedc447bbd8487"]
+        );
+        let multi: Descriptor = format!("multi(1,{},{})", WIF, UNCOMPRESSED_WIF)
+            .parse()
+            .expect("parse");
+        assert_eq!(
+            multi.address(0, Network::MainNet),
+            Err(Error::InvalidAddress)
+        );
+    }
+
+    #[test]
+    fn test_checksum_and_display() {
+        let descriptor = "wpkh([d34db33f/84'/0'/0']xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V/0/*)";
+        let parsed: Descriptor = descriptor.parse().expect("parse");
+        let normalized = descriptor.replace('\'', "h");
+        assert_eq!(format!("{:#}", parsed), normalized);
+        let printed = parsed.to_string();
+        assert_eq!(
+            printed,
+            format!("{}#{}", normalized, checksum(&normalized).unwrap())
+        );
+        assert_eq!(printed.parse(), Ok(parsed.clone()));
+        let with_checksum = format!("{}#{}", descriptor, checksum(descriptor).unwrap());
+        assert_eq!(with_checksum.parse(), Ok(parsed));
+
+        let mut invalid = printed.clone();
+        invalid.pop();
+        invalid.push('q');
+        assert_eq!(invalid.parse::<Descriptor>(), Err(Error::MisChecksum));
+        assert_eq!(
+            format!("{}#", normalized).parse::<Descriptor>(),
+            Err(Error::MisChecksum)
+        );
+
+        for descriptor in &[
+            format!("sh(wsh(sortedmulti(1,{},{})))", PUBLIC_KEY, WIF),
+            format!("wsh(multi(1,{}))", PUBLIC_KEY),
+            format!("pkh({})", UNCOMPRESSED_PUBLIC_KEY),
+            format!("pk({})", UNCOMPRESSED_WIF),
+            "pkh(xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi/0h/*h)".to_string(),
+        ] {
+            let parsed: Descriptor = descriptor.parse().expect(descriptor);
+            assert_eq!(&format!("{:#}", parsed), descriptor);
+        }
+    }
+
+    #[test]
+    fn test_invalid_descriptors() {
+        let xpub = "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8";
+        for descriptor in &[
+            // hardened derivation from a public key
+            format!("wpkh({}/1h/*)", xpub),
+            format!("wpkh({}/*h)", xpub),
+            // wildcard not at the end
+            format!("wpkh({}/*/1)", xpub),
+            // uncompressed key in SegWit
+            format!("wpkh({})", UNCOMPRESSED_PUBLIC_KEY),
+            format!("wsh(multi(1,{}))", UNCOMPRESSED_WIF),
+            format!("tr({})", UNCOMPRESSED_PUBLIC_KEY),
+            // x-only key outside tr()
+            format!("pk({})", &PUBLIC_KEY[2..]),
+            // invalid threshold
+            format!("multi(0,{})", PUBLIC_KEY),
+            format!("multi(2,{})", PUBLIC_KEY),
+            // too many keys
+            format!("multi(1,{0},{0},{0},{0})", PUBLIC_KEY),
+            format!("sh(multi(1{}))", format!(",{}", PUBLIC_KEY).repeat(16)),
+            format!("wsh(multi(1{}))", format!(",{}", PUBLIC_KEY).repeat(21)),
+            // invalid origin
+            format!("pkh([deadbee/0]{})", PUBLIC_KEY),
+            format!("pkh([deadbeef/0x]{})", PUBLIC_KEY),
+            format!("pkh(deadbeef/0]{})", PUBLIC_KEY),
+            // unsupported expressions
+            "raw(deadbeef)".to_string(),
+            format!("sh(sh(wpkh({})))", PUBLIC_KEY),
+            format!("wpkh({}", PUBLIC_KEY),
+            format!("wpkh({0},{0})", PUBLIC_KEY),
+            // SLIP-132 version
+            "wpkh(zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs/0/*)".to_string(),
+        ] {
+            assert_eq!(
+                descriptor.parse::<Descriptor>(),
+                Err(Error::InvalidDescriptor),
+                "{}",
+                descriptor
+            );
+        }
+
+        // taproot script trees
+        for descriptor in &[
+            format!("tr({0},pk({0}))", PUBLIC_KEY),
+            format!("tr({0},{{pk({0}),pk({0})}})", PUBLIC_KEY),
+        ] {
+            assert_eq!(
+                descriptor.parse::<Descriptor>(),
+                Err(Error::UnsupportedDescriptor),
+                "{}",
+                descriptor
+            );
+        }
+    }
+}
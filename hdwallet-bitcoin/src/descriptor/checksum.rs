@@ -0,0 +1,60 @@
+use crate::Error;
+
+const INPUT_CHARSET: &str = "0123456789()[],'/*abcdefgh@:$%{}IJKLMNOPQRSTUVWXYZ&+-.;<=>?!^_|~ijklmnopqrstuvwxyzABCDEFGH`#\"\\ ";
+const CHECKSUM_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATOR: [u64; 5] = [
+    0xf5dee51989,
+    0xa9fdca3312,
+    0x1bab10e32d,
+    0x3706b1677a,
+    0x644d626ffd,
+];
+
+fn polymod(chk: u64, value: u64) -> u64 {
+    let top = chk >> 35;
+    let mut chk = (chk & 0x7ffffffff) << 5 ^ value;
+    for (i, generator) in GENERATOR.iter().enumerate() {
+        if (top >> i) & 1 == 1 {
+            chk ^= generator;
+        }
+    }
+    chk
+}
+
+/// BIP-380 checksum of a descriptor without the `#` separator.
+pub(crate) fn checksum(descriptor: &str) -> Result<String, Error> {
+    let mut chk = 1;
+    let mut groups = Vec::with_capacity(3);
+    for c in descriptor.chars() {
+        let position = INPUT_CHARSET.find(c).ok_or(Error::InvalidDescriptor)? as u64;
+        chk = polymod(chk, position & 31);
+        groups.push(position >> 5);
+        if groups.len() == 3 {
+            chk = polymod(chk, groups[0] * 9 + groups[1] * 3 + groups[2]);
+            groups.clear();
+        }
+    }
+    match groups.as_slice() {
+        [group] => chk = polymod(chk, *group),
+        [first, second] => chk = polymod(chk, first * 3 + second),
+        _ => {}
+    }
+    for _ in 0..8 {
+        chk = polymod(chk, 0);
+    }
+    chk ^= 1;
+    Ok((0..8)
+        .map(|i| CHECKSUM_CHARSET[((chk >> (5 * (7 - i))) & 31) as usize] as char)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksum() {
+        assert_eq!(checksum("raw(deadbeef)"), Ok("89f8spxm".to_string()));
+        assert_eq!(checksum("raw(deadbeef)\n"), Err(Error::InvalidDescriptor));
+    }
+}
@@ -0,0 +1,382 @@
+use crate::{
+    origin::{parse_key_index, write_path},
+    Error, KeyOrigin, Network, PrivKey, PubKey, ScriptType, Wif,
+};
+use hdwallet::{
+    secp256k1::{Parity, PublicKey, XOnlyPublicKey},
+    traits::{Deserialize, Serialize},
+    KeyIndex,
+};
+use std::fmt;
+
+/// Script context of a key expression, which restricts the accepted key encodings.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Context {
+    /// `pk()`, `pkh()`, `sh(multi())` and bare `multi()`, uncompressed keys allowed.
+    Legacy,
+    /// Inside `wpkh()` or `wsh()`, compressed keys only.
+    Segwit,
+    /// Inside `tr()`, x-only keys allowed.
+    Taproot,
+}
+
+/// Key derivation step after an extended key, ending the path of ranged descriptors.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Wildcard {
+    /// Not ranged, the key is used as is.
+    None,
+    /// `/*`, the normal child at the descriptor index.
+    Normal,
+    /// `/*h`, the hardened child at the descriptor index.
+    Hardened,
+}
+
+/// Key of a single-key expression.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SingleKey {
+    /// Hex encoded public key, 65 bytes if uncompressed.
+    Public { key: PublicKey, compressed: bool },
+    /// Hex encoded 32-byte key, only in `tr()`.
+    XOnly(XOnlyPublicKey),
+    /// WIF encoded private key.
+    Private(Wif),
+}
+
+/// Extended key of an extended key expression, xpub/tpub or xprv/tprv.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ExtendedKey {
+    Public(PubKey),
+    Private(PrivKey),
+}
+
+/// Key expression of a descriptor, BIP-380.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DescriptorKey {
+    Single {
+        origin: Option<KeyOrigin>,
+        key: SingleKey,
+    },
+    /// Extended key and the derivation steps after it, e.g. `xpub.../0/*`.
+    Extended {
+        origin: Option<KeyOrigin>,
+        key: ExtendedKey,
+        path: Vec<KeyIndex>,
+        wildcard: Wildcard,
+    },
+}
+
+impl DescriptorKey {
+    /// Whether the key ends with a wildcard.
+    pub fn is_ranged(&self) -> bool {
+        match self {
+            DescriptorKey::Single { .. } => false,
+            DescriptorKey::Extended { wildcard, .. } => *wildcard != Wildcard::None,
+        }
+    }
+
+    /// Whether the public key is serialized compressed in scripts, extended keys always are.
+    pub fn is_compressed(&self) -> bool {
+        match self {
+            DescriptorKey::Single {
+                key: SingleKey::Public { compressed, .. },
+                ..
+            } => *compressed,
+            DescriptorKey::Single {
+                key: SingleKey::Private(wif),
+                ..
+            } => wif.compressed,
+            _ => true,
+        }
+    }
+
+    /// Public key at `index`, which only matters for ranged keys.
+    ///
+    /// An x-only key is returned with even parity.
+    pub fn public_key(&self, index: u32) -> Result<PublicKey, Error> {
+        let (key, path, wildcard) = match self {
+            DescriptorKey::Single { key, .. } => {
+                return Ok(match key {
+                    SingleKey::Public { key, .. } => *key,
+                    SingleKey::XOnly(key) => PublicKey::from_x_only_public_key(*key, Parity::Even),
+                    SingleKey::Private(wif) => wif.public_key(),
+                })
+            }
+            DescriptorKey::Extended {
+                key,
+                path,
+                wildcard,
+                ..
+            } => (key, path, wildcard),
+        };
+        let mut path = path.clone();
+        match wildcard {
+            Wildcard::None => {}
+            _ if index >= 1 << 31 => return Err(Error::KeyIndexOutOfRange),
+            Wildcard::Normal => path.push(KeyIndex::Normal(index)),
+            Wildcard::Hardened => path.push(KeyIndex::hardened_from_normalize_index(index)?),
+        }
+        match key {
+            ExtendedKey::Public(key) => {
+                let mut key = key.clone();
+                for key_index in path {
+                    key = key.derive_public_key(key_index)?;
+                }
+                Ok(key.extended_key.public_key)
+            }
+            ExtendedKey::Private(key) => {
+                let mut key = key.clone();
+                for key_index in path {
+                    key = key.derive_private_key(key_index)?;
+                }
+                Ok(key.extended_key.public_key())
+            }
+        }
+    }
+
+    /// Public key at `index` as pushed in scripts.
+    pub(crate) fn serialize_public_key(&self, index: u32) -> Result<Vec<u8>, Error> {
+        let key = self.public_key(index)?;
+        Ok(if self.is_compressed() {
+            key.serialize().to_vec()
+        } else {
+            key.serialize_uncompressed().to_vec()
+        })
+    }
+
+    pub(crate) fn parse(s: &str, context: Context) -> Result<Self, Error> {
+        let (origin, s) = match s.strip_prefix('[') {
+            Some(s) => {
+                let (origin, s) = s.split_once(']').ok_or(Error::InvalidDescriptor)?;
+                (Some(origin.parse()?), s)
+            }
+            None => (None, s),
+        };
+        let mut components = s.split('/');
+        let key = components.next().unwrap_or_default();
+        let path: Vec<&str> = components.collect();
+        if path.is_empty() {
+            if let Some(key) = parse_single_key(key, context)? {
+                return Ok(DescriptorKey::Single { origin, key });
+            }
+        }
+
+        let key = match PubKey::deserialize(key.to_string()) {
+            Ok(key) => ExtendedKey::Public(key),
+            Err(_) => ExtendedKey::Private(
+                PrivKey::deserialize(key.to_string()).map_err(|_| Error::InvalidDescriptor)?,
+            ),
+        };
+        let (network, script_type) = match &key {
+            ExtendedKey::Public(key) => (key.network, key.script_type),
+            ExtendedKey::Private(key) => (key.network, key.script_type),
+        };
+        // descriptors take xpub/tpub encoded keys only
+        if matches!(network, Network::Custom(_) | Network::Unknown(_))
+            || script_type != ScriptType::Legacy
+        {
+            return Err(Error::InvalidDescriptor);
+        }
+        let (wildcard, path) = match path.split_last() {
+            Some((&"*", path)) => (Wildcard::Normal, path),
+            Some((&"*h", path)) | Some((&"*H", path)) | Some((&"*'", path)) => {
+                (Wildcard::Hardened, path)
+            }
+            _ => (Wildcard::None, path.as_slice()),
+        };
+        let path = path
+            .iter()
+            .map(|index| parse_key_index(index))
+            .collect::<Result<Vec<_>, _>>()?;
+        let hardened = wildcard == Wildcard::Hardened
+            || path
+                .iter()
+                .any(|index| matches!(index, KeyIndex::Hardened(_)));
+        if hardened && matches!(key, ExtendedKey::Public(_)) {
+            return Err(Error::InvalidDescriptor);
+        }
+        Ok(DescriptorKey::Extended {
+            origin,
+            key,
+            path,
+            wildcard,
+        })
+    }
+}
+
+/// Parse a hex public key or a WIF private key, `None` if `s` is neither.
+fn parse_single_key(s: &str, context: Context) -> Result<Option<SingleKey>, Error> {
+    let key = match hex::decode(s) {
+        Ok(data) if data.len() == 32 && context == Context::Taproot => {
+            SingleKey::XOnly(XOnlyPublicKey::from_slice(&data)?)
+        }
+        Ok(data) if data.len() == 33 || data.len() == 65 => SingleKey::Public {
+            key: PublicKey::from_slice(&data)?,
+            compressed: data.len() == 33,
+        },
+        Ok(_) => return Err(Error::InvalidDescriptor),
+        Err(_) => match Wif::deserialize(s.to_string()) {
+            Ok(wif) => SingleKey::Private(wif),
+            Err(_) => return Ok(None),
+        },
+    };
+    let compressed = match &key {
+        SingleKey::Public { compressed, .. } => *compressed,
+        SingleKey::Private(wif) => wif.compressed,
+        SingleKey::XOnly(_) => true,
+    };
+    if !compressed && context != Context::Legacy {
+        return Err(Error::InvalidDescriptor);
+    }
+    Ok(Some(key))
+}
+
+impl fmt::Display for DescriptorKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let origin = match self {
+            DescriptorKey::Single { origin, .. } | DescriptorKey::Extended { origin, .. } => origin,
+        };
+        if let Some(origin) = origin {
+            write!(f, "[{}]", origin)?;
+        }
+        match self {
+            DescriptorKey::Single { key, .. } => match key {
+                SingleKey::Public { key, compressed } if !compressed => {
+                    f.write_str(&hex::encode(key.serialize_uncompressed()))
+                }
+                SingleKey::Public { key, .. } => f.write_str(&hex::encode(key.serialize())),
+                SingleKey::XOnly(key) => f.write_str(&hex::encode(key.serialize())),
+                SingleKey::Private(wif) => f.write_str(&Serialize::<String>::serialize(wif)),
+            },
+            DescriptorKey::Extended {
+                key,
+                path,
+                wildcard,
+                ..
+            } => {
+                let key: String = match key {
+                    ExtendedKey::Public(key) => key.serialize(),
+                    ExtendedKey::Private(key) => key.serialize(),
+                };
+                f.write_str(&key)?;
+                write_path(f, path)?;
+                match wildcard {
+                    Wildcard::None => Ok(()),
+                    Wildcard::Normal => f.write_str("/*"),
+                    Wildcard::Hardened => f.write_str("/*h"),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // BIP-32 test vector 1 master key
+    const XPRV: &str = "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi";
+    const XPUB: &str = "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8";
+    const PUBLIC_KEY: &str = "03a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd";
+
+    fn derive(path: &str) -> PublicKey {
+        let key = PrivKey::deserialize(XPRV.to_string()).unwrap();
+        key.derive_path(path.into())
+            .unwrap()
+            .extended_key
+            .public_key()
+    }
+
+    #[test]
+    fn test_origin() {
+        let s = format!("[d34db33f/84h/0h/0h]{}/0/*", XPUB);
+        let key = DescriptorKey::parse(&s, Context::Segwit).unwrap();
+        match &key {
+            DescriptorKey::Extended {
+                origin: Some(origin),
+                path,
+                wildcard,
+                ..
+            } => {
+                assert_eq!(origin.to_string(), "d34db33f/84h/0h/0h");
+                assert_eq!(path, &[KeyIndex::Normal(0)]);
+                assert_eq!(*wildcard, Wildcard::Normal);
+            }
+            _ => panic!("{:?}", key),
+        }
+        assert_eq!(key.to_string(), s);
+        assert_eq!(key.public_key(5), Ok(derive("m/0/5")));
+
+        // hardened steps are printed with `h`
+        let key = DescriptorKey::parse(&format!("[d34db33f/0'/1H]{}", PUBLIC_KEY), Context::Legacy)
+            .unwrap();
+        assert_eq!(key.to_string(), format!("[d34db33f/0h/1h]{}", PUBLIC_KEY));
+        assert!(!key.is_ranged());
+    }
+
+    #[test]
+    fn test_hardened_wildcard() {
+        for wildcard in &["*h", "*H", "*'"] {
+            let s = format!("{}/1h/{}", XPRV, wildcard);
+            let key = DescriptorKey::parse(&s, Context::Segwit).unwrap();
+            assert!(key.is_ranged());
+            assert_eq!(key.to_string(), format!("{}/1h/*h", XPRV));
+            assert_eq!(key.public_key(3), Ok(derive("m/1H/3H")));
+            assert_eq!(key.public_key(1 << 31), Err(Error::KeyIndexOutOfRange));
+        }
+        // public keys can't derive hardened children
+        for s in &[format!("{}/*h", XPUB), format!("{}/1h/*", XPUB)] {
+            assert_eq!(
+                DescriptorKey::parse(s, Context::Segwit),
+                Err(Error::InvalidDescriptor),
+                "{}",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn test_multipath() {
+        // BIP-389 multipath expressions are not supported
+        for s in &[format!("{}/<0;1>/*", XPUB), format!("{}/<0;1>", XPRV)] {
+            assert_eq!(
+                DescriptorKey::parse(s, Context::Segwit),
+                Err(Error::InvalidDescriptor),
+                "{}",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_keys() {
+        let zpub = "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs";
+        for (s, context) in &[
+            // malformed keys and paths
+            ("".to_string(), Context::Legacy),
+            ("xpub".to_string(), Context::Legacy),
+            (PUBLIC_KEY[..64].to_string(), Context::Legacy),
+            (format!("{}/*/0", XPUB), Context::Legacy),
+            (format!("{}/+1/*", XPUB), Context::Legacy),
+            (format!("{}/0x", XPUB), Context::Legacy),
+            (format!("{}/", XPUB), Context::Legacy),
+            // derivation steps after a single key
+            (format!("{}/0", PUBLIC_KEY), Context::Legacy),
+            // malformed origins
+            (format!("[d34db33f{}", XPUB), Context::Legacy),
+            (format!("[d34db33f/+0]{}", XPUB), Context::Legacy),
+            (format!("[d34db3]{}", XPUB), Context::Legacy),
+            // SLIP-132 versions
+            (format!("{}/0/*", zpub), Context::Segwit),
+            // x-only keys outside tr()
+            (PUBLIC_KEY[2..].to_string(), Context::Segwit),
+        ] {
+            assert_eq!(
+                DescriptorKey::parse(s, *context),
+                Err(Error::InvalidDescriptor),
+                "{}",
+                s
+            );
+        }
+        assert!(DescriptorKey::parse(&PUBLIC_KEY[2..], Context::Taproot).is_ok());
+    }
+}
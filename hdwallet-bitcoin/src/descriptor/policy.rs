@@ -0,0 +1,315 @@
+use super::{key::Context, Descriptor, DescriptorKey, ExtendedKey, Wildcard};
+use crate::{origin::parse_key_index, Address, Chain, Error, Network};
+use hdwallet::KeyIndex;
+
+/// Key placeholder `@i/<M;N>/*` of a wallet policy template, `@i/**` is `@i/<0;1>/*`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Placeholder {
+    key_index: usize,
+    receive: u32,
+    change: u32,
+}
+
+/// BIP-388 wallet policy, a descriptor template and its key information vector.
+///
+/// The template is a descriptor with key placeholders `@i/**` or `@i/<M;N>/*` in place of the
+/// keys, and the keys are xpubs with an optional origin and no derivation steps. Policies the
+/// BIP doesn't allow are rejected as [`Error::InvalidDescriptor`]:
+///
+/// - keys out of order, `@i` first appears after `@(i+1)`, or unused keys,
+/// - the same xpub twice in the key information vector,
+/// - a placeholder used twice with overlapping derivations,
+/// - keys in the template, bare `pk()` and `multi()`.
+///
+/// Taproot script trees such as `tr(@0/**,{...})` are [`Error::UnsupportedDescriptor`].
+///
+/// ```rust
+/// use hdwallet_bitcoin::{Chain, Network, WalletPolicy};
+///
+/// let policy = WalletPolicy::new(
+///     "wpkh(@0/**)",
+///     &["[73c5da0a/84h/0h/0h]xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V"],
+/// )
+/// .unwrap();
+/// let address = policy.address(Chain::External, 0, Network::MainNet).unwrap();
+/// assert_eq!(address.to_string(), "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu");
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WalletPolicy {
+    template: String,
+    keys: Vec<DescriptorKey>,
+}
+
+impl WalletPolicy {
+    /// Parse and validate the policy of `template` and the key information vector `keys`.
+    pub fn new(template: &str, keys: &[&str]) -> Result<Self, Error> {
+        let keys = keys
+            .iter()
+            .map(|key| DescriptorKey::parse(key, Context::Segwit))
+            .collect::<Result<Vec<_>, _>>()?;
+        WalletPolicy::from_keys(template, keys)
+    }
+
+    /// Validate the policy of `template` and the already parsed key information vector `keys`.
+    pub fn from_keys(template: &str, keys: Vec<DescriptorKey>) -> Result<Self, Error> {
+        let mut extended_keys = Vec::new();
+        for key in &keys {
+            match key {
+                DescriptorKey::Extended {
+                    key: ExtendedKey::Public(key),
+                    path,
+                    wildcard: Wildcard::None,
+                    ..
+                } if path.is_empty() => extended_keys.push(&key.extended_key),
+                _ => return Err(Error::InvalidDescriptor),
+            }
+        }
+        // the origins don't matter, the same key twice is reused
+        for (i, key) in extended_keys.iter().enumerate() {
+            if extended_keys[..i].contains(key) {
+                return Err(Error::InvalidDescriptor);
+            }
+        }
+
+        let (_, placeholders) = split_template(template)?;
+        let mut next_key_index = 0;
+        for (i, placeholder) in placeholders.iter().enumerate() {
+            if placeholder.key_index > next_key_index || placeholder.key_index >= keys.len() {
+                return Err(Error::InvalidDescriptor);
+            }
+            if placeholder.key_index == next_key_index {
+                next_key_index += 1;
+            }
+            let overlaps = placeholders[..i].iter().any(|other| {
+                other.key_index == placeholder.key_index
+                    && [other.receive, other.change]
+                        .iter()
+                        .any(|index| *index == placeholder.receive || *index == placeholder.change)
+            });
+            if placeholder.receive == placeholder.change || overlaps {
+                return Err(Error::InvalidDescriptor);
+            }
+        }
+        if next_key_index != keys.len() {
+            return Err(Error::InvalidDescriptor);
+        }
+
+        let policy = WalletPolicy {
+            template: template.to_string(),
+            keys,
+        };
+        for chain in [Chain::External, Chain::Internal] {
+            let descriptor = policy.descriptor(chain)?;
+            // any other key is written out in the template
+            if descriptor.keys().len() != placeholders.len()
+                || matches!(descriptor, Descriptor::Pk(_) | Descriptor::Multi(_))
+            {
+                return Err(Error::InvalidDescriptor);
+            }
+        }
+        Ok(policy)
+    }
+
+    /// Descriptor template, e.g. `wsh(sortedmulti(2,@0/**,@1/**))`.
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    /// Key information vector, `keys[i]` replaces `@i`.
+    pub fn keys(&self) -> &[DescriptorKey] {
+        &self.keys
+    }
+
+    /// Ranged descriptor of the receive or change addresses.
+    pub fn descriptor(&self, chain: Chain) -> Result<Descriptor, Error> {
+        let (segments, placeholders) = split_template(&self.template)?;
+        let mut descriptor = segments[0].to_string();
+        for (placeholder, segment) in placeholders.iter().zip(&segments[1..]) {
+            let mut key = self
+                .keys
+                .get(placeholder.key_index)
+                .ok_or(Error::InvalidDescriptor)?
+                .clone();
+            if let DescriptorKey::Extended { path, wildcard, .. } = &mut key {
+                let index = match chain {
+                    Chain::External => placeholder.receive,
+                    Chain::Internal => placeholder.change,
+                };
+                *path = vec![KeyIndex::Normal(index)];
+                *wildcard = Wildcard::Normal;
+            }
+            descriptor.push_str(&key.to_string());
+            descriptor.push_str(segment);
+        }
+        Descriptor::parse(&descriptor)
+    }
+
+    /// Receive or change address at `index` on `network`.
+    pub fn address(&self, chain: Chain, index: u32, network: Network) -> Result<Address, Error> {
+        self.descriptor(chain)?.address(index, network)
+    }
+}
+
+/// Split `template` around its key placeholders, the segments are one more than the
+/// placeholders.
+fn split_template(template: &str) -> Result<(Vec<&str>, Vec<Placeholder>), Error> {
+    let mut segments = Vec::new();
+    let mut placeholders = Vec::new();
+    let mut rest = template;
+    while let Some((segment, placeholder)) = rest.split_once('@') {
+        segments.push(segment);
+        let digits = placeholder
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(placeholder.len());
+        // `@01` isn't a placeholder
+        if digits > 1 && placeholder.starts_with('0') {
+            return Err(Error::InvalidDescriptor);
+        }
+        let key_index = placeholder[..digits]
+            .parse()
+            .map_err(|_| Error::InvalidDescriptor)?;
+        let placeholder = &placeholder[digits..];
+        let ((receive, change), remaining) = match placeholder.strip_prefix("/**") {
+            Some(remaining) => ((0, 1), remaining),
+            None => {
+                let (indices, remaining) = placeholder
+                    .strip_prefix("/<")
+                    .and_then(|s| s.split_once(">/*"))
+                    .ok_or(Error::InvalidDescriptor)?;
+                let (receive, change) = indices.split_once(';').ok_or(Error::InvalidDescriptor)?;
+                ((normal_index(receive)?, normal_index(change)?), remaining)
+            }
+        };
+        placeholders.push(Placeholder {
+            key_index,
+            receive,
+            change,
+        });
+        rest = remaining;
+    }
+    segments.push(rest);
+    Ok((segments, placeholders))
+}
+
+fn normal_index(s: &str) -> Result<u32, Error> {
+    match parse_key_index(s)? {
+        KeyIndex::Normal(index) => Ok(index),
+        KeyIndex::Hardened(_) => Err(Error::InvalidDescriptor),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // BIP-84 and BIP-86 accounts of the "abandon ... about" mnemonic
+    const WPKH_KEY: &str = "[73c5da0a/84h/0h/0h]xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V";
+    const TR_KEY: &str = "[73c5da0a/86h/0h/0h]xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ";
+
+    #[test]
+    fn test_single_key_policies() {
+        let policy = WalletPolicy::new("wpkh(@0/**)", &[WPKH_KEY]).unwrap();
+        assert_eq!(policy.template(), "wpkh(@0/**)");
+        assert_eq!(
+            policy.address(Chain::External, 1, Network::MainNet),
+            Ok("bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g"
+                .parse()
+                .unwrap())
+        );
+        assert_eq!(
+            policy.address(Chain::Internal, 0, Network::MainNet),
+            Ok("bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el"
+                .parse()
+                .unwrap())
+        );
+        let policy = WalletPolicy::new("tr(@0/<0;1>/*)", &[TR_KEY]).unwrap();
+        assert_eq!(
+            policy.address(Chain::External, 0, Network::MainNet),
+            Ok(
+                "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+                    .parse()
+                    .unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_multisig_policies() {
+        let policy =
+            WalletPolicy::new("wsh(sortedmulti(2,@0/**,@1/<2;3>/*))", &[WPKH_KEY, TR_KEY]).unwrap();
+        let receive = format!("wsh(sortedmulti(2,{}/0/*,{}/2/*))", WPKH_KEY, TR_KEY);
+        let change = format!("wsh(sortedmulti(2,{}/1/*,{}/3/*))", WPKH_KEY, TR_KEY);
+        for (chain, expected) in [(Chain::External, receive), (Chain::Internal, change)] {
+            let descriptor = policy.descriptor(chain).unwrap();
+            assert_eq!(format!("{:#}", descriptor), expected);
+            assert_eq!(
+                policy.address(chain, 7, Network::MainNet),
+                expected
+                    .parse::<Descriptor>()
+                    .unwrap()
+                    .address(7, Network::MainNet)
+            );
+        }
+
+        // a key may appear twice with disjoint derivations
+        let template = "sh(wsh(multi(1,@0/<0;1>/*,@0/<2;3>/*,@1/**)))";
+        assert!(WalletPolicy::new(template, &[WPKH_KEY, TR_KEY]).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_policies() {
+        let xprv = "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi";
+        let with_path = format!("{}/0", WPKH_KEY);
+        let unused = "[d34db33f/84h/0h/0h]xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V";
+        let literal = format!("wsh(multi(1,@0/**,{}/0/*))", TR_KEY);
+        for (template, keys) in [
+            // missing or malformed derivation
+            ("wpkh(@0)", vec![WPKH_KEY]),
+            ("wpkh(@0/*)", vec![WPKH_KEY]),
+            ("wpkh(@0/<0;0>/*)", vec![WPKH_KEY]),
+            ("wpkh(@0/<0h;1>/*)", vec![WPKH_KEY]),
+            ("wpkh(@0/<0;1>)", vec![WPKH_KEY]),
+            ("wpkh(@0/**h)", vec![WPKH_KEY]),
+            ("wpkh(@/**)", vec![WPKH_KEY]),
+            ("wpkh(@00/**)", vec![WPKH_KEY]),
+            ("wsh(multi(1,@0/**,@01/**))", vec![WPKH_KEY, TR_KEY]),
+            // key information
+            ("wpkh(@0/**)", vec![xprv]),
+            ("wpkh(@0/**)", vec![with_path.as_str()]),
+            ("wsh(multi(1,@0/**,@1/**))", vec![WPKH_KEY, unused]),
+            // placeholders out of order, out of range or unused
+            ("wsh(multi(1,@1/**,@0/**))", vec![WPKH_KEY, TR_KEY]),
+            ("wpkh(@1/**)", vec![WPKH_KEY]),
+            ("wpkh(@0/**)", vec![WPKH_KEY, TR_KEY]),
+            // overlapping derivations of the same key
+            ("wsh(multi(1,@0/**,@0/**))", vec![WPKH_KEY]),
+            ("wsh(multi(1,@0/<0;1>/*,@0/<1;2>/*))", vec![WPKH_KEY]),
+            // keys in the template and bare scripts
+            (literal.as_str(), vec![WPKH_KEY]),
+            ("pk(@0/**)", vec![WPKH_KEY]),
+            ("sortedmulti(1,@0/**)", vec![WPKH_KEY]),
+            ("wpkh(@0/**)#8zl0zxma", vec![WPKH_KEY]),
+        ] {
+            assert_eq!(
+                WalletPolicy::new(template, &keys),
+                Err(Error::InvalidDescriptor),
+                "{}",
+                template
+            );
+        }
+
+        // taproot script trees
+        const MASTER_XPUB: &str = "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8";
+        assert_eq!(
+            WalletPolicy::new("tr(@0/**,pk(@1/**))", &[TR_KEY, WPKH_KEY]),
+            Err(Error::UnsupportedDescriptor)
+        );
+        assert_eq!(
+            WalletPolicy::new(
+                "tr(@0/**,{pk(@1/**),pk(@2/**)})",
+                &[TR_KEY, WPKH_KEY, MASTER_XPUB]
+            ),
+            Err(Error::UnsupportedDescriptor)
+        );
+    }
+}
@@ -0,0 +1,489 @@
+//! Electrum seeds, new-style versioned mnemonics and old-style watch-only master public keys.
+
+use crate::{
+    hash::{hash160, sha256, sha256d},
+    secp256k1_sign_only, secp256k1_verify_only, Address, Chain, Error, Network, Payload, PrivKey,
+    ScriptType,
+};
+use hdwallet::{
+    backend::{Backend, DefaultBackend},
+    secp256k1::{PublicKey, Scalar, SecretKey},
+    zeroize::Zeroize,
+    ExtendedPrivKey,
+};
+use std::{fmt, str::FromStr};
+use unicode_normalization::{char::canonical_combining_class, UnicodeNormalization};
+
+/// Salt prefix of the PBKDF2 seed stretching, before the passphrase.
+const SALT_PREFIX: &[u8] = b"electrum";
+const PBKDF2_ROUNDS: u32 = 2048;
+/// Rounds of SHA256 stretching an old-style seed.
+const OLD_STRETCH_ROUNDS: usize = 100_000;
+/// Words of the old-style mnemonic word list.
+const OLD_WORDLIST_LEN: usize = 1626;
+
+/// Version of an Electrum new-style seed, the hex prefix of `HMAC-SHA512("Seed version", seed)`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ElectrumSeedType {
+    /// `01`, P2PKH wallet at `m`.
+    Standard,
+    /// `100`, P2WPKH wallet at `m/0'`.
+    Segwit,
+    /// `101`, 2-of-3 P2SH multisig with TrustedCoin, keys at `m/0'` and `m/1'`.
+    TwoFactor,
+    /// `102`, 2-of-3 P2WSH multisig with TrustedCoin, keys at `m/0'` and `m/1'`.
+    TwoFactorSegwit,
+}
+
+impl ElectrumSeedType {
+    const ALL: [ElectrumSeedType; 4] = [
+        ElectrumSeedType::Standard,
+        ElectrumSeedType::Segwit,
+        ElectrumSeedType::TwoFactor,
+        ElectrumSeedType::TwoFactorSegwit,
+    ];
+
+    fn prefix(self) -> &'static str {
+        match self {
+            ElectrumSeedType::Standard => "01",
+            ElectrumSeedType::Segwit => "100",
+            ElectrumSeedType::TwoFactor => "101",
+            ElectrumSeedType::TwoFactorSegwit => "102",
+        }
+    }
+
+    /// Derivation paths of the wallet keys from the master key.
+    pub fn derivation_paths(self) -> &'static [&'static str] {
+        match self {
+            ElectrumSeedType::Standard => &["m"],
+            ElectrumSeedType::Segwit => &["m/0H"],
+            ElectrumSeedType::TwoFactor | ElectrumSeedType::TwoFactorSegwit => &["m/0H", "m/1H"],
+        }
+    }
+
+    /// Script type of the wallet keys, `zprv` for segwit and `Zprv` for two-factor segwit.
+    pub fn script_type(self) -> ScriptType {
+        match self {
+            ElectrumSeedType::Standard | ElectrumSeedType::TwoFactor => ScriptType::Legacy,
+            ElectrumSeedType::Segwit => ScriptType::P2wpkh,
+            ElectrumSeedType::TwoFactorSegwit => ScriptType::P2wsh,
+        }
+    }
+}
+
+/// Electrum new-style seed, a mnemonic whose HMAC carries the seed version.
+///
+/// Any words make a seed as long as the version matches, there is no word list. The mnemonic is
+/// normalized as Electrum does: NFKD, lowercase, without accents, single spaces and no spaces
+/// between CJK characters. It is zeroized on drop.
+///
+/// ```rust
+/// use hdwallet_bitcoin::{AddressType, ElectrumSeed, ElectrumSeedType, Network, PubKey};
+///
+/// let seed = ElectrumSeed::parse("cycle rocket west magnet parrot shuffle foot correct salt library feed song").unwrap();
+/// assert_eq!(seed.seed_type(), ElectrumSeedType::Standard);
+/// let keys = seed.keys("", Network::MainNet).unwrap();
+/// let address = PubKey::from_private_key(&keys[0])
+///     .unwrap()
+///     .derive_path("m/0/0".into())
+///     .unwrap()
+///     .address(AddressType::P2pkh)
+///     .unwrap();
+/// assert_eq!(address.to_string(), "1NNkttn1YvVGdqBW4PR6zvc3Zx3H5owKRf");
+/// ```
+pub struct ElectrumSeed {
+    seed_type: ElectrumSeedType,
+    mnemonic: String,
+}
+
+impl ElectrumSeed {
+    /// Parse a mnemonic, [`Error::InvalidSeed`] if it has no known version.
+    ///
+    /// Two-factor seeds have 12 words, or at least 20 for the seeds of Electrum before 2.7.
+    pub fn parse(mnemonic: &str) -> Result<Self, Error> {
+        let words = mnemonic.split_whitespace().count();
+        let mnemonic = normalize(mnemonic);
+        let version = hex::encode(DefaultBackend::hmac_sha512(
+            &DefaultBackend::hmac_sha512_key(b"Seed version"),
+            &[mnemonic.as_bytes()],
+        ));
+        let seed_type = ElectrumSeedType::ALL
+            .iter()
+            .copied()
+            .find(|seed_type| version.starts_with(seed_type.prefix()))
+            .ok_or(Error::InvalidSeed)?;
+        if seed_type == ElectrumSeedType::TwoFactor && words != 12 && words < 20 {
+            return Err(Error::InvalidSeed);
+        }
+        Ok(ElectrumSeed {
+            seed_type,
+            mnemonic,
+        })
+    }
+
+    pub fn seed_type(&self) -> ElectrumSeedType {
+        self.seed_type
+    }
+
+    /// BIP-32 seed, PBKDF2-HMAC-SHA512 of the mnemonic salted with `electrum` and the
+    /// normalized passphrase.
+    pub fn to_seed(&self, passphrase: &str) -> [u8; 64] {
+        to_seed(&self.mnemonic, passphrase)
+    }
+
+    /// Master key of the seed.
+    pub fn master_key(&self, passphrase: &str) -> Result<ExtendedPrivKey, Error> {
+        let mut seed = self.to_seed(passphrase);
+        let master_key = ExtendedPrivKey::with_seed(&seed);
+        seed.zeroize();
+        Ok(master_key?)
+    }
+
+    /// Keys of the Electrum wallet at the [`ElectrumSeedType::derivation_paths`], with the script
+    /// type of the seed.
+    ///
+    /// The two keys of a two-factor seed before Electrum 2.7 are the master keys of its first 12
+    /// words and of the remaining words, such seeds have no passphrase.
+    pub fn keys(&self, passphrase: &str, network: Network) -> Result<Vec<PrivKey>, Error> {
+        let words: Vec<&str> = self.mnemonic.split(' ').collect();
+        if self.seed_type == ElectrumSeedType::TwoFactor && words.len() >= 20 {
+            if !passphrase.is_empty() {
+                return Err(Error::InvalidSeed);
+            }
+            return [&words[..12], &words[12..]]
+                .iter()
+                .map(|words| {
+                    let mut seed = to_seed(&words.join(" "), "");
+                    let master_key = ExtendedPrivKey::with_seed(&seed);
+                    seed.zeroize();
+                    Ok(PrivKey::from_master_key(master_key?, network))
+                })
+                .collect();
+        }
+        let master_key = PrivKey::from_master_key(self.master_key(passphrase)?, network);
+        self.seed_type
+            .derivation_paths()
+            .iter()
+            .map(|path| {
+                let mut key = master_key.derive_path((*path).into())?;
+                key.script_type = self.seed_type.script_type();
+                Ok(key)
+            })
+            .collect()
+    }
+}
+
+impl Drop for ElectrumSeed {
+    fn drop(&mut self) {
+        self.mnemonic.zeroize();
+    }
+}
+
+/// Master public key of an Electrum old-style seed, watch-only.
+///
+/// Old-style wallets derive uncompressed P2PKH addresses from the 64-byte master public key,
+/// printed as 128 hex characters without the `04` prefix.
+///
+/// ```rust
+/// use hdwallet_bitcoin::{Chain, ElectrumOldPubKey, Network};
+///
+/// let master_key: ElectrumOldPubKey = "e9d4b7866dd1e91c862aebf62a49548c7dbf7bcc6e4b7b8c9da820c7737968df9c09d5a3e271dc814a29981f81b3faaf2737b551ef5dcc6189cf0f8252c442b3"
+///     .parse()
+///     .unwrap();
+/// let address = master_key.address(Chain::External, 0, Network::MainNet).unwrap();
+/// assert_eq!(address.to_string(), "1FJEEB8ihPMbzs2SkLmr37dHyRFzakqUmo");
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct ElectrumOldPubKey {
+    pub public_key: PublicKey,
+}
+
+impl ElectrumOldPubKey {
+    /// Master public key of an old-style hex seed of 16 or 32 bytes.
+    pub fn from_seed(seed: &str) -> Result<Self, Error> {
+        let seed = normalize(seed);
+        match hex::decode(&seed) {
+            Ok(bytes) if bytes.len() == 16 || bytes.len() == 32 => {}
+            _ => return Err(Error::InvalidSeed),
+        }
+        // the hex characters are stretched, not the bytes
+        let mut secret = sha256(&[seed.as_bytes(), seed.as_bytes()].concat());
+        for _ in 1..OLD_STRETCH_ROUNDS {
+            secret = sha256(&[&secret[..], seed.as_bytes()].concat());
+        }
+        let secret_key = SecretKey::from_slice(&secret);
+        secret.zeroize();
+        Ok(ElectrumOldPubKey {
+            public_key: secret_key?.public_key(secp256k1_sign_only()),
+        })
+    }
+
+    /// Master public key of an old-style mnemonic of 12 or 24 words.
+    ///
+    /// `wordlist` is the 1626-word list of Electrum's `old_mnemonic.py`, which this crate
+    /// doesn't bundle.
+    pub fn from_mnemonic(mnemonic: &str, wordlist: &[&str]) -> Result<Self, Error> {
+        if wordlist.len() != OLD_WORDLIST_LEN {
+            return Err(Error::InvalidLength);
+        }
+        let mnemonic = normalize(mnemonic);
+        let words = mnemonic
+            .split(' ')
+            .map(|word| wordlist.iter().position(|w| *w == word))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(Error::InvalidSeed)?;
+        if words.len() != 12 && words.len() != 24 {
+            return Err(Error::InvalidSeed);
+        }
+        // three words encode 32 bits as differences modulo the word count
+        let n = OLD_WORDLIST_LEN;
+        let mut seed = String::new();
+        for words in words.chunks(3) {
+            let x = words[0]
+                + n * ((n + words[1] - words[0]) % n)
+                + n * n * ((n + words[2] - words[1]) % n);
+            let x = u32::try_from(x).map_err(|_| Error::InvalidSeed)?;
+            seed.push_str(&format!("{:08x}", x));
+        }
+        let master_key = ElectrumOldPubKey::from_seed(&seed);
+        seed.zeroize();
+        master_key
+    }
+
+    /// Public key at `index` of the receive or change chain.
+    pub fn public_key(&self, chain: Chain, index: u32) -> Result<PublicKey, Error> {
+        let for_change = match chain {
+            Chain::External => 0,
+            Chain::Internal => 1,
+        };
+        let mut data = format!("{}:{}:", index, for_change).into_bytes();
+        data.extend_from_slice(&self.public_key.serialize_uncompressed()[1..]);
+        let tweak = Scalar::from_be_bytes(sha256d(&data)).map_err(|_| Error::InvalidSeed)?;
+        Ok(self
+            .public_key
+            .add_exp_tweak(secp256k1_verify_only(), &tweak)?)
+    }
+
+    /// Uncompressed P2PKH address at `index` of the receive or change chain.
+    pub fn address(&self, chain: Chain, index: u32, network: Network) -> Result<Address, Error> {
+        let public_key = self.public_key(chain, index)?;
+        Address::new(
+            network,
+            Payload::PubkeyHash(hash160(&public_key.serialize_uncompressed())),
+        )
+    }
+}
+
+impl FromStr for ElectrumOldPubKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let mut bytes = hex::decode(s)
+            .ok()
+            .filter(|bytes| bytes.len() == 64)
+            .ok_or(Error::InvalidLength)?;
+        bytes.insert(0, 0x04);
+        Ok(ElectrumOldPubKey {
+            public_key: PublicKey::from_slice(&bytes)?,
+        })
+    }
+}
+
+impl fmt::Display for ElectrumOldPubKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&hex::encode(&self.public_key.serialize_uncompressed()[1..]))
+    }
+}
+
+fn to_seed(mnemonic: &str, passphrase: &str) -> [u8; 64] {
+    let mut salt = SALT_PREFIX.to_vec();
+    salt.extend_from_slice(normalize(passphrase).as_bytes());
+    let seed = pbkdf2_hmac_sha512(mnemonic.as_bytes(), &salt, PBKDF2_ROUNDS);
+    salt.zeroize();
+    seed
+}
+
+/// PBKDF2-HMAC-SHA512 of a single 64-byte block.
+fn pbkdf2_hmac_sha512(password: &[u8], salt: &[u8], rounds: u32) -> [u8; 64] {
+    let key = DefaultBackend::hmac_sha512_key(password);
+    let mut u = DefaultBackend::hmac_sha512(&key, &[salt, &1u32.to_be_bytes()]);
+    let mut output = u;
+    for _ in 1..rounds {
+        u = DefaultBackend::hmac_sha512(&key, &[&u]);
+        for (byte, u) in output.iter_mut().zip(u.iter()) {
+            *byte ^= u;
+        }
+    }
+    u.zeroize();
+    output
+}
+
+/// Normalize a mnemonic or passphrase as Electrum does.
+fn normalize(s: &str) -> String {
+    let s: String = s
+        .nfkd()
+        .flat_map(char::to_lowercase)
+        .filter(|c| canonical_combining_class(*c) == 0)
+        .collect();
+    let chars: Vec<char> = s
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .collect();
+    // a space is never first or last
+    chars
+        .iter()
+        .enumerate()
+        .filter(|(i, c)| !(**c == ' ' && is_cjk(chars[i - 1]) && is_cjk(chars[i + 1])))
+        .map(|(_, c)| *c)
+        .collect()
+}
+
+/// Whether `c` belongs to the CJK, kana, hangul and related blocks Electrum joins without
+/// spaces.
+fn is_cjk(c: char) -> bool {
+    matches!(
+        u32::from(c),
+        0x1100..=0x11ff
+            | 0x2e80..=0x2fdf
+            | 0x2ff0..=0x2fff
+            | 0x3040..=0x31ff
+            | 0x3400..=0x4dbf
+            | 0x4e00..=0x9fff
+            | 0xa000..=0xa4ff
+            | 0xa960..=0xa97f
+            | 0xac00..=0xd7ff
+            | 0xf900..=0xfaff
+            | 0xff00..=0xffef
+            | 0x16f00..=0x16f9f
+            | 0x1b000..=0x1b0ff
+            | 0x20000..=0x2a6df
+            | 0x2a700..=0x2b81f
+            | 0x2f800..=0x2fa1d
+            | 0xe0100..=0xe01ef
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PubKey;
+    use hdwallet::traits::Serialize;
+
+    const STANDARD: &str =
+        "cycle rocket west magnet parrot shuffle foot correct salt library feed song";
+    const SEGWIT: &str =
+        "bitter grass shiver impose acquire brush forget axis eager alone wine silver";
+    const TWO_FACTOR: &str = "kiss live scene rude gate step hip quarter bunker oxygen motor glove";
+    const OLD_MPK: &str = "e9d4b7866dd1e91c862aebf62a49548c7dbf7bcc6e4b7b8c9da820c7737968df9c09d5a3e271dc814a29981f81b3faaf2737b551ef5dcc6189cf0f8252c442b3";
+
+    fn address(key: &PrivKey, chain: u32) -> String {
+        let key = PubKey::from_private_key(key)
+            .unwrap()
+            .derive_path(format!("m/{}/0", chain).as_str().into())
+            .unwrap();
+        key.address(key.script_type.address_type().unwrap())
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_new_seeds() {
+        let seed = ElectrumSeed::parse(STANDARD).unwrap();
+        assert_eq!(seed.seed_type(), ElectrumSeedType::Standard);
+        let keys = seed.keys("", Network::MainNet).unwrap();
+        let xprv: String = keys[0].serialize();
+        assert_eq!(xprv, "xprv9s21ZrQH143K32jECVM729vWgGq4mUDJCk1ozqAStTphzQtCTuoFmFafNoG1g55iCnBTXUzz3zWnDb5CVLGiFvmaZjuazHDL8a81cPQ8KL6");
+        assert_eq!(address(&keys[0], 1), "1KSezYMhAJMWqFbVFB2JshYg69UpmEXR4D");
+
+        let seed = ElectrumSeed::parse(SEGWIT).unwrap();
+        assert_eq!(seed.seed_type(), ElectrumSeedType::Segwit);
+        let keys = seed.keys("", Network::MainNet).unwrap();
+        assert_eq!(keys.len(), 1);
+        let zpub: String = PubKey::from_private_key(&keys[0]).unwrap().serialize();
+        assert!(zpub.starts_with("zpub"));
+        assert_eq!(
+            address(&keys[0], 0),
+            "bc1q3g5tmkmlvxryhh843v4dz026avatc0zzr6h3af"
+        );
+        assert_eq!(
+            address(&keys[0], 1),
+            "bc1qdy94n2q5qcp0kg7v9yzwe6wvfkhnvyzje7nx2p"
+        );
+
+        let seed = ElectrumSeed::parse(TWO_FACTOR).unwrap();
+        assert_eq!(seed.seed_type(), ElectrumSeedType::TwoFactor);
+        let keys = seed.keys("passphrase", Network::MainNet).unwrap();
+        let master_key =
+            PrivKey::from_master_key(seed.master_key("passphrase").unwrap(), Network::MainNet);
+        assert_eq!(keys[0], master_key.derive_path("m/0H".into()).unwrap());
+        assert_eq!(keys[1], master_key.derive_path("m/1H".into()).unwrap());
+
+        // BIP-39 mnemonics have no Electrum version
+        assert_eq!(
+            ElectrumSeed::parse("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about").err(),
+            Some(Error::InvalidSeed)
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("  Cycle\tRÓCKET\n  west "), "cycle rocket west");
+        assert_eq!(normalize("ａｂｃ"), "abc");
+        assert_eq!(normalize("あ い b　う"), "あい b う");
+        let seed = ElectrumSeed::parse(&STANDARD.to_uppercase().replace(' ', "  ")).unwrap();
+        assert_eq!(
+            seed.to_seed("Pässphrase"),
+            ElectrumSeed::parse(STANDARD).unwrap().to_seed("passphrase")
+        );
+    }
+
+    #[test]
+    fn test_old_seeds() {
+        let master_key: ElectrumOldPubKey = OLD_MPK.parse().unwrap();
+        assert_eq!(master_key.to_string(), OLD_MPK);
+        assert_eq!(
+            master_key
+                .address(Chain::Internal, 0, Network::MainNet)
+                .map(|address| address.to_string()),
+            Ok("1KRW8pH6HFHZh889VDq6fEKvmrsmApwNfe".to_string())
+        );
+
+        let seed = "8edad31a95e7d59f8837667510d75a4d";
+        let master_key = ElectrumOldPubKey::from_seed(seed).unwrap();
+        assert_eq!(master_key.to_string(), "440cb714b7799f148ef7f250aae0e3f0806f30404607ba0441613e6224bd310fe821ae2fb12ae804db359cf989f21405b9ce13289a0c1c4065cd88edccdb24a4");
+        assert_eq!(
+            master_key.address(Chain::External, 0, Network::Unknown([0; 4])),
+            Err(Error::UnknownVersion)
+        );
+        // a stand-in word list, the arithmetic doesn't depend on the words
+        let words: Vec<String> = (0..OLD_WORDLIST_LEN).map(|i| format!("w{}", i)).collect();
+        let wordlist: Vec<&str> = words.iter().map(String::as_str).collect();
+        let mnemonic = "w1074 w280 w1186 w1073 w1486 w811 w467 w1096 w334 w419 w206 w312";
+        assert_eq!(
+            ElectrumOldPubKey::from_mnemonic(mnemonic, &wordlist),
+            Ok(master_key)
+        );
+        assert_eq!(
+            ElectrumOldPubKey::from_mnemonic("w1 w2 w3", &wordlist),
+            Err(Error::InvalidSeed)
+        );
+
+        // the start of Electrum's word list covers the words of its old seed vector
+        let mut wordlist: Vec<&str> = include_str!("electrum/old_wordlist_start.txt")
+            .lines()
+            .collect();
+        wordlist.extend(words[wordlist.len()..].iter().map(String::as_str));
+        let mnemonic =
+            "powerful random nobody notice nothing important anyway look away hidden message over";
+        assert_eq!(
+            ElectrumOldPubKey::from_mnemonic(mnemonic, &wordlist).map(|key| key.to_string()),
+            Ok(OLD_MPK.to_string())
+        );
+        assert_eq!(
+            ElectrumOldPubKey::from_seed("8edad31a"),
+            Err(Error::InvalidSeed)
+        );
+    }
+}
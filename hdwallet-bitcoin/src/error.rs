@@ -1,4 +1,5 @@
-use hdwallet::secp256k1;
+use hdwallet::{secp256k1, ChainPathError};
+use std::fmt;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Error {
@@ -6,10 +7,83 @@ pub enum Error {
     UnknownVersion,
     Secp(secp256k1::Error),
     InvalidBase58,
+    InvalidLength,
+    KeyIndexOutOfRange,
+    ChainPath(ChainPathError),
+    MaxDepthExceeded,
+    Rng,
+    InvalidAddress,
+    /// WIF version byte of no known network, or of another network than the expected one.
+    UnknownNetworkByte(u8),
+    InvalidDescriptor,
+    /// Key origin path or fingerprint disagrees with the extended key.
+    KeyOriginMismatch,
+    InvalidTransaction,
+    /// Malformed PSBT, or a PSBT field inconsistent with the transaction.
+    InvalidPsbt,
+    /// Multisig threshold, key count or script type out of range.
+    InvalidMultisig,
+    /// Malformed message signature, or one not made by the key of the address.
+    InvalidSignature,
+    /// Malformed BIP-47 payment code or notification payload.
+    InvalidPaymentCode,
+    /// Malformed MuSig2 nonce or partial signature, or a signer outside the aggregate key.
+    InvalidMusig,
+    /// Electrum seed of no known version, or malformed old-style seed.
+    InvalidSeed,
+    /// Descriptor this crate can't derive, e.g. a taproot script tree.
+    UnsupportedDescriptor,
 }
 
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::MisChecksum => write!(f, "Mismatched checksum"),
+            Error::UnknownVersion => write!(f, "Unknown version"),
+            Error::Secp(err) => write!(f, "Secp256k1 error {}", err),
+            Error::InvalidBase58 => write!(f, "Invalid base58"),
+            Error::InvalidLength => write!(f, "Invalid length"),
+            Error::KeyIndexOutOfRange => write!(f, "Key index out of range"),
+            Error::ChainPath(err) => write!(f, "Chain path {}", err),
+            Error::MaxDepthExceeded => write!(f, "Derivation depth exceeds 255"),
+            Error::Rng => write!(f, "rand error"),
+            Error::InvalidAddress => write!(f, "Invalid address"),
+            Error::UnknownNetworkByte(byte) => write!(f, "Unknown network byte {:#04x}", byte),
+            Error::InvalidDescriptor => write!(f, "Invalid output descriptor"),
+            Error::KeyOriginMismatch => write!(f, "Key origin mismatches the key"),
+            Error::InvalidTransaction => write!(f, "Invalid transaction"),
+            Error::InvalidPsbt => write!(f, "Invalid PSBT"),
+            Error::InvalidMultisig => write!(f, "Invalid multisig"),
+            Error::InvalidSignature => write!(f, "Invalid message signature"),
+            Error::InvalidPaymentCode => write!(f, "Invalid payment code"),
+            Error::InvalidMusig => write!(f, "Invalid MuSig2 input"),
+            Error::InvalidSeed => write!(f, "Invalid Electrum seed"),
+            Error::UnsupportedDescriptor => write!(f, "Unsupported output descriptor"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
 impl From<secp256k1::Error> for Error {
     fn from(err: secp256k1::Error) -> Self {
         Error::Secp(err)
     }
 }
+
+impl From<ChainPathError> for Error {
+    fn from(err: ChainPathError) -> Self {
+        Error::ChainPath(err)
+    }
+}
+
+impl From<hdwallet::error::Error> for Error {
+    fn from(err: hdwallet::error::Error) -> Self {
+        match err {
+            hdwallet::error::Error::KeyIndexOutOfRange => Error::KeyIndexOutOfRange,
+            hdwallet::error::Error::ChainPath(err) => Error::ChainPath(err),
+            hdwallet::error::Error::Secp(err) => Error::Secp(err),
+            hdwallet::error::Error::Rng(_) => Error::Rng,
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use hdwallet::backend::{Backend, DefaultBackend};
+use ripemd::{Digest, Ripemd160};
+
+/// RIPEMD160(SHA256(data))
+pub(crate) fn hash160(data: &[u8]) -> [u8; 20] {
+    let mut hasher = Ripemd160::new();
+    hasher.update(DefaultBackend::sha256(&[data]));
+    hasher.finalize().into()
+}
+
+/// SHA256(data)
+pub(crate) fn sha256(data: &[u8]) -> [u8; 32] {
+    DefaultBackend::sha256(&[data])
+}
+
+/// SHA256(SHA256(data))
+pub(crate) fn sha256d(data: &[u8]) -> [u8; 32] {
+    DefaultBackend::sha256(&[&DefaultBackend::sha256(&[data])])
+}
+
+/// BIP-340 tagged hash, SHA256(SHA256(tag) || SHA256(tag) || data).
+pub(crate) fn tagged_hash(tag: &str, data: &[&[u8]]) -> [u8; 32] {
+    let tag_hash = DefaultBackend::sha256(&[tag.as_bytes()]);
+    let mut parts: Vec<&[u8]> = vec![&tag_hash, &tag_hash];
+    parts.extend_from_slice(data);
+    DefaultBackend::sha256(&parts)
+}
@@ -17,31 +17,107 @@
 //! let (extended_key, derivation) = key_chain.derive_private_key("m/1H/0".into()).expect("derive ExtendedPrivKey");
 //! let key = BitcoinPrivKey {
 //!     network: BitcoinNetwork::MainNet,
+//!     script_type: Default::default(),
 //!     derivation,
+//!     parent_fingerprint: None,
 //!     extended_key,
 //! };
 //! let serialized_key: String = key.serialize();
 //! println!("derive m/1H/0 key: {}", serialized_key);
 //! ```
 //!
+//! [`PubKey::address`] encodes P2PKH, P2SH-P2WPKH, P2WPKH and P2TR addresses, and [`Address`]
+//! parses and validates them:
+//!
+//! ```rust
+//! # use hdwallet::ExtendedPrivKey;
+//! use hdwallet_bitcoin::{Address, AddressType, Network, PrivKey, PubKey};
+//!
+//! # let master_key = ExtendedPrivKey::random(&mut rand::thread_rng()).expect("master key");
+//! let account = PrivKey::from_master_key(master_key, Network::MainNet)
+//!     .derive_path("m/84H/0H/0H".into())
+//!     .expect("derive");
+//! let receive = PubKey::from_private_key(&account)
+//!     .expect("public key")
+//!     .derive_path("m/0/0".into())
+//!     .expect("derive");
+//! let address = receive.address(AddressType::P2wpkh).expect("address").to_string();
+//! assert!(Address::parse(&address, Network::MainNet).is_ok());
+//! ```
+//!
 
+mod account;
+mod address;
+mod base64;
+mod descriptor;
+mod electrum;
 mod error;
+mod hash;
+mod message;
+mod multisig;
+mod musig;
+mod network;
+mod origin;
+mod payment_code;
+mod psbt;
 mod serialize;
+mod sighash;
+mod silent_payment;
+mod taproot;
+mod transaction;
+mod wif;
 
-use hdwallet::{Derivation, ExtendedPrivKey, ExtendedPubKey};
+use hdwallet::{
+    secp256k1::{Secp256k1, SignOnly, VerifyOnly},
+    zeroize::Zeroize,
+    ChainPath, ChainPathError, Derivation, ExtendedPrivKey, ExtendedPubKey, KeyIndex, SubPath,
+};
+use once_cell::race::OnceBox;
+use serialize::{fingerprint, parent_fingerprint};
 
+pub use account::{Account, Chain, Purpose, UsageOracle, DEFAULT_GAP_LIMIT};
+pub use address::{Address, AddressType, Payload};
+pub use descriptor::{
+    Descriptor, DescriptorKey, ExtendedKey, Multi, SingleKey, WalletPolicy, Wildcard,
+};
+pub use electrum::{ElectrumOldPubKey, ElectrumSeed, ElectrumSeedType};
 pub use error::Error;
+pub use message::{sign_message, sign_message_bip322, verify_message};
+pub use multisig::{cosigner_key, SortedMulti};
+pub use musig::{
+    nonce_gen, AggNonce, KeyAggContext, PartialSignature, PubNonce, SecNonce, Session,
+};
+pub use network::{Network, NetworkParams};
+pub use origin::{KeyOrigin, OriginPubKey};
+pub use payment_code::{PaymentCode, PaymentCodeAccount};
+pub use psbt::{Psbt, PsbtMap};
+pub use serialize::ScriptType;
+pub use silent_payment::{
+    silent_payment_outputs, SilentPaymentAccount, SilentPaymentAddress, SilentPaymentOutput,
+};
+pub use taproot::{TaprootPrivKeyExt, TaprootPubKeyExt};
+pub use transaction::{OutPoint, Transaction, TxIn, TxOut};
+pub use wif::Wif;
+
+/// Global signing context.
+fn secp256k1_sign_only() -> &'static Secp256k1<SignOnly> {
+    static CONTEXT: OnceBox<Secp256k1<SignOnly>> = OnceBox::new();
+    CONTEXT.get_or_init(|| Box::new(Secp256k1::signing_only()))
+}
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub enum Network {
-    MainNet,
-    TestNet,
+/// Global verification context.
+fn secp256k1_verify_only() -> &'static Secp256k1<VerifyOnly> {
+    static CONTEXT: OnceBox<Secp256k1<VerifyOnly>> = OnceBox::new();
+    CONTEXT.get_or_init(|| Box::new(Secp256k1::verification_only()))
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PrivKey {
     pub network: Network,
+    pub script_type: ScriptType,
     pub derivation: Derivation,
+    /// Parent key fingerprint, used when `derivation` has no parent key, e.g. a deserialized key.
+    pub parent_fingerprint: Option<[u8; 4]>,
     pub extended_key: ExtendedPrivKey,
 }
 
@@ -50,33 +126,172 @@ impl PrivKey {
         PrivKey {
             extended_key,
             network,
+            script_type: ScriptType::Legacy,
             derivation: Derivation::master(),
+            parent_fingerprint: None,
+        }
+    }
+
+    /// Same key serialized with the SLIP-132 version of `script_type`, e.g. xprv to zprv.
+    pub fn with_script_type(self, script_type: ScriptType) -> Self {
+        PrivKey {
+            script_type,
+            ..self
         }
     }
+
+    /// Key fingerprint, the first 32 bits of the key identifier.
+    pub fn fingerprint(&self) -> [u8; 4] {
+        fingerprint(&self.extended_key.public_key())
+    }
+
+    /// Fingerprint of the parent key, all zeros for master key.
+    pub fn parent_fingerprint(&self) -> [u8; 4] {
+        parent_fingerprint(&self.derivation, self.parent_fingerprint)
+    }
+
+    /// Derive a child key, the derivation info is updated accordingly.
+    pub fn derive_private_key(&self, key_index: KeyIndex) -> Result<PrivKey, Error> {
+        let extended_key = self.extended_key.derive_private_key(key_index)?;
+        Ok(PrivKey {
+            network: self.network,
+            script_type: self.script_type,
+            derivation: Derivation {
+                depth: child_depth(&self.derivation)?,
+                parent_key: Some(self.extended_key.clone()),
+                key_index: Some(key_index),
+            },
+            parent_fingerprint: None,
+            extended_key,
+        })
+    }
+
+    /// Derive a descendant key, `m` in chain_path denotes this key.
+    pub fn derive_path(&self, chain_path: ChainPath) -> Result<PrivKey, Error> {
+        derive_path(self, chain_path, PrivKey::derive_private_key)
+    }
+}
+
+/// Erase the key and the parent key kept in derivation.
+impl Zeroize for PrivKey {
+    fn zeroize(&mut self) {
+        self.extended_key.zeroize();
+        self.derivation.zeroize();
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PubKey {
     pub network: Network,
+    pub script_type: ScriptType,
     pub derivation: Derivation,
+    /// Parent key fingerprint, used when `derivation` has no parent key, e.g. a deserialized key.
+    pub parent_fingerprint: Option<[u8; 4]>,
     pub extended_key: ExtendedPubKey,
 }
 
 impl PubKey {
-    pub fn from_private_key(priv_key: &PrivKey) -> PubKey {
+    /// Public key of `priv_key`, [`Error::UnknownVersion`] if the private key was decoded with
+    /// unregistered version bytes, as its public key version is unknown.
+    pub fn from_private_key(priv_key: &PrivKey) -> Result<PubKey, Error> {
+        if let Network::Unknown(_) = priv_key.network {
+            return Err(Error::UnknownVersion);
+        }
         let extended_pub_key = ExtendedPubKey::from_private_key(&priv_key.extended_key);
-        PubKey {
+        Ok(PubKey {
             network: priv_key.network,
+            script_type: priv_key.script_type,
             derivation: priv_key.derivation.clone(),
+            parent_fingerprint: priv_key.parent_fingerprint,
             extended_key: extended_pub_key,
+        })
+    }
+
+    /// Same key serialized with the SLIP-132 version of `script_type`, e.g. zpub to xpub.
+    ///
+    /// ```rust
+    /// use hdwallet::traits::{Deserialize, Serialize};
+    /// use hdwallet_bitcoin::{PubKey, ScriptType};
+    ///
+    /// let zpub = "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs";
+    /// let xpub = PubKey::deserialize(zpub.to_string())
+    ///     .unwrap()
+    ///     .with_script_type(ScriptType::Legacy);
+    /// assert_eq!(Serialize::<String>::serialize(&xpub), "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V");
+    /// ```
+    pub fn with_script_type(self, script_type: ScriptType) -> Self {
+        PubKey {
+            script_type,
+            ..self
         }
     }
+
+    /// Key fingerprint, the first 32 bits of the key identifier.
+    pub fn fingerprint(&self) -> [u8; 4] {
+        fingerprint(&self.extended_key.public_key)
+    }
+
+    /// Fingerprint of the parent key, all zeros for master key.
+    pub fn parent_fingerprint(&self) -> [u8; 4] {
+        parent_fingerprint(&self.derivation, self.parent_fingerprint)
+    }
+
+    /// Derive a normal child key, the derivation info is updated accordingly.
+    pub fn derive_public_key(&self, key_index: KeyIndex) -> Result<PubKey, Error> {
+        let extended_key = self.extended_key.derive_public_key(key_index)?;
+        Ok(PubKey {
+            network: self.network,
+            script_type: self.script_type,
+            derivation: Derivation {
+                depth: child_depth(&self.derivation)?,
+                parent_key: None,
+                key_index: Some(key_index),
+            },
+            parent_fingerprint: Some(self.fingerprint()),
+            extended_key,
+        })
+    }
+
+    /// Derive a descendant key, `m` in chain_path denotes this key.
+    pub fn derive_path(&self, chain_path: ChainPath) -> Result<PubKey, Error> {
+        derive_path(self, chain_path, PubKey::derive_public_key)
+    }
+}
+
+fn child_depth(derivation: &Derivation) -> Result<u8, Error> {
+    derivation
+        .depth
+        .checked_add(1)
+        .ok_or(Error::MaxDepthExceeded)
+}
+
+fn derive_path<K: Clone>(
+    key: &K,
+    chain_path: ChainPath,
+    derive: impl Fn(&K, KeyIndex) -> Result<K, Error>,
+) -> Result<K, Error> {
+    let mut iter = chain_path.iter();
+    // chain_path must start with root
+    if iter.next() != Some(Ok(SubPath::Root)) {
+        return Err(ChainPathError::Invalid.into());
+    }
+    let mut key = key.clone();
+    for sub_path in iter {
+        match sub_path? {
+            SubPath::Child(key_index) => key = derive(&key, key_index)?,
+            SubPath::Root => return Err(ChainPathError::Invalid.into()),
+        }
+    }
+    Ok(key)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use hdwallet::{traits::Serialize, ChainPath, DefaultKeyChain, KeyChain};
+    use hdwallet::{
+        traits::{Deserialize, Serialize},
+        ChainPath, DefaultKeyChain, KeyChain,
+    };
 
     #[test]
     fn test_bip32_vector_1() {
@@ -94,14 +309,33 @@ mod tests {
             let (extended_key, derivation) = key_chain.derive_private_key(ChainPath::from(*chain_path)).expect("fetch key");
             let priv_key = PrivKey{
                 network: Network::MainNet,
+                script_type: ScriptType::Legacy,
                 derivation,
+                parent_fingerprint: None,
                 extended_key
             };
             assert_eq!(&Serialize::<String>::serialize(&priv_key), hex_priv_key);
-            assert_eq!(&Serialize::<String>::serialize(&PubKey::from_private_key(&priv_key)), hex_pub_key);
+            assert_eq!(&Serialize::<String>::serialize(&PubKey::from_private_key(&priv_key).expect("public key")), hex_pub_key);
         }
     }
 
+    #[test]
+    fn test_derive_from_deserialized_key() {
+        // BIP-32 test vector 1, m/0H/1 derives m/0H/1/2H/2
+        let priv_key = PrivKey::deserialize("xprv9wTYmMFdV23N2TdNG573QoEsfRrWKQgWeibmLntzniatZvR9BmLnvSxqu53Kw1UmYPxLgboyZQaXwTCg8MSY3H2EU4pWcQDnRnrVA1xe8fs".to_string()).expect("deserialize");
+        let child_key = priv_key.derive_path("m/2H/2".into()).expect("derive");
+        assert_eq!(&Serialize::<String>::serialize(&child_key), "xprvA2JDeKCSNNZky6uBCviVfJSKyQ1mDYahRjijr5idH2WwLsEd4Hsb2Tyh8RfQMuPh7f7RtyzTtdrbdqqsunu5Mm3wDvUAKRHSC34sJ7in334");
+        // m/0H/1/2H derives m/0H/1/2H/2/1000000000 from public key
+        let pub_key = PubKey::deserialize("xpub6D4BDPcP2GT577Vvch3R8wDkScZWzQzMMUm3PWbmWvVJrZwQY4VUNgqFJPMM3No2dFDFGTsxxpG5uJh7n7epu4trkrX7x7DogT5Uv6fcLW5".to_string()).expect("deserialize");
+        let child_key = pub_key
+            .derive_path("m/2/1000000000".into())
+            .expect("derive");
+        assert_eq!(&Serialize::<String>::serialize(&child_key), "xpub6H1LXWLaKsWFhvm6RVpEL9P4KfRZSW7abD2ttkWP3SSQvnyA8FSVqNTEcYFgJS2UaFcxupHiYkro49S8yGasTvXEYBVPamhGW6cFJodrTHy");
+        assert!(pub_key.derive_path("m/2H".into()).is_err());
+        assert_eq!(hex::encode(pub_key.fingerprint()), "ee7ab90c");
+        assert_eq!(hex::encode(child_key.parent_fingerprint()), "d880d7d8");
+    }
+
     #[test]
     fn test_bip32_vector_2() {
         let seed = hex::decode("fffcf9f6f3f0edeae7e4e1dedbd8d5d2cfccc9c6c3c0bdbab7b4b1aeaba8a5a29f9c999693908d8a8784817e7b7875726f6c696663605d5a5754514e4b484542").expect("decode");
@@ -118,11 +352,13 @@ mod tests {
             let (extended_key, derivation) = key_chain.derive_private_key(ChainPath::from(*chain_path)).expect("fetch key");
             let priv_key = PrivKey{
                 network: Network::MainNet,
+                script_type: ScriptType::Legacy,
                 derivation,
+                parent_fingerprint: None,
                 extended_key
             };
             assert_eq!(&Serialize::<String>::serialize(&priv_key), hex_priv_key);
-            assert_eq!(&Serialize::<String>::serialize(&PubKey::from_private_key(&priv_key)), hex_pub_key);
+            assert_eq!(&Serialize::<String>::serialize(&PubKey::from_private_key(&priv_key).expect("public key")), hex_pub_key);
         }
     }
 
@@ -138,11 +374,13 @@ mod tests {
             let (extended_key, derivation) = key_chain.derive_private_key(ChainPath::from(*chain_path)).expect("fetch key");
             let priv_key = PrivKey{
                 network: Network::MainNet,
+                script_type: ScriptType::Legacy,
                 derivation,
+                parent_fingerprint: None,
                 extended_key
             };
             assert_eq!(&Serialize::<String>::serialize(&priv_key), hex_priv_key);
-            assert_eq!(&Serialize::<String>::serialize(&PubKey::from_private_key(&priv_key)), hex_pub_key);
+            assert_eq!(&Serialize::<String>::serialize(&PubKey::from_private_key(&priv_key).expect("public key")), hex_pub_key);
         }
     }
 }
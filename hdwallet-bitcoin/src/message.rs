@@ -0,0 +1,414 @@
+//! Signed messages proving the ownership of an address, the legacy "Bitcoin Signed Message"
+//! format with BIP-137 headers and the BIP-322 simple format.
+
+use crate::{
+    base64,
+    hash::{hash160, sha256d, tagged_hash},
+    secp256k1_sign_only, secp256k1_verify_only,
+    sighash::{self, SIGHASH_ALL, SIGHASH_DEFAULT},
+    transaction::{write_compact_size, write_var_bytes, Reader},
+    Address, AddressType, Error, Network, OutPoint, Payload, TaprootPrivKeyExt, Transaction, TxIn,
+    TxOut,
+};
+use hdwallet::{
+    secp256k1::{
+        ecdsa::{RecoverableSignature, RecoveryId, Signature},
+        schnorr, Message, PublicKey, XOnlyPublicKey,
+    },
+    ChainPath, KeyChain,
+};
+
+/// Prefix of legacy signed messages, with its length byte.
+const MESSAGE_MAGIC: &[u8] = b"\x18Bitcoin Signed Message:\n";
+
+/// Legacy signature of `message` by the key of `key_chain` at `chain_path`, base64 encoded.
+///
+/// The BIP-137 header tells the address type, one of P2PKH, P2SH-P2WPKH and P2WPKH, other
+/// address types are [`Error::InvalidAddress`].
+///
+/// ```rust
+/// use hdwallet::{DefaultKeyChain, ExtendedPrivKey, KeyChain};
+/// use hdwallet_bitcoin::{sign_message, verify_message, Address, AddressType, Network};
+///
+/// let key_chain = DefaultKeyChain::new(ExtendedPrivKey::random(&mut rand::thread_rng()).unwrap());
+/// let (key, _) = key_chain.derive_private_key("m/84H/0H/0H/0/0".into()).unwrap();
+/// let address = Address::p2wpkh(&key.public_key(), Network::MainNet).unwrap();
+/// let signature = sign_message(&key_chain, "m/84H/0H/0H/0/0".into(), AddressType::P2wpkh, b"Hello World").unwrap();
+/// assert_eq!(verify_message(&address, b"Hello World", &signature), Ok(()));
+/// ```
+pub fn sign_message(
+    key_chain: &impl KeyChain,
+    chain_path: ChainPath,
+    address_type: AddressType,
+    message: &[u8],
+) -> Result<String, Error> {
+    let header = match address_type {
+        AddressType::P2pkh => 31,
+        AddressType::P2shP2wpkh => 35,
+        AddressType::P2wpkh => 39,
+        AddressType::P2tr => return Err(Error::InvalidAddress),
+    };
+    let (key, _) = key_chain.derive_private_key(chain_path)?;
+    let message = Message::from_slice(&message_hash(message)).expect("32 bytes");
+    let (recovery_id, signature) = secp256k1_sign_only()
+        .sign_ecdsa_recoverable(&message, &key.private_key())
+        .serialize_compact();
+    let mut data = vec![header + recovery_id.to_i32() as u8];
+    data.extend_from_slice(&signature);
+    Ok(base64::encode(&data))
+}
+
+/// BIP-322 simple signature of `message` by the key of `key_chain` at `chain_path`, base64
+/// encoded.
+///
+/// Only P2WPKH and P2TR addresses have simple signatures, other address types are
+/// [`Error::InvalidAddress`].
+pub fn sign_message_bip322(
+    key_chain: &impl KeyChain,
+    chain_path: ChainPath,
+    address_type: AddressType,
+    message: &[u8],
+) -> Result<String, Error> {
+    let (key, _) = key_chain.derive_private_key(chain_path)?;
+    let public_key = key.public_key();
+    let address = match address_type {
+        AddressType::P2wpkh | AddressType::P2tr => {
+            // the network doesn't matter for the scriptPubKey
+            Address::from_public_key(&public_key, address_type, Network::MainNet)?
+        }
+        _ => return Err(Error::InvalidAddress),
+    };
+    let to_spend = to_spend(&address.script_pubkey(), message);
+    let to_sign = to_sign(&to_spend, Vec::new());
+    let witness = if address_type == AddressType::P2wpkh {
+        let script_code = Address::p2pkh(&public_key, Network::MainNet)?.script_pubkey();
+        let hash = sighash::segwit_v0(&to_sign, 0, &script_code, 0, SIGHASH_ALL);
+        let message = Message::from_slice(&hash).expect("32 bytes");
+        let signature = secp256k1_sign_only().sign_ecdsa_low_r(&message, &key.private_key());
+        let mut signature = signature.serialize_der().to_vec();
+        signature.push(SIGHASH_ALL as u8);
+        vec![signature, public_key.serialize().to_vec()]
+    } else {
+        let hash = sighash::taproot_key_path(&to_sign, 0, &to_spend.outputs, SIGHASH_DEFAULT)?;
+        let signature = key.sign_taproot(&hash, None, None)?;
+        vec![signature.as_ref().to_vec()]
+    };
+    let mut data = Vec::new();
+    write_compact_size(&mut data, witness.len() as u64);
+    for item in &witness {
+        write_var_bytes(&mut data, item);
+    }
+    Ok(base64::encode(&data))
+}
+
+/// Verify that `signature` of `message` was made by the key of `address`.
+///
+/// Both legacy and BIP-322 simple signatures are accepted. The BIP-137 header of a legacy
+/// signature doesn't need to match the address type, as Electrum signs segwit addresses with
+/// P2PKH headers. Failures are [`Error::InvalidSignature`], and [`Error::InvalidAddress`] for
+/// BIP-322 signatures of address types other than P2WPKH and P2TR.
+pub fn verify_message(address: &Address, message: &[u8], signature: &str) -> Result<(), Error> {
+    let data = base64::decode(signature).ok_or(Error::InvalidSignature)?;
+    match data.first() {
+        Some(27..=42) if data.len() == 65 => verify_legacy(address, message, &data),
+        _ => verify_bip322(address, message, &data),
+    }
+}
+
+/// Double SHA256 of the message with the legacy prefix.
+fn message_hash(message: &[u8]) -> [u8; 32] {
+    let mut data = MESSAGE_MAGIC.to_vec();
+    write_var_bytes(&mut data, message);
+    sha256d(&data)
+}
+
+fn verify_legacy(address: &Address, message: &[u8], data: &[u8]) -> Result<(), Error> {
+    let header = data[0] - 27;
+    let recovery_id =
+        RecoveryId::from_i32(i32::from(header & 3)).map_err(|_| Error::InvalidSignature)?;
+    let signature = RecoverableSignature::from_compact(&data[1..], recovery_id)
+        .map_err(|_| Error::InvalidSignature)?;
+    let message = Message::from_slice(&message_hash(message)).expect("32 bytes");
+    let public_key = secp256k1_verify_only()
+        .recover_ecdsa(&message, &signature)
+        .map_err(|_| Error::InvalidSignature)?;
+    let signed = if header < 4 {
+        // uncompressed key, P2PKH only
+        Address::new(
+            address.network,
+            Payload::PubkeyHash(hash160(&public_key.serialize_uncompressed())),
+        )?
+    } else {
+        let address_type = match &address.payload {
+            Payload::PubkeyHash(_) => AddressType::P2pkh,
+            Payload::ScriptHash(_) => AddressType::P2shP2wpkh,
+            Payload::WitnessProgram { version: 0, .. } => AddressType::P2wpkh,
+            _ => return Err(Error::InvalidSignature),
+        };
+        Address::from_public_key(&public_key, address_type, address.network)?
+    };
+    if signed != *address {
+        return Err(Error::InvalidSignature);
+    }
+    Ok(())
+}
+
+fn verify_bip322(address: &Address, message: &[u8], data: &[u8]) -> Result<(), Error> {
+    let mut reader = Reader::new(data);
+    let len = reader
+        .read_compact_size()
+        .map_err(|_| Error::InvalidSignature)?;
+    let witness = (0..len)
+        .map(|_| reader.read_var_bytes().map(<[u8]>::to_vec))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| Error::InvalidSignature)?;
+    if !reader.is_empty() {
+        return Err(Error::InvalidSignature);
+    }
+    let script_pubkey = address.script_pubkey();
+    let to_spend = to_spend(&script_pubkey, message);
+    let to_sign = to_sign(&to_spend, Vec::new());
+    let verified = match (&address.payload, witness.as_slice()) {
+        (
+            Payload::WitnessProgram {
+                version: 0,
+                program,
+            },
+            [signature, public_key],
+        ) if program.len() == 20 => {
+            let (sighash_type, signature) =
+                signature.split_last().ok_or(Error::InvalidSignature)?;
+            let public_key =
+                PublicKey::from_slice(public_key).map_err(|_| Error::InvalidSignature)?;
+            let signature = Signature::from_der(signature).map_err(|_| Error::InvalidSignature)?;
+            let script_code = Address::p2pkh(&public_key, address.network)?.script_pubkey();
+            let hash = sighash::segwit_v0(&to_sign, 0, &script_code, 0, SIGHASH_ALL);
+            let message = Message::from_slice(&hash).expect("32 bytes");
+            u32::from(*sighash_type) == SIGHASH_ALL
+                && hash160(&public_key.serialize()) == program[..]
+                && secp256k1_verify_only()
+                    .verify_ecdsa(&message, &signature, &public_key)
+                    .is_ok()
+        }
+        (
+            Payload::WitnessProgram {
+                version: 1,
+                program,
+            },
+            [signature],
+        ) if program.len() == 32 => {
+            let sighash_type = match signature.len() {
+                64 => SIGHASH_DEFAULT,
+                65 if u32::from(signature[64]) == SIGHASH_ALL => SIGHASH_ALL,
+                _ => return Err(Error::InvalidSignature),
+            };
+            let output_key =
+                XOnlyPublicKey::from_slice(program).map_err(|_| Error::InvalidSignature)?;
+            let signature = schnorr::Signature::from_slice(&signature[..64])
+                .map_err(|_| Error::InvalidSignature)?;
+            let hash = sighash::taproot_key_path(&to_sign, 0, &to_spend.outputs, sighash_type)?;
+            let message = Message::from_slice(&hash).expect("32 bytes");
+            secp256k1_verify_only()
+                .verify_schnorr(&signature, &message, &output_key)
+                .is_ok()
+        }
+        (Payload::WitnessProgram { .. }, _) => false,
+        _ => return Err(Error::InvalidAddress),
+    };
+    if !verified {
+        return Err(Error::InvalidSignature);
+    }
+    Ok(())
+}
+
+/// Virtual transaction spending from the BIP-322 message hash to `script_pubkey`.
+fn to_spend(script_pubkey: &[u8], message: &[u8]) -> Transaction {
+    let mut script_sig = vec![0x00, 0x20];
+    script_sig.extend_from_slice(&tagged_hash("BIP0322-signed-message", &[message]));
+    Transaction {
+        version: 0,
+        inputs: vec![TxIn {
+            previous_output: OutPoint {
+                txid: [0; 32],
+                vout: 0xffff_ffff,
+            },
+            script_sig,
+            sequence: 0,
+            witness: Vec::new(),
+        }],
+        outputs: vec![TxOut {
+            value: 0,
+            script_pubkey: script_pubkey.to_vec(),
+        }],
+        lock_time: 0,
+    }
+}
+
+/// Virtual transaction spending `to_spend` to `OP_RETURN`, its witness is the signature.
+fn to_sign(to_spend: &Transaction, witness: Vec<Vec<u8>>) -> Transaction {
+    Transaction {
+        version: 0,
+        inputs: vec![TxIn {
+            previous_output: OutPoint {
+                txid: to_spend.txid(),
+                vout: 0,
+            },
+            script_sig: Vec::new(),
+            sequence: 0,
+            witness,
+        }],
+        outputs: vec![TxOut {
+            value: 0,
+            script_pubkey: vec![0x6a],
+        }],
+        lock_time: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Wif;
+    use hdwallet::{DefaultKeyChain, ExtendedPrivKey};
+
+    // BIP-322 test vectors
+    const WIF: &str = "L3VFeEujGtevx9w18HD1fhRbCH67Az2dpCymeRE1SoPK6XQtaN2k";
+    const P2WPKH: &str = "bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l";
+    const P2TR: &str = "bc1ppv609nr0vr25u07u95waq5lucwfm6tde4nydujnu8npg4q75mr5sxq8lt3";
+    const BIP322_SIGNATURES: [(&str, &str); 2] = [
+        ("", "AkcwRAIgM2gBAQqvZX15ZiysmKmQpDrG83avLIT492QBzLnQIxYCIBaTpOaD20qRlEylyxFSeEA2ba9YOixpX8z46TSDtS40ASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI="),
+        ("Hello World", "AkcwRAIgZRfIY3p7/DoVTty6YZbWS71bc5Vct9p9Fia83eRmw2QCICK/ENGfwLtptFluMGs2KsqoNSk89pO7F29zJLUx9a/sASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI="),
+    ];
+    const TAPROOT_SIGNATURE: &str = "AUHd69PrJQEv+oKTfZ8l+WROBHuy9HKrbFCJu7U1iK2iiEy1vMU5EfMtjc+VSHM7aU0SDbak5IUZRVno2P5mjSafAQ==";
+
+    fn key_chain() -> DefaultKeyChain {
+        let wif = Wif::parse(WIF, Network::MainNet).expect("wif");
+        DefaultKeyChain::new(ExtendedPrivKey::new(wif.private_key, vec![0; 32]))
+    }
+
+    fn address(s: &str) -> Address {
+        Address::parse(s, Network::MainNet).expect("address")
+    }
+
+    #[test]
+    fn test_to_spend() {
+        let script_pubkey = address(P2WPKH).script_pubkey();
+        for (message, txid) in &[
+            (
+                "",
+                "c5680aa69bb8d860bf82d4e9cd3504b55dde018de765a91bb566283c545a99a7",
+            ),
+            (
+                "Hello World",
+                "b79d196740ad5217771c1098fc4a4b51e0535c32236c71f1ea4d61a2d603352b",
+            ),
+        ] {
+            let mut id = to_spend(&script_pubkey, message.as_bytes()).txid();
+            id.reverse();
+            assert_eq!(hex::encode(id), *txid);
+        }
+    }
+
+    #[test]
+    fn test_bip322() {
+        let key_chain = key_chain();
+        for (message, signature) in &BIP322_SIGNATURES {
+            let signed = sign_message_bip322(
+                &key_chain,
+                "m".into(),
+                AddressType::P2wpkh,
+                message.as_bytes(),
+            )
+            .unwrap();
+            assert_eq!(signed, *signature);
+            assert_eq!(
+                verify_message(&address(P2WPKH), message.as_bytes(), signature),
+                Ok(())
+            );
+        }
+        assert_eq!(
+            verify_message(&address(P2WPKH), b"", BIP322_SIGNATURES[1].1),
+            Err(Error::InvalidSignature)
+        );
+        assert_eq!(
+            verify_message(&address(P2TR), b"Hello World", BIP322_SIGNATURES[1].1),
+            Err(Error::InvalidSignature)
+        );
+
+        assert_eq!(
+            verify_message(&address(P2TR), b"Hello World", TAPROOT_SIGNATURE),
+            Ok(())
+        );
+        let signed =
+            sign_message_bip322(&key_chain, "m".into(), AddressType::P2tr, b"Hello World").unwrap();
+        assert_eq!(
+            verify_message(&address(P2TR), b"Hello World", &signed),
+            Ok(())
+        );
+        assert_eq!(
+            verify_message(&address(P2TR), b"Hello", &signed),
+            Err(Error::InvalidSignature)
+        );
+        assert_eq!(
+            sign_message_bip322(&key_chain, "m".into(), AddressType::P2pkh, b""),
+            Err(Error::InvalidAddress)
+        );
+    }
+
+    #[test]
+    fn test_legacy() {
+        let key_chain = key_chain();
+        let public_key = Wif::parse(WIF, Network::MainNet).unwrap().public_key();
+        for (address_type, signature) in &[
+            (AddressType::P2pkh, "IOW2xi+ebJLeBtr674l4QH76dqDoVjLV80R9EFKFQX5rBrlCXPIZaYs8Yuayg0ZqjyiCbLy9pzZIS7JWT65/nsU="),
+            (AddressType::P2shP2wpkh, "JOW2xi+ebJLeBtr674l4QH76dqDoVjLV80R9EFKFQX5rBrlCXPIZaYs8Yuayg0ZqjyiCbLy9pzZIS7JWT65/nsU="),
+            (AddressType::P2wpkh, "KOW2xi+ebJLeBtr674l4QH76dqDoVjLV80R9EFKFQX5rBrlCXPIZaYs8Yuayg0ZqjyiCbLy9pzZIS7JWT65/nsU="),
+        ] {
+            let address =
+                Address::from_public_key(&public_key, *address_type, Network::MainNet).unwrap();
+            let signed =
+                sign_message(&key_chain, "m".into(), *address_type, b"Hello World").unwrap();
+            assert_eq!(signed, *signature);
+            assert_eq!(verify_message(&address, b"Hello World", signature), Ok(()));
+            assert_eq!(
+                verify_message(&address, b"Hello", signature),
+                Err(Error::InvalidSignature)
+            );
+        }
+        // P2PKH headers are accepted for segwit addresses
+        assert_eq!(
+            verify_message(
+                &address(P2WPKH),
+                b"Hello World",
+                "IOW2xi+ebJLeBtr674l4QH76dqDoVjLV80R9EFKFQX5rBrlCXPIZaYs8Yuayg0ZqjyiCbLy9pzZIS7JWT65/nsU="
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            sign_message(&key_chain, "m".into(), AddressType::P2tr, b""),
+            Err(Error::InvalidAddress)
+        );
+
+        // uncompressed key
+        let wif = Wif::parse(
+            "5KYZdUEo39z3FPrtuX2QbbwGnNP5zTd7yyr2SC1j299sBCnWjss",
+            Network::MainNet,
+        )
+        .unwrap();
+        let address = Address {
+            network: Network::MainNet,
+            payload: Payload::PubkeyHash(hash160(&wif.public_key().serialize_uncompressed())),
+        };
+        let signature = "G9L5yLFjti0QTHhPyFrZCT1V/MMnBtXKmoiKDZ78NDBjERki6ZTQZdSMCtkgoNmp17By9ItJr8o7ChX0XxY91nk=";
+        let message = b"This is an example of a signed message.";
+        assert_eq!(verify_message(&address, message, signature), Ok(()));
+        assert_eq!(
+            verify_message(
+                &Address::p2pkh(&wif.public_key(), Network::MainNet).unwrap(),
+                message,
+                signature
+            ),
+            Err(Error::InvalidSignature)
+        );
+    }
+}
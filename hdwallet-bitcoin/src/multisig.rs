@@ -0,0 +1,301 @@
+//! BIP-48 multisig cosigner keys and BIP-67 sorted multisig scripts.
+
+use crate::{
+    account::derive_public_key,
+    descriptor::{multisig_script, MAX_REDEEM_SCRIPT_SIZE, MAX_WSH_MULTI_KEYS},
+    Address, Error, KeyOrigin, Network, OriginPubKey, PubKey, ScriptType,
+};
+use hdwallet::{secp256k1::PublicKey, KeyChain, KeyIndex};
+
+/// Cosigner key of a BIP-48 multisig wallet at `m/48'/coin_type'/account'/script_type'`.
+///
+/// `script_type` is [`ScriptType::P2shP2wsh`] (`1'`) or [`ScriptType::P2wsh`] (`2'`), the key is
+/// serialized as Ypub/Zpub accordingly. Other script types are [`Error::InvalidMultisig`].
+pub fn cosigner_key(
+    key_chain: &impl KeyChain,
+    coin_type: u32,
+    account: u32,
+    script_type: ScriptType,
+    network: Network,
+) -> Result<OriginPubKey, Error> {
+    let script_type_index = match script_type {
+        ScriptType::P2shP2wsh => 1,
+        ScriptType::P2wsh => 2,
+        _ => return Err(Error::InvalidMultisig),
+    };
+    let steps = [48, coin_type, account, script_type_index];
+    let path = format!("m/{}H/{}H/{}H/{}H", steps[0], steps[1], steps[2], steps[3]);
+    let key = derive_public_key(key_chain, &path, script_type, network)?;
+    let origin = KeyOrigin {
+        fingerprint: derive_public_key(key_chain, "m", ScriptType::Legacy, network)?.fingerprint(),
+        path: steps
+            .iter()
+            .map(|step| KeyIndex::hardened_from_normalize_index(*step))
+            .collect::<Result<_, _>>()?,
+    };
+    OriginPubKey::new(origin, key)
+}
+
+/// `threshold`-of-n multisig of extended keys with the public keys sorted per BIP-67, as
+/// `sortedmulti()` of descriptors.
+///
+/// The keys are usually the cosigner keys of a chain, e.g. the receive chain `0` of each
+/// [`cosigner_key`], and the scripts are built from their children at an index.
+///
+/// ```rust
+/// use hdwallet::{traits::Deserialize, KeyIndex};
+/// use hdwallet_bitcoin::{PubKey, ScriptType, SortedMulti};
+///
+/// let keys = [
+///     "Zpub74Jru6aftwwHxCUCWEvP6DgrfFsdA4U6ZRtQ5i8qJpMcC39yZGv3egBhQfV3MS9pZtH5z8iV5qWkJsK6ESs6mSzt4qvGhzJxPeeVS2e1zUG",
+///     "Zpub74egFEhgoVvKFF5KCwuD6QDHLCiLnQ7VE1BDNMa97xWquA1s6M6h5hDjPt24dYd6hGJWhx1DsNDeydR5t217GsNUQ3TRLuDoZanv6xbRqnP",
+///     "Zpub74W1oFFUddCgAd7wR4RHG9MYxyGwSshNejQ9sJiEAtvqbtbJ1Ha6LWqN7FqaMU56SFZudYzRBtJGDZbPKvsT2P4hzmhqfkCmSpYYmSQsohQ",
+/// ]
+/// .iter()
+/// .map(|key| PubKey::deserialize(key.to_string()))
+/// .collect::<Result<Vec<_>, _>>()
+/// .unwrap();
+/// let receive = SortedMulti::new(2, keys)
+///     .unwrap()
+///     .derive_public_key(KeyIndex::Normal(0))
+///     .unwrap();
+/// let address = receive.address(0, ScriptType::P2wsh).unwrap();
+/// assert_eq!(
+///     address.to_string(),
+///     "bc1q6enyvy9r794w6c8c6palsydmkkrksa68j4mprmlwjnnanl7zssasgxc569"
+/// );
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SortedMulti {
+    pub threshold: usize,
+    pub keys: Vec<PubKey>,
+}
+
+impl SortedMulti {
+    /// [`Error::InvalidMultisig`] unless `threshold` is between 1 and the number of keys, there
+    /// are at most 20 keys, and all keys are of the same network.
+    pub fn new(threshold: usize, keys: Vec<PubKey>) -> Result<Self, Error> {
+        let same_network = keys.iter().all(|key| key.network == keys[0].network);
+        if threshold == 0
+            || threshold > keys.len()
+            || keys.len() > MAX_WSH_MULTI_KEYS
+            || !same_network
+        {
+            return Err(Error::InvalidMultisig);
+        }
+        Ok(SortedMulti { threshold, keys })
+    }
+
+    /// Network of the keys and the addresses.
+    pub fn network(&self) -> Network {
+        self.keys[0].network
+    }
+
+    /// Derive the normal child `key_index` of every key.
+    pub fn derive_public_key(&self, key_index: KeyIndex) -> Result<SortedMulti, Error> {
+        Ok(SortedMulti {
+            threshold: self.threshold,
+            keys: self
+                .keys
+                .iter()
+                .map(|key| key.derive_public_key(key_index))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Redeem or witness script `OP_k <keys> OP_n OP_CHECKMULTISIG` of the children at `index`.
+    pub fn script(&self, index: u32) -> Result<Vec<u8>, Error> {
+        if index >= 1 << 31 {
+            return Err(Error::KeyIndexOutOfRange);
+        }
+        let keys = self
+            .keys
+            .iter()
+            .map(|key| {
+                Ok(key
+                    .derive_public_key(KeyIndex::Normal(index))?
+                    .extended_key
+                    .public_key)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(sorted_script(self.threshold, &keys))
+    }
+
+    /// Address at `index`, P2SH for [`ScriptType::Legacy`], P2SH-P2WSH or P2WSH.
+    ///
+    /// Other script types, and P2SH redeem scripts over 520 bytes, are
+    /// [`Error::InvalidMultisig`].
+    pub fn address(&self, index: u32, script_type: ScriptType) -> Result<Address, Error> {
+        let script = self.script(index)?;
+        let network = self.network();
+        match script_type {
+            ScriptType::Legacy if script.len() <= MAX_REDEEM_SCRIPT_SIZE => {
+                Address::p2sh(&script, network)
+            }
+            ScriptType::P2shP2wsh => Address::p2sh_p2wsh(&script, network),
+            ScriptType::P2wsh => Address::p2wsh(&script, network),
+            _ => Err(Error::InvalidMultisig),
+        }
+    }
+}
+
+/// Multisig script of the compressed `keys` in BIP-67 order.
+fn sorted_script(threshold: usize, keys: &[PublicKey]) -> Vec<u8> {
+    let mut keys: Vec<Vec<u8>> = keys.iter().map(|key| key.serialize().to_vec()).collect();
+    keys.sort();
+    multisig_script(threshold, &keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hdwallet::{traits::Serialize, DefaultKeyChain, ExtendedPrivKey};
+
+    /// Key chains of BIP-39 mnemonic `abandon abandon ... about`, BIP-32 test vector 1 and 2.
+    fn key_chains() -> Vec<DefaultKeyChain> {
+        [
+            "5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc19a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4",
+            "000102030405060708090a0b0c0d0e0f",
+            "fffcf9f6f3f0edeae7e4e1dedbd8d5d2cfccc9c6c3c0bdbab7b4b1aeaba8a5a29f9c999693908d8a8784817e7b7875726f6c696663605d5a5754514e4b484542",
+        ]
+        .iter()
+        .map(|seed| {
+            let seed = hex::decode(seed).expect("decode");
+            DefaultKeyChain::new(ExtendedPrivKey::with_seed(&seed).expect("master key"))
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_bip67() {
+        // BIP-67 test vectors, the third has no valid keys
+        for (keys, script, address) in &[
+            (
+                &["02ff12471208c14bd580709cb2358d98975247d8765f92bc25eab3b2763ed605f8", "02fe6f0a5a297eb38c391581c4413e084773ea23954d93f7753db7dc0adc188b2f"][..],
+                "522102fe6f0a5a297eb38c391581c4413e084773ea23954d93f7753db7dc0adc188b2f2102ff12471208c14bd580709cb2358d98975247d8765f92bc25eab3b2763ed605f852ae",
+                "39bgKC7RFbpoCRbtD5KEdkYKtNyhpsNa3Z",
+            ),
+            (
+                &["02632b12f4ac5b1d1b72b2a3b508c19172de44f6f46bcee50ba33f3f9291e47ed0", "027735a29bae7780a9755fae7a1c4374c656ac6a69ea9f3697fda61bb99a4f3e77", "02e2cc6bd5f45edd43bebe7cb9b675f0ce9ed3efe613b177588290ad188d11b404"][..],
+                "522102632b12f4ac5b1d1b72b2a3b508c19172de44f6f46bcee50ba33f3f9291e47ed021027735a29bae7780a9755fae7a1c4374c656ac6a69ea9f3697fda61bb99a4f3e772102e2cc6bd5f45edd43bebe7cb9b675f0ce9ed3efe613b177588290ad188d11b40453ae",
+                "3CKHTjBKxCARLzwABMu9yD85kvtm7WnMfH",
+            ),
+            (
+                &["022df8750480ad5b26950b25c7ba79d3e37d75f640f8e5d9bcd5b150a0f85014da", "03e3818b65bcc73a7d64064106a859cc1a5a728c4345ff0b641209fba0d90de6e9", "021f2f6e1e50cb6a953935c3601284925decd3fd21bc445712576873fb8c6ebc18"][..],
+                "5221021f2f6e1e50cb6a953935c3601284925decd3fd21bc445712576873fb8c6ebc1821022df8750480ad5b26950b25c7ba79d3e37d75f640f8e5d9bcd5b150a0f85014da2103e3818b65bcc73a7d64064106a859cc1a5a728c4345ff0b641209fba0d90de6e953ae",
+                "3Q4sF6tv9wsdqu2NtARzNCpQgwifm2rAba",
+            ),
+        ] {
+            let keys = keys
+                .iter()
+                .map(|key| PublicKey::from_slice(&hex::decode(key).unwrap()).unwrap())
+                .collect::<Vec<_>>();
+            let script = hex::decode(script).unwrap();
+            assert_eq!(sorted_script(2, &keys), script);
+            assert_eq!(
+                Address::p2sh(&script, Network::MainNet)
+                    .unwrap()
+                    .to_string(),
+                *address
+            );
+        }
+    }
+
+    #[test]
+    fn test_cosigner_key() {
+        let key_chain = &key_chains()[0];
+        for (coin_type, script_type, network, origin, key) in &[
+            (0, ScriptType::P2wsh, Network::MainNet, "73c5da0a/48h/0h/0h/2h", "Zpub74Jru6aftwwHxCUCWEvP6DgrfFsdA4U6ZRtQ5i8qJpMcC39yZGv3egBhQfV3MS9pZtH5z8iV5qWkJsK6ESs6mSzt4qvGhzJxPeeVS2e1zUG"),
+            (0, ScriptType::P2shP2wsh, Network::MainNet, "73c5da0a/48h/0h/0h/1h", "Ypub6jUbbRukkGPp4DgJDD4HL2NKkSZ1UPk111mg59XtJRQZHvJ6XqvJzrntik9U4jCFQkgrBqevdKLPMdYZXU9KAGhKpMhW5XujwqiQ7Csmm4Z"),
+            (1, ScriptType::P2wsh, Network::TestNet, "73c5da0a/48h/1h/0h/2h", "Vpub5n95dMZrDHj6SeBgJ1oz4Fae2N2eJNuWK3VTKDb2dzGpMFLUHLmtyDfen7AaQxwQ5mZnMyXdVrkEaoMLVTH8FmVBRVWPGFYWhmtDUGehGmq"),
+        ] {
+            let cosigner = cosigner_key(key_chain, *coin_type, 0, *script_type, *network).unwrap();
+            assert_eq!(cosigner.origin.to_string(), *origin);
+            assert_eq!(&Serialize::<String>::serialize(&cosigner.key), key);
+        }
+        assert_eq!(
+            cosigner_key(key_chain, 0, 0, ScriptType::P2wpkh, Network::MainNet),
+            Err(Error::InvalidMultisig)
+        );
+    }
+
+    #[test]
+    fn test_sorted_multi() {
+        let key_chains = key_chains();
+        // 2-of-3 wallets of the three key chains, addresses at 0/0, 0/1 and 1/0
+        for (script_type, bip48_type, addresses) in &[
+            (
+                ScriptType::P2wsh,
+                ScriptType::P2wsh,
+                [
+                    "bc1q6enyvy9r794w6c8c6palsydmkkrksa68j4mprmlwjnnanl7zssasgxc569",
+                    "bc1quze2qcq8u3vy7sjqx87y5hgk20spjgh6700k2p9cn80928nl38usptwc2r",
+                    "bc1q09tzxu8h37w0crwzefmupzqvfcrmpxpt77z79jq6345jm94rh3vspp9tpj",
+                ],
+            ),
+            (
+                ScriptType::P2shP2wsh,
+                ScriptType::P2shP2wsh,
+                [
+                    "3C8CKFLEp3oZ9AhTxzXydkQ7RHKE59mVhY",
+                    "3HgWGKCGtCWhaAUHpysnzk6Vbsw2njTbED",
+                    "349nZ3CpS4rmhddZ3gWcFiACoFNNRcun1W",
+                ],
+            ),
+            (
+                ScriptType::Legacy,
+                ScriptType::P2wsh,
+                [
+                    "3M5TB2n68r3MJVuuyjiahqUhpJYkK6CAR2",
+                    "3NqstMT9y5NzsELwPmqDFyGa1BXg98ib9B",
+                    "34EUKZTLxQhzmCckKj5pYHPhQQqHGsNTEm",
+                ],
+            ),
+        ] {
+            let keys = key_chains
+                .iter()
+                .map(|key_chain| {
+                    cosigner_key(key_chain, 0, 0, *bip48_type, Network::MainNet)
+                        .unwrap()
+                        .key
+                })
+                .collect();
+            let multisig = SortedMulti::new(2, keys).unwrap();
+            for ((chain, index), address) in [(0, 0), (0, 1), (1, 0)].iter().zip(addresses) {
+                let chain = multisig
+                    .derive_public_key(KeyIndex::Normal(*chain))
+                    .unwrap();
+                assert_eq!(
+                    chain.address(*index, *script_type).unwrap().to_string(),
+                    *address
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_multisig() {
+        let key_chain = &key_chains()[0];
+        let key = |network| {
+            cosigner_key(key_chain, 0, 0, ScriptType::P2wsh, network)
+                .unwrap()
+                .key
+        };
+        let main = key(Network::MainNet);
+        assert!(SortedMulti::new(0, vec![main.clone()]).is_err());
+        assert!(SortedMulti::new(2, vec![main.clone()]).is_err());
+        assert!(SortedMulti::new(1, vec![main.clone(); 21]).is_err());
+        assert!(SortedMulti::new(1, vec![main.clone(), key(Network::TestNet)]).is_err());
+        // 16 keys exceed the P2SH redeem script limit only
+        let multisig = SortedMulti::new(1, vec![main; 16]).unwrap();
+        assert_eq!(
+            multisig.address(0, ScriptType::Legacy),
+            Err(Error::InvalidMultisig)
+        );
+        assert!(multisig.address(0, ScriptType::P2wsh).is_ok());
+        assert_eq!(
+            multisig.address(0, ScriptType::P2wpkh),
+            Err(Error::InvalidMultisig)
+        );
+    }
+}
@@ -0,0 +1,964 @@
+//! BIP-327 MuSig2 multi-signatures and the BIP-328 aggregate extended public key.
+
+use crate::{
+    derive_path, hash::tagged_hash, secp256k1_sign_only, secp256k1_verify_only,
+    taproot::tap_tweak_hash, Error,
+};
+use hdwallet::{
+    backend::{Backend, DefaultBackend},
+    secp256k1::{
+        constants::CURVE_ORDER, schnorr::Signature, Parity, PublicKey, Scalar, SecretKey,
+        XOnlyPublicKey,
+    },
+    zeroize::Zeroize,
+    ChainPath, ExtendedPubKey, KeyIndex,
+};
+
+/// Chain code of the aggregate extended public key, BIP-328.
+const CHAIN_CODE: [u8; 32] = [
+    0x86, 0x80, 0x87, 0xca, 0x02, 0xa6, 0xf9, 0x74, 0xc4, 0x59, 0x89, 0x24, 0xc3, 0x6b, 0x57, 0x76,
+    0x2d, 0x32, 0xcb, 0x45, 0x71, 0x71, 0x67, 0xe3, 0x00, 0x62, 0x2c, 0x71, 0x67, 0xe3, 0x89, 0x65,
+];
+const ZERO: [u8; 32] = [0; 32];
+const ONE: [u8; 32] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+];
+const SEC_NONCE_LEN: usize = 97;
+const PUB_NONCE_LEN: usize = 66;
+
+/// Aggregate key of the signers, with the tweaks applied to it.
+///
+/// The untweaked context is also the BIP-328 aggregate extended public key, deriving a child
+/// applies the BIP-32 tweak so the signers can sign for the child key with their own keys:
+///
+/// ```rust
+/// use hdwallet::{DefaultKeyChain, ExtendedPrivKey, ExtendedPubKey, KeyChain};
+/// use hdwallet_bitcoin::{nonce_gen, AggNonce, KeyAggContext, Session};
+///
+/// let signers: Vec<_> = (0..2)
+///     .map(|_| ExtendedPrivKey::random(&mut rand::thread_rng()).unwrap())
+///     .collect();
+/// let keys: Vec<_> = signers.iter().map(ExtendedPubKey::from_private_key).collect();
+/// let context = KeyAggContext::from_extended_keys(&keys)
+///     .and_then(|context| context.derive_path("m/0/1".into()))
+///     .and_then(|context| context.tap_tweak(None))
+///     .unwrap();
+///
+/// let message = [1u8; 32];
+/// let nonces: Vec<_> = signers
+///     .iter()
+///     .map(|signer| {
+///         let rand = rand::random::<[u8; 32]>();
+///         let public_key = signer.public_key();
+///         nonce_gen(&rand, Some(&signer.private_key()), &public_key, None, None, None).unwrap()
+///     })
+///     .collect();
+/// let pub_nonces: Vec<_> = nonces.iter().map(|(_, pub_nonce)| *pub_nonce).collect();
+/// let session = Session::new(&context, &AggNonce::new(&pub_nonces).unwrap(), &message).unwrap();
+/// let partial_signatures: Vec<_> = nonces
+///     .into_iter()
+///     .zip(&signers)
+///     .map(|((sec_nonce, _), signer)| session.sign(sec_nonce, &signer.private_key()).unwrap())
+///     .collect();
+/// assert!(session.aggregate(&partial_signatures).is_ok());
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeyAggContext {
+    keys: Vec<PublicKey>,
+    second_key: Option<PublicKey>,
+    list_hash: [u8; 32],
+    public_key: PublicKey,
+    /// Whether the tweaks negated the aggregate key an odd number of times.
+    negated: bool,
+    /// Sum of the tweaks.
+    tweak: [u8; 32],
+    chain_code: [u8; 32],
+}
+
+impl KeyAggContext {
+    /// Aggregate `keys`, the order of the keys matters.
+    pub fn new(keys: &[PublicKey]) -> Result<Self, Error> {
+        let first_key = keys.first().ok_or(Error::InvalidMusig)?;
+        let serialized: Vec<u8> = keys.iter().flat_map(|key| key.serialize()).collect();
+        let list_hash = tagged_hash("KeyAgg list", &[&serialized]);
+        let second_key = keys.iter().find(|key| *key != first_key).copied();
+        let points = keys
+            .iter()
+            .map(|key| point_mul(key, &coefficient(&list_hash, second_key.as_ref(), key)))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(Error::InvalidMusig)?;
+        let points: Vec<&PublicKey> = points.iter().collect();
+        let public_key = PublicKey::combine_keys(&points).map_err(|_| Error::InvalidMusig)?;
+        Ok(KeyAggContext {
+            keys: keys.to_vec(),
+            second_key,
+            list_hash,
+            public_key,
+            negated: false,
+            tweak: ZERO,
+            chain_code: CHAIN_CODE,
+        })
+    }
+
+    /// Aggregate the public keys of extended keys, their chain codes are ignored.
+    pub fn from_extended_keys(keys: &[ExtendedPubKey]) -> Result<Self, Error> {
+        let keys: Vec<PublicKey> = keys.iter().map(|key| key.public_key).collect();
+        KeyAggContext::new(&keys)
+    }
+
+    /// Keys of the signers, in aggregation order.
+    pub fn keys(&self) -> &[PublicKey] {
+        &self.keys
+    }
+
+    /// Tweaked aggregate key.
+    pub fn public_key(&self) -> PublicKey {
+        self.public_key
+    }
+
+    /// Tweaked aggregate key the signatures verify against.
+    pub fn x_only_public_key(&self) -> XOnlyPublicKey {
+        self.public_key.x_only_public_key().0
+    }
+
+    /// Aggregate extended public key, with the chain code of the last derivation.
+    pub fn extended_key(&self) -> ExtendedPubKey {
+        ExtendedPubKey {
+            public_key: self.public_key,
+            chain_code: self.chain_code.to_vec(),
+        }
+    }
+
+    /// Add `tweak` times the generator to the aggregate key, to its even-y form if `x_only`.
+    pub fn apply_tweak(&self, tweak: &[u8; 32], x_only: bool) -> Result<Self, Error> {
+        if Scalar::from_be_bytes(*tweak).is_err() {
+            return Err(Error::InvalidMusig);
+        }
+        let negate = x_only && !has_even_y(&self.public_key);
+        let public_key = if negate {
+            self.public_key.negate(secp256k1_verify_only())
+        } else {
+            self.public_key
+        };
+        let public_key =
+            point_add(Some(public_key), generator_mul(tweak)).ok_or(Error::InvalidMusig)?;
+        let tweak_sum = if negate {
+            scalar_neg(&self.tweak)
+        } else {
+            self.tweak
+        };
+        Ok(KeyAggContext {
+            public_key,
+            negated: self.negated ^ negate,
+            tweak: scalar_add(tweak, &tweak_sum),
+            ..self.clone()
+        })
+    }
+
+    /// Apply the BIP-341 taproot tweak of `merkle_root`, `None` for an output without script tree.
+    pub fn tap_tweak(&self, merkle_root: Option<&[u8; 32]>) -> Result<Self, Error> {
+        let tweak = tap_tweak_hash(&self.x_only_public_key(), merkle_root)?;
+        self.apply_tweak(&tweak.to_be_bytes(), true)
+    }
+
+    /// Derive the normal child `key_index` of the aggregate extended public key.
+    pub fn derive_public_key(&self, key_index: KeyIndex) -> Result<Self, Error> {
+        let index = match key_index {
+            KeyIndex::Normal(index) if key_index.is_valid() => index,
+            _ => return Err(Error::KeyIndexOutOfRange),
+        };
+        let signature = DefaultBackend::hmac_sha512(
+            &DefaultBackend::hmac_sha512_key(&self.chain_code),
+            &[&self.public_key.serialize(), &index.to_be_bytes()],
+        );
+        let (tweak, chain_code) = signature.split_at(signature.len() / 2);
+        let mut context = self.apply_tweak(&SecretKey::from_slice(tweak)?.secret_bytes(), false)?;
+        context.chain_code.copy_from_slice(chain_code);
+        Ok(context)
+    }
+
+    /// Derive the aggregate extended public key along `chain_path`, which must start with `m`.
+    pub fn derive_path(&self, chain_path: ChainPath) -> Result<Self, Error> {
+        derive_path(self, chain_path, KeyAggContext::derive_public_key)
+    }
+
+    fn coefficient(&self, key: &PublicKey) -> [u8; 32] {
+        coefficient(&self.list_hash, self.second_key.as_ref(), key)
+    }
+
+    /// Whether signers negate their keys, for an odd aggregate key or negating tweaks.
+    fn negate_keys(&self) -> bool {
+        !has_even_y(&self.public_key) ^ self.negated
+    }
+}
+
+/// Secret nonce of a signer.
+///
+/// Signing with the same nonce twice leaks the private key, so the nonce is neither `Clone` nor
+/// `Copy`, [`Session::sign`] consumes it and it is zeroized on drop.
+pub struct SecNonce {
+    k1: [u8; 32],
+    k2: [u8; 32],
+    public_key: PublicKey,
+}
+
+impl SecNonce {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != SEC_NONCE_LEN {
+            return Err(Error::InvalidLength);
+        }
+        let mut nonce = SecNonce {
+            k1: ZERO,
+            k2: ZERO,
+            public_key: PublicKey::from_slice(&bytes[64..]).map_err(|_| Error::InvalidMusig)?,
+        };
+        nonce.k1.copy_from_slice(&bytes[..32]);
+        nonce.k2.copy_from_slice(&bytes[32..64]);
+        if generator_mul(&nonce.k1).is_none() || generator_mul(&nonce.k2).is_none() {
+            return Err(Error::InvalidMusig);
+        }
+        Ok(nonce)
+    }
+
+    pub fn to_bytes(&self) -> [u8; SEC_NONCE_LEN] {
+        let mut bytes = [0u8; SEC_NONCE_LEN];
+        bytes[..32].copy_from_slice(&self.k1);
+        bytes[32..64].copy_from_slice(&self.k2);
+        bytes[64..].copy_from_slice(&self.public_key.serialize());
+        bytes
+    }
+
+    /// Public key of the signer the nonce was generated for.
+    pub fn public_key(&self) -> PublicKey {
+        self.public_key
+    }
+
+    pub fn public_nonce(&self) -> Result<PubNonce, Error> {
+        Ok(PubNonce {
+            r1: generator_mul(&self.k1).ok_or(Error::InvalidMusig)?,
+            r2: generator_mul(&self.k2).ok_or(Error::InvalidMusig)?,
+        })
+    }
+}
+
+impl Drop for SecNonce {
+    fn drop(&mut self) {
+        self.k1.zeroize();
+        self.k2.zeroize();
+    }
+}
+
+/// Public nonce of a signer, sent to the other signers.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct PubNonce {
+    r1: PublicKey,
+    r2: PublicKey,
+}
+
+impl PubNonce {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != PUB_NONCE_LEN {
+            return Err(Error::InvalidLength);
+        }
+        Ok(PubNonce {
+            r1: PublicKey::from_slice(&bytes[..33]).map_err(|_| Error::InvalidMusig)?,
+            r2: PublicKey::from_slice(&bytes[33..]).map_err(|_| Error::InvalidMusig)?,
+        })
+    }
+
+    pub fn serialize(&self) -> [u8; PUB_NONCE_LEN] {
+        let mut bytes = [0u8; PUB_NONCE_LEN];
+        bytes[..33].copy_from_slice(&self.r1.serialize());
+        bytes[33..].copy_from_slice(&self.r2.serialize());
+        bytes
+    }
+}
+
+/// Sum of the public nonces of all signers, either half may be the point at infinity.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct AggNonce {
+    r1: Option<PublicKey>,
+    r2: Option<PublicKey>,
+}
+
+impl AggNonce {
+    /// Aggregate the public nonces of the signers, in any order.
+    pub fn new(nonces: &[PubNonce]) -> Result<Self, Error> {
+        if nonces.is_empty() {
+            return Err(Error::InvalidMusig);
+        }
+        Ok(AggNonce {
+            r1: nonces
+                .iter()
+                .fold(None, |r1, nonce| point_add(r1, Some(nonce.r1))),
+            r2: nonces
+                .iter()
+                .fold(None, |r2, nonce| point_add(r2, Some(nonce.r2))),
+        })
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != PUB_NONCE_LEN {
+            return Err(Error::InvalidLength);
+        }
+        let point = |bytes: &[u8]| match bytes.iter().all(|byte| *byte == 0) {
+            true => Ok(None),
+            false => PublicKey::from_slice(bytes)
+                .map(Some)
+                .map_err(|_| Error::InvalidMusig),
+        };
+        Ok(AggNonce {
+            r1: point(&bytes[..33])?,
+            r2: point(&bytes[33..])?,
+        })
+    }
+
+    pub fn serialize(&self) -> [u8; PUB_NONCE_LEN] {
+        let mut bytes = [0u8; PUB_NONCE_LEN];
+        if let Some(r1) = self.r1 {
+            bytes[..33].copy_from_slice(&r1.serialize());
+        }
+        if let Some(r2) = self.r2 {
+            bytes[33..].copy_from_slice(&r2.serialize());
+        }
+        bytes
+    }
+}
+
+/// Partial signature of a signer, summed into the signature by [`Session::aggregate`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct PartialSignature([u8; 32]);
+
+impl PartialSignature {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != 32 {
+            return Err(Error::InvalidLength);
+        }
+        let mut signature = [0u8; 32];
+        signature.copy_from_slice(bytes);
+        if signature >= CURVE_ORDER {
+            return Err(Error::InvalidMusig);
+        }
+        Ok(PartialSignature(signature))
+    }
+
+    pub fn serialize(&self) -> [u8; 32] {
+        self.0
+    }
+}
+
+/// Generate the nonce of a signer with public key `public_key`.
+///
+/// `rand` must be fresh randomness for every call. The optional secret key, aggregate key,
+/// message and extra input are mixed in to protect against a broken random source.
+pub fn nonce_gen(
+    rand: &[u8; 32],
+    secret_key: Option<&SecretKey>,
+    public_key: &PublicKey,
+    aggregate_key: Option<&XOnlyPublicKey>,
+    msg: Option<&[u8]>,
+    extra_in: Option<&[u8]>,
+) -> Result<(SecNonce, PubNonce), Error> {
+    let mut rand = *rand;
+    if let Some(secret_key) = secret_key {
+        let aux = tagged_hash("MuSig/aux", &[&rand]);
+        for ((byte, key), aux) in rand.iter_mut().zip(secret_key.secret_bytes()).zip(aux) {
+            *byte = key ^ aux;
+        }
+    }
+    let public_key = public_key.serialize();
+    let aggregate_key = aggregate_key
+        .map(|key| key.serialize().to_vec())
+        .unwrap_or_default();
+    let mut msg_prefixed = Vec::new();
+    match msg {
+        Some(msg) => {
+            msg_prefixed.push(1);
+            msg_prefixed.extend_from_slice(&(msg.len() as u64).to_be_bytes());
+            msg_prefixed.extend_from_slice(msg);
+        }
+        None => msg_prefixed.push(0),
+    }
+    let extra_in = extra_in.unwrap_or_default();
+    let k = |i: u8| {
+        reduce(tagged_hash(
+            "MuSig/nonce",
+            &[
+                &rand,
+                &[public_key.len() as u8],
+                &public_key,
+                &[aggregate_key.len() as u8],
+                &aggregate_key,
+                &msg_prefixed,
+                &(extra_in.len() as u32).to_be_bytes(),
+                extra_in,
+                &[i],
+            ],
+        ))
+    };
+    let sec_nonce = SecNonce {
+        k1: k(0),
+        k2: k(1),
+        public_key: PublicKey::from_slice(&public_key)?,
+    };
+    rand.zeroize();
+    let pub_nonce = sec_nonce.public_nonce()?;
+    Ok((sec_nonce, pub_nonce))
+}
+
+/// Signing session of a message, shared by all signers.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Session {
+    key_agg: KeyAggContext,
+    /// Coefficient of the second nonces.
+    b: [u8; 32],
+    /// Final nonce of the signature.
+    r: PublicKey,
+    /// BIP-340 challenge.
+    e: [u8; 32],
+}
+
+impl Session {
+    pub fn new(key_agg: &KeyAggContext, agg_nonce: &AggNonce, msg: &[u8]) -> Result<Self, Error> {
+        let aggregate_key = key_agg.x_only_public_key().serialize();
+        let b = reduce(tagged_hash(
+            "MuSig/noncecoef",
+            &[&agg_nonce.serialize(), &aggregate_key, msg],
+        ));
+        let r2 = agg_nonce.r2.and_then(|r2| point_mul(&r2, &b));
+        // an infinite nonce can only result from a dishonest signer, who can't forge with the
+        // generator instead
+        let r = match point_add(agg_nonce.r1, r2) {
+            Some(r) => r,
+            None => generator_mul(&ONE).expect("generator"),
+        };
+        let e = reduce(tagged_hash(
+            "BIP0340/challenge",
+            &[&r.x_only_public_key().0.serialize(), &aggregate_key, msg],
+        ));
+        Ok(Session {
+            key_agg: key_agg.clone(),
+            b,
+            r,
+            e,
+        })
+    }
+
+    /// Partial signature of the signer of `secret_key`, with its nonce `sec_nonce`.
+    pub fn sign(
+        &self,
+        sec_nonce: SecNonce,
+        secret_key: &SecretKey,
+    ) -> Result<PartialSignature, Error> {
+        let public_key = secret_key.public_key(secp256k1_sign_only());
+        if public_key != sec_nonce.public_key || !self.key_agg.keys.contains(&public_key) {
+            return Err(Error::InvalidMusig);
+        }
+        let pub_nonce = sec_nonce.public_nonce()?;
+        let (k1, k2) = match has_even_y(&self.r) {
+            true => (sec_nonce.k1, sec_nonce.k2),
+            false => (scalar_neg(&sec_nonce.k1), scalar_neg(&sec_nonce.k2)),
+        };
+        let mut d = secret_key.secret_bytes();
+        if self.key_agg.negate_keys() {
+            d = scalar_neg(&d);
+        }
+        let ead = scalar_mul(
+            &scalar_mul(&self.e, &self.key_agg.coefficient(&public_key)),
+            &d,
+        );
+        let signature = PartialSignature(scalar_add(
+            &scalar_add(&k1, &scalar_mul(&self.b, &k2)),
+            &ead,
+        ));
+        d.zeroize();
+        self.verify_partial_signature(&signature, &pub_nonce, &public_key)?;
+        Ok(signature)
+    }
+
+    /// Verify the partial signature of the signer of `public_key` with public nonce `pub_nonce`.
+    pub fn verify_partial_signature(
+        &self,
+        signature: &PartialSignature,
+        pub_nonce: &PubNonce,
+        public_key: &PublicKey,
+    ) -> Result<(), Error> {
+        if !self.key_agg.keys.contains(public_key) {
+            return Err(Error::InvalidMusig);
+        }
+        let secp = secp256k1_verify_only();
+        let mut r = point_add(Some(pub_nonce.r1), point_mul(&pub_nonce.r2, &self.b));
+        if !has_even_y(&self.r) {
+            r = r.map(|r| r.negate(secp));
+        }
+        let ea = scalar_mul(&self.e, &self.key_agg.coefficient(public_key));
+        let public_key = match self.key_agg.negate_keys() {
+            true => public_key.negate(secp),
+            false => *public_key,
+        };
+        if generator_mul(&signature.0) != point_add(r, point_mul(&public_key, &ea)) {
+            return Err(Error::InvalidMusig);
+        }
+        Ok(())
+    }
+
+    /// BIP-340 signature of the aggregate key, the sum of the partial signatures of all signers.
+    pub fn aggregate(&self, signatures: &[PartialSignature]) -> Result<Signature, Error> {
+        let mut tweak = scalar_mul(&self.e, &self.key_agg.tweak);
+        if !has_even_y(&self.key_agg.public_key) {
+            tweak = scalar_neg(&tweak);
+        }
+        let s = signatures
+            .iter()
+            .fold(tweak, |s, signature| scalar_add(&s, &signature.0));
+        let mut signature = self.r.x_only_public_key().0.serialize().to_vec();
+        signature.extend_from_slice(&s);
+        Ok(Signature::from_slice(&signature)?)
+    }
+}
+
+/// `KeyAgg coefficient` of `key`, one for the second distinct key.
+fn coefficient(list_hash: &[u8; 32], second_key: Option<&PublicKey>, key: &PublicKey) -> [u8; 32] {
+    if second_key == Some(key) {
+        return ONE;
+    }
+    reduce(tagged_hash(
+        "KeyAgg coefficient",
+        &[list_hash, &key.serialize()],
+    ))
+}
+
+/// Hash as a scalar modulo the curve order.
+fn reduce(mut hash: [u8; 32]) -> [u8; 32] {
+    if hash >= CURVE_ORDER {
+        let mut borrow = 0;
+        for (byte, order) in hash.iter_mut().zip(CURVE_ORDER).rev() {
+            let difference = i16::from(*byte) - i16::from(order) - borrow;
+            borrow = i16::from(difference < 0);
+            *byte = difference as u8;
+        }
+    }
+    hash
+}
+
+// Scalars are reduced big-endian integers, zero included unlike `SecretKey`.
+
+fn scalar_add(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    match SecretKey::from_slice(a) {
+        Ok(a) => a
+            .add_tweak(&Scalar::from_be_bytes(*b).expect("reduced"))
+            .map_or(ZERO, |sum| sum.secret_bytes()),
+        Err(_) => *b,
+    }
+}
+
+fn scalar_mul(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    match SecretKey::from_slice(a) {
+        // fails for a zero `b` only
+        Ok(a) => a
+            .mul_tweak(&Scalar::from_be_bytes(*b).expect("reduced"))
+            .map_or(ZERO, |product| product.secret_bytes()),
+        Err(_) => ZERO,
+    }
+}
+
+fn scalar_neg(a: &[u8; 32]) -> [u8; 32] {
+    SecretKey::from_slice(a).map_or(ZERO, |a| a.negate().secret_bytes())
+}
+
+// Points are `None` at infinity.
+
+fn generator_mul(scalar: &[u8; 32]) -> Option<PublicKey> {
+    let scalar = SecretKey::from_slice(scalar).ok()?;
+    Some(scalar.public_key(secp256k1_sign_only()))
+}
+
+fn point_mul(point: &PublicKey, scalar: &[u8; 32]) -> Option<PublicKey> {
+    let scalar = Scalar::from_be_bytes(*scalar).expect("reduced");
+    point.mul_tweak(secp256k1_verify_only(), &scalar).ok()
+}
+
+fn point_add(a: Option<PublicKey>, b: Option<PublicKey>) -> Option<PublicKey> {
+    match (a, b) {
+        (Some(a), Some(b)) => a.combine(&b).ok(),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+fn has_even_y(point: &PublicKey) -> bool {
+    point.x_only_public_key().1 == Parity::Even
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Network, PubKey, ScriptType, TaprootPubKeyExt};
+    use hdwallet::{traits::Serialize, DefaultKeyChain, Derivation, ExtendedPrivKey, KeyChain};
+
+    const SECRET_KEY: &str = "7FB9E0E687ADA1EEBF7ECFE2F21E73EBDB51A7D450948DFE8D76D7F2D1007671";
+    const SEC_NONCE: &str = "508B81A611F100A6B2B6B29656590898AF488BCF2E1F55CF22E5CFB84421FE61FA27FD49B1D50085B481285E1CA205D55C82CC1B31FF5CD54A489829355901F703935F972DA013F80AE011890FA89B67A27B7BE6CCB24D3274D18B2D4067F261A9";
+    const AGG_NONCE: &str = "028465FCF0BBDBCF443AABCCE533D42B4B5A10966AC09A49655E8C42DAAB8FCD61037496A3CC86926D452CAFCFD55D25972CA1675D549310DE296BFF42F72EEEA8C9";
+    const MSG: &str = "F95466D086770E689964664219266FE5ED215C92AE20BAB5C9D79ADDDDF3C0CF";
+
+    fn public_key(s: &str) -> PublicKey {
+        PublicKey::from_slice(&hex::decode(s).unwrap()).unwrap()
+    }
+
+    fn decode32(s: &str) -> [u8; 32] {
+        hex::decode(s).unwrap().try_into().unwrap()
+    }
+
+    fn secret_key() -> SecretKey {
+        SecretKey::from_slice(&hex::decode(SECRET_KEY).unwrap()).unwrap()
+    }
+
+    fn sec_nonce() -> SecNonce {
+        SecNonce::from_bytes(&hex::decode(SEC_NONCE).unwrap()).unwrap()
+    }
+
+    fn pub_nonce(s: &str) -> PubNonce {
+        PubNonce::from_bytes(&hex::decode(s).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_key_agg() {
+        let keys = [
+            "02F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9",
+            "03DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            "023590A94E768F8E1815C2F24B4D80A8E3149316C3518CE7B7AD338368D038CA66",
+        ]
+        .map(public_key);
+        let vectors: [(&[usize], &str); 4] = [
+            (
+                &[0, 1, 2],
+                "90539EEDE565F5D054F32CC0C220126889ED1E5D193BAF15AEF344FE59D4610C",
+            ),
+            (
+                &[2, 1, 0],
+                "6204DE8B083426DC6EAF9502D27024D53FC826BF7D2012148A0575435DF54B2B",
+            ),
+            (
+                &[0, 0, 0],
+                "B436E3BAD62B8CD409969A224731C193D051162D8C5AE8B109306127DA3AA935",
+            ),
+            (
+                &[0, 0, 1, 1],
+                "69BC22BFA5D106306E48A20679DE1D7389386124D07571D0D872686028C26A3E",
+            ),
+        ];
+        for (indices, expected) in vectors {
+            let keys: Vec<_> = indices.iter().map(|i| keys[*i]).collect();
+            let context = KeyAggContext::new(&keys).unwrap();
+            assert_eq!(
+                hex::encode_upper(context.x_only_public_key().serialize()),
+                expected
+            );
+        }
+        assert_eq!(KeyAggContext::new(&[]), Err(Error::InvalidMusig));
+    }
+
+    #[test]
+    fn test_nonce_gen() {
+        let public_key =
+            public_key("024D4B6CD1361032CA9BD2AEB9D900AA4D45D9EAD80AC9423374C451A7254D0766");
+        let secret_key = SecretKey::from_slice(&[2; 32]).unwrap();
+        let aggregate_key = XOnlyPublicKey::from_slice(&[7; 32]).unwrap();
+        let (sec_nonce, pub_nonce) = nonce_gen(
+            &[0x0f; 32],
+            Some(&secret_key),
+            &public_key,
+            Some(&aggregate_key),
+            Some(&[1; 32]),
+            Some(&[8; 32]),
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode_upper(&sec_nonce.to_bytes()[..64]),
+            "B114E502BEAA4E301DD08A50264172C84E41650E6CB726B410C0694D59EFFB6495B5CAF28D045B973D63E3C99A44B807BDE375FD6CB39E46DC4A511708D0E9D2"
+        );
+        assert_eq!(sec_nonce.public_key(), public_key);
+        assert_eq!(
+            hex::encode_upper(pub_nonce.serialize()),
+            "02F7BE7089E8376EB355272368766B17E88E7DB72047D05E56AA881EA52B3B35DF02C29C8046FDD0DED4C7E55869137200FBDBFE2EB654267B6D7013602CAED3115A"
+        );
+
+        let (sec_nonce, _) = nonce_gen(
+            &[0x0f; 32],
+            Some(&secret_key),
+            &public_key,
+            Some(&aggregate_key),
+            Some(&[]),
+            Some(&[8; 32]),
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode_upper(&sec_nonce.to_bytes()[..64]),
+            "E862B068500320088138468D47E0E6F147E01B6024244AE45EAC40ACE5929B9F0789E051170B9E705D0B9EB49049A323BBBBB206D8E05C19F46C6228742AA7A9"
+        );
+    }
+
+    #[test]
+    fn test_nonce_agg() {
+        let nonces = [
+            "0337C87821AFD50A8644D820A8F3E02E499C931865C2360FB43D0A0D20DAFE07EA0287BF891D2A6DEAEBADC909352AA9405D1428C15F4B75F04DAE642A95C2548480",
+            "0279BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F817980279BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798",
+            "032DE2662628C90B03F5E720284EB52FF7D71F4284F627B68A853D78C78E1FFE9303E4C5524E83FFE1493B9077CF1CA6BEB2090C93D930321071AD40B2F44E599046",
+            "0237C87821AFD50A8644D820A8F3E02E499C931865C2360FB43D0A0D20DAFE07EA0387BF891D2A6DEAEBADC909352AA9405D1428C15F4B75F04DAE642A95C2548480",
+        ]
+        .map(pub_nonce);
+        let agg_nonce = AggNonce::new(&nonces[..3]).unwrap();
+        assert_eq!(hex::encode_upper(agg_nonce.serialize()), AGG_NONCE);
+        assert_eq!(AggNonce::from_bytes(&agg_nonce.serialize()), Ok(agg_nonce));
+        let infinite = AggNonce::new(&[nonces[0], nonces[3]]).unwrap();
+        assert_eq!(infinite.serialize(), [0; PUB_NONCE_LEN]);
+        assert_eq!(AggNonce::from_bytes(&[0; PUB_NONCE_LEN]), Ok(infinite));
+        assert_eq!(
+            PubNonce::from_bytes(&[0; PUB_NONCE_LEN]),
+            Err(Error::InvalidMusig)
+        );
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let keys = [
+            "03935F972DA013F80AE011890FA89B67A27B7BE6CCB24D3274D18B2D4067F261A9",
+            "02F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9",
+            "02DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA661",
+        ]
+        .map(public_key);
+        let nonces = [
+            "0337C87821AFD50A8644D820A8F3E02E499C931865C2360FB43D0A0D20DAFE07EA0287BF891D2A6DEAEBADC909352AA9405D1428C15F4B75F04DAE642A95C2548480",
+            "0279BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F817980279BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798",
+            "032DE2662628C90B03F5E720284EB52FF7D71F4284F627B68A853D78C78E1FFE9303E4C5524E83FFE1493B9077CF1CA6BEB2090C93D930321071AD40B2F44E599046",
+            "0237C87821AFD50A8644D820A8F3E02E499C931865C2360FB43D0A0D20DAFE07EA0387BF891D2A6DEAEBADC909352AA9405D1428C15F4B75F04DAE642A95C2548480",
+        ]
+        .map(pub_nonce);
+        let msgs = [hex::decode(MSG).unwrap(), vec![], vec![0x26; 38]];
+        let vectors: [(&[usize], &[usize], usize, &str); 6] = [
+            (
+                &[0, 1, 2],
+                &[0, 1, 2],
+                0,
+                "012ABBCB52B3016AC03AD82395A1A415C48B93DEF78718E62A7A90052FE224FB",
+            ),
+            (
+                &[1, 0, 2],
+                &[1, 0, 2],
+                0,
+                "9FF2F7AAA856150CC8819254218D3ADEEB0535269051897724F9DB3789513A52",
+            ),
+            (
+                &[1, 2, 0],
+                &[1, 2, 0],
+                0,
+                "FA23C359F6FAC4E7796BB93BC9F0532A95468C539BA20FF86D7C76ED92227900",
+            ),
+            (
+                &[0, 1],
+                &[0, 3],
+                0,
+                "AE386064B26105404798F75DE2EB9AF5EDA5387B064B83D049CB7C5E08879531",
+            ),
+            (
+                &[0, 1, 2],
+                &[0, 1, 2],
+                1,
+                "D7D63FFD644CCDA4E62BC2BC0B1D02DD32A1DC3030E155195810231D1037D82D",
+            ),
+            (
+                &[0, 1, 2],
+                &[0, 1, 2],
+                2,
+                "E184351828DA5094A97C79CABDAAA0BFB87608C32E8829A4DF5340A6F243B78C",
+            ),
+        ];
+        for (key_indices, nonce_indices, msg, expected) in vectors {
+            let keys: Vec<_> = key_indices.iter().map(|i| keys[*i]).collect();
+            let nonces: Vec<_> = nonce_indices.iter().map(|i| nonces[*i]).collect();
+            let context = KeyAggContext::new(&keys).unwrap();
+            let session =
+                Session::new(&context, &AggNonce::new(&nonces).unwrap(), &msgs[msg]).unwrap();
+            let signature = session.sign(sec_nonce(), &secret_key()).unwrap();
+            assert_eq!(hex::encode_upper(signature.serialize()), expected);
+            assert_eq!(
+                PartialSignature::from_bytes(&signature.serialize()),
+                Ok(signature)
+            );
+            let signer = key_indices.iter().position(|i| *i == 0).unwrap();
+            assert!(session
+                .verify_partial_signature(&signature, &nonces[signer], &keys[signer])
+                .is_ok());
+            let other = (signer + 1) % keys.len();
+            assert_eq!(
+                session.verify_partial_signature(&signature, &nonces[other], &keys[other]),
+                Err(Error::InvalidMusig)
+            );
+        }
+
+        // the signer must be one of the aggregated keys
+        let context = KeyAggContext::new(&keys[1..]).unwrap();
+        let agg_nonce = AggNonce::from_bytes(&hex::decode(AGG_NONCE).unwrap()).unwrap();
+        let session = Session::new(&context, &agg_nonce, &msgs[0]).unwrap();
+        assert_eq!(
+            session.sign(sec_nonce(), &secret_key()),
+            Err(Error::InvalidMusig)
+        );
+        assert_eq!(
+            PartialSignature::from_bytes(&CURVE_ORDER),
+            Err(Error::InvalidMusig)
+        );
+    }
+
+    #[test]
+    fn test_tweaks() {
+        let keys = [
+            "02F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9",
+            "02DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            "03935F972DA013F80AE011890FA89B67A27B7BE6CCB24D3274D18B2D4067F261A9",
+        ]
+        .map(public_key);
+        let tweaks = [
+            "E8F791FF9225A2AF0102AFFF4A9A723D9612A682A25EBE79802B263CDFCD83BB",
+            "AE2EA797CC0FE72AC5B97B97F3C6957D7E4199A167A58EB08BCAFFDA70AC0455",
+            "F52ECBC565B3D8BEA2DFD5B75A4F457E54369809322E4120831626F290FA87E0",
+            "1969AD73CC177FA0B4FCED6DF1F7BF9907E665FDE9BA196A74FED0A3CF5AEF9D",
+        ]
+        .map(decode32);
+        let vectors: [(&[bool], &str); 5] = [
+            (
+                &[true],
+                "E28A5C66E61E178C2BA19DB77B6CF9F7E2F0F56C17918CD13135E60CC848FE91",
+            ),
+            (
+                &[false],
+                "38B0767798252F21BF5702C48028B095428320F73A4B14DB1E25DE58543D2D2D",
+            ),
+            (
+                &[false, true],
+                "408A0A21C4A0F5DACAF9646AD6EB6FECD7F7A11F03ED1F48DFFF2185BC2C2408",
+            ),
+            (
+                &[false, false, true, true],
+                "45ABD206E61E3DF2EC9E264A6FEC8292141A633C28586388235541F9ADE75435",
+            ),
+            (
+                &[true, false, true, false],
+                "B255FDCAC27B40C7CE7848E2D3B7BF5EA0ED756DA81565AC804CCCA3E1D5D239",
+            ),
+        ];
+        let agg_nonce = AggNonce::from_bytes(&hex::decode(AGG_NONCE).unwrap()).unwrap();
+        let msg = hex::decode(MSG).unwrap();
+        for (x_only, expected) in vectors {
+            let context = tweaks
+                .iter()
+                .zip(x_only)
+                .try_fold(
+                    KeyAggContext::new(&keys).unwrap(),
+                    |context, (tweak, x_only)| context.apply_tweak(tweak, *x_only),
+                )
+                .unwrap();
+            let session = Session::new(&context, &agg_nonce, &msg).unwrap();
+            let signature = session.sign(sec_nonce(), &secret_key()).unwrap();
+            assert_eq!(hex::encode_upper(signature.serialize()), expected);
+        }
+        assert_eq!(
+            KeyAggContext::new(&keys)
+                .unwrap()
+                .apply_tweak(&CURVE_ORDER, true),
+            Err(Error::InvalidMusig)
+        );
+    }
+
+    #[test]
+    fn test_extended_key() {
+        let keys = [
+            "03935F972DA013F80AE011890FA89B67A27B7BE6CCB24D3274D18B2D4067F261A9",
+            "02F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9",
+        ]
+        .map(public_key);
+        let context = KeyAggContext::new(&keys).unwrap();
+        let key = PubKey {
+            network: Network::MainNet,
+            script_type: ScriptType::Legacy,
+            derivation: Derivation::master(),
+            parent_fingerprint: None,
+            extended_key: context.extended_key(),
+        };
+        assert_eq!(
+            hex::encode(context.public_key().serialize()),
+            "0354240c76b8f2999143301a99c7f721ee57eee0bce401df3afeaa9ae218c70f23"
+        );
+        let serialized_key: String = key.serialize();
+        assert_eq!(serialized_key, "xpub661MyMwAqRbcFt6tk3uaczE1y6EvM1TqXvawXcYmFEWijEM4PDBnuCXwwXEKGEouzXE6QLLRxjatMcLLzJ5LV5Nib1BN7vJg6yp45yHHRbm");
+
+        let child = context.derive_path("m/0/7".into()).unwrap();
+        let extended_key = context
+            .extended_key()
+            .derive_public_key(KeyIndex::Normal(0))
+            .and_then(|key| key.derive_public_key(KeyIndex::Normal(7)))
+            .unwrap();
+        assert_eq!(child.extended_key(), extended_key);
+        assert_eq!(
+            context.derive_path("m/0H".into()),
+            Err(Error::KeyIndexOutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_sign_with_derived_keys() {
+        let signers: Vec<_> = ["m/48H/0H/0H/2H", "m/48H/0H/1H/2H", "m/48H/0H/2H/2H"]
+            .iter()
+            .enumerate()
+            .map(|(i, path)| {
+                let master_key = ExtendedPrivKey::with_seed(&[i as u8; 32]).unwrap();
+                let key_chain = DefaultKeyChain::new(master_key);
+                key_chain.derive_private_key((*path).into()).unwrap().0
+            })
+            .collect();
+        let keys: Vec<_> = signers
+            .iter()
+            .map(ExtendedPubKey::from_private_key)
+            .collect();
+        let context = KeyAggContext::from_extended_keys(&keys)
+            .unwrap()
+            .derive_path("m/1/5".into())
+            .unwrap();
+        let output = context.tap_tweak(None).unwrap();
+        assert_eq!(
+            context.extended_key().tap_tweak(None).unwrap().0,
+            output.x_only_public_key()
+        );
+
+        let msg = [0x42; 32];
+        let nonces: Vec<_> = signers
+            .iter()
+            .enumerate()
+            .map(|(i, signer)| {
+                let public_key = signer.public_key();
+                let aggregate_key = output.x_only_public_key();
+                nonce_gen(
+                    &[i as u8; 32],
+                    Some(&signer.private_key()),
+                    &public_key,
+                    Some(&aggregate_key),
+                    Some(&msg),
+                    None,
+                )
+                .unwrap()
+            })
+            .collect();
+        let pub_nonces: Vec<_> = nonces.iter().map(|(_, pub_nonce)| *pub_nonce).collect();
+        let session = Session::new(&output, &AggNonce::new(&pub_nonces).unwrap(), &msg).unwrap();
+        let signatures: Vec<_> = nonces
+            .into_iter()
+            .zip(&signers)
+            .map(|((sec_nonce, _), signer)| session.sign(sec_nonce, &signer.private_key()).unwrap())
+            .collect();
+        let signature = session.aggregate(&signatures).unwrap();
+        assert!(context
+            .extended_key()
+            .verify_taproot(&msg, &signature, None)
+            .is_ok());
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{Error, ScriptType};
+
+/// Bitcoin network, determines the version bytes of extended keys and the address encoding.
+///
+/// Regtest and Signet share the testnet extended key versions, so decoding a tprv/tpub always
+/// gives [`Network::TestNet`]; set the network of the decoded key when it matters.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Network {
+    MainNet,
+    TestNet,
+    Regtest,
+    Signet,
+    /// Caller-supplied parameters, e.g. of an altcoin fork.
+    Custom(NetworkParams),
+    /// Unregistered version bytes of a decoded extended key.
+    ///
+    /// The key serializes back to the same version, but it has no public key version, address
+    /// prefixes or WIF prefix: converting it to a public key, an address or a WIF is
+    /// [`Error::UnknownVersion`] until its network is set to a [`Network::Custom`] one.
+    Unknown([u8; 4]),
+}
+
+/// Extended key versions, address prefixes and WIF prefix of a network.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct NetworkParams {
+    /// Version bytes of extended private keys, e.g. `0488ade4` (xprv).
+    pub xprv_version: [u8; 4],
+    /// Version bytes of extended public keys, e.g. `0488b21e` (xpub).
+    pub xpub_version: [u8; 4],
+    /// SLIP-132 private and public versions of P2WPKH nested in P2SH keys, e.g. yprv/ypub.
+    pub p2sh_p2wpkh_versions: ([u8; 4], [u8; 4]),
+    /// SLIP-132 private and public versions of multisig P2WSH nested in P2SH keys, e.g. Yprv/Ypub.
+    pub p2sh_p2wsh_versions: ([u8; 4], [u8; 4]),
+    /// SLIP-132 private and public versions of P2WPKH keys, e.g. zprv/zpub.
+    pub p2wpkh_versions: ([u8; 4], [u8; 4]),
+    /// SLIP-132 private and public versions of multisig P2WSH keys, e.g. Zprv/Zpub.
+    pub p2wsh_versions: ([u8; 4], [u8; 4]),
+    /// Version byte of P2PKH addresses.
+    pub p2pkh_prefix: u8,
+    /// Version byte of P2SH addresses.
+    pub p2sh_prefix: u8,
+    /// Human-readable part of SegWit addresses.
+    pub bech32_hrp: &'static str,
+    /// Version byte of WIF private keys.
+    pub wif_prefix: u8,
+}
+
+impl NetworkParams {
+    pub const MAINNET: NetworkParams = NetworkParams {
+        xprv_version: [0x04, 0x88, 0xad, 0xe4],
+        xpub_version: [0x04, 0x88, 0xb2, 0x1e],
+        p2sh_p2wpkh_versions: ([0x04, 0x9d, 0x78, 0x78], [0x04, 0x9d, 0x7c, 0xb2]),
+        p2sh_p2wsh_versions: ([0x02, 0x95, 0xb0, 0x05], [0x02, 0x95, 0xb4, 0x3f]),
+        p2wpkh_versions: ([0x04, 0xb2, 0x43, 0x0c], [0x04, 0xb2, 0x47, 0x46]),
+        p2wsh_versions: ([0x02, 0xaa, 0x7a, 0x99], [0x02, 0xaa, 0x7e, 0xd3]),
+        p2pkh_prefix: 0x00,
+        p2sh_prefix: 0x05,
+        bech32_hrp: "bc",
+        wif_prefix: 0x80,
+    };
+
+    pub const TESTNET: NetworkParams = NetworkParams {
+        xprv_version: [0x04, 0x35, 0x83, 0x94],
+        xpub_version: [0x04, 0x35, 0x87, 0xcf],
+        p2sh_p2wpkh_versions: ([0x04, 0x4a, 0x4e, 0x28], [0x04, 0x4a, 0x52, 0x62]),
+        p2sh_p2wsh_versions: ([0x02, 0x42, 0x85, 0xb5], [0x02, 0x42, 0x89, 0xef]),
+        p2wpkh_versions: ([0x04, 0x5f, 0x18, 0xbc], [0x04, 0x5f, 0x1c, 0xf6]),
+        p2wsh_versions: ([0x02, 0x57, 0x50, 0x48], [0x02, 0x57, 0x54, 0x83]),
+        p2pkh_prefix: 0x6f,
+        p2sh_prefix: 0xc4,
+        bech32_hrp: "tb",
+        wif_prefix: 0xef,
+    };
+
+    pub const REGTEST: NetworkParams = NetworkParams {
+        bech32_hrp: "bcrt",
+        ..NetworkParams::TESTNET
+    };
+
+    pub const SIGNET: NetworkParams = NetworkParams::TESTNET;
+
+    /// Private and public extended key versions of `script_type`.
+    pub fn versions(&self, script_type: ScriptType) -> ([u8; 4], [u8; 4]) {
+        match script_type {
+            ScriptType::Legacy => (self.xprv_version, self.xpub_version),
+            ScriptType::P2shP2wpkh => self.p2sh_p2wpkh_versions,
+            ScriptType::P2shP2wsh => self.p2sh_p2wsh_versions,
+            ScriptType::P2wpkh => self.p2wpkh_versions,
+            ScriptType::P2wsh => self.p2wsh_versions,
+        }
+    }
+}
+
+impl Network {
+    /// Parameters of the network, [`Error::UnknownVersion`] for [`Network::Unknown`].
+    pub fn params(self) -> Result<NetworkParams, Error> {
+        match self {
+            Network::MainNet => Ok(NetworkParams::MAINNET),
+            Network::TestNet => Ok(NetworkParams::TESTNET),
+            Network::Regtest => Ok(NetworkParams::REGTEST),
+            Network::Signet => Ok(NetworkParams::SIGNET),
+            Network::Custom(params) => Ok(params),
+            Network::Unknown(_) => Err(Error::UnknownVersion),
+        }
+    }
+}
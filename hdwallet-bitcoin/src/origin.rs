@@ -0,0 +1,250 @@
+use crate::{Error, PubKey};
+use hdwallet::{
+    traits::{Deserialize, Serialize},
+    ChainPath, ChainPathError, KeyIndex, SubPath,
+};
+use std::{fmt, str::FromStr};
+
+/// Origin of a key, the `[fingerprint/path]` prefix of a BIP-380 key expression.
+///
+/// Syntax errors are reported as [`Error::InvalidDescriptor`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeyOrigin {
+    /// Fingerprint of the master key.
+    pub fingerprint: [u8; 4],
+    /// Path from the master key to the key.
+    pub path: Vec<KeyIndex>,
+}
+
+/// Extended public key with its origin, `[fingerprint/path]xpub...` as exported by hardware
+/// wallets.
+///
+/// The origin must agree with the key: its path has the depth of the key and ends with the
+/// child number of the key, and the fingerprint is checked against the key itself for a master
+/// key and against the parent fingerprint for a depth 1 key. Derived children keep the full path
+/// from the master key.
+///
+/// ```rust
+/// use hdwallet_bitcoin::OriginPubKey;
+///
+/// let key: OriginPubKey = "[3442193e/0h/1]xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ"
+///     .parse()
+///     .unwrap();
+/// let child = key.derive_path("m/0/5".into()).unwrap();
+/// assert_eq!(child.origin.to_string(), "3442193e/0h/1/0/5");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OriginPubKey {
+    pub origin: KeyOrigin,
+    pub key: PubKey,
+}
+
+impl OriginPubKey {
+    /// Attach `origin` to `key`, [`Error::KeyOriginMismatch`] if they disagree.
+    pub fn new(origin: KeyOrigin, key: PubKey) -> Result<Self, Error> {
+        let depth_matches = origin.path.len() == usize::from(key.derivation.depth);
+        let index_matches = origin.path.last().copied() == key.derivation.key_index;
+        let fingerprint_matches = match key.derivation.depth {
+            0 => origin.fingerprint == key.fingerprint(),
+            1 => origin.fingerprint == key.parent_fingerprint(),
+            _ => true,
+        };
+        if !(depth_matches && index_matches && fingerprint_matches) {
+            return Err(Error::KeyOriginMismatch);
+        }
+        Ok(OriginPubKey { origin, key })
+    }
+
+    /// Derive a normal child key, its origin path is extended with `key_index`.
+    pub fn derive_public_key(&self, key_index: KeyIndex) -> Result<OriginPubKey, Error> {
+        let key = self.key.derive_public_key(key_index)?;
+        let mut origin = self.origin.clone();
+        origin.path.push(key_index);
+        Ok(OriginPubKey { origin, key })
+    }
+
+    /// Derive a descendant key, `m` in chain_path denotes this key.
+    pub fn derive_path(&self, chain_path: ChainPath) -> Result<OriginPubKey, Error> {
+        let mut iter = chain_path.iter();
+        // chain_path must start with root
+        if iter.next() != Some(Ok(SubPath::Root)) {
+            return Err(ChainPathError::Invalid.into());
+        }
+        let mut key = self.clone();
+        for sub_path in iter {
+            match sub_path? {
+                SubPath::Child(key_index) => key = key.derive_public_key(key_index)?,
+                SubPath::Root => return Err(ChainPathError::Invalid.into()),
+            }
+        }
+        Ok(key)
+    }
+}
+
+/// Parse a path component, hardened with a `h`, `H` or `'` suffix.
+pub(crate) fn parse_key_index(s: &str) -> Result<KeyIndex, Error> {
+    let (index, hardened) = match s.strip_suffix(|c| c == 'h' || c == 'H' || c == '\'') {
+        Some(index) => (index, true),
+        None => (s, false),
+    };
+    // u32::from_str also takes a leading `+`
+    if !index.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(Error::InvalidDescriptor);
+    }
+    let index = index
+        .parse::<u32>()
+        .ok()
+        .filter(|index| *index < 1 << 31)
+        .ok_or(Error::InvalidDescriptor)?;
+    if hardened {
+        Ok(KeyIndex::hardened_from_normalize_index(index)?)
+    } else {
+        Ok(KeyIndex::Normal(index))
+    }
+}
+
+/// Write each index as `/<index>`, hardened ones with a `h` suffix.
+pub(crate) fn write_path(f: &mut fmt::Formatter, path: &[KeyIndex]) -> fmt::Result {
+    for index in path {
+        match index {
+            KeyIndex::Normal(i) => write!(f, "/{}", i)?,
+            KeyIndex::Hardened(_) => write!(f, "/{}h", index.normalize_index())?,
+        }
+    }
+    Ok(())
+}
+
+impl FromStr for KeyOrigin {
+    type Err = Error;
+
+    /// Parse `fingerprint/path`, e.g. `d34db33f/84h/0h/0h`.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let mut components = s.split('/');
+        let fingerprint = components.next().unwrap_or_default();
+        let mut origin = KeyOrigin {
+            fingerprint: [0; 4],
+            path: components
+                .map(parse_key_index)
+                .collect::<Result<Vec<_>, _>>()?,
+        };
+        if fingerprint.len() != 8 {
+            return Err(Error::InvalidDescriptor);
+        }
+        hex::decode_to_slice(fingerprint, &mut origin.fingerprint)
+            .map_err(|_| Error::InvalidDescriptor)?;
+        Ok(origin)
+    }
+}
+
+impl fmt::Display for KeyOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&hex::encode(self.fingerprint))?;
+        write_path(f, &self.path)
+    }
+}
+
+impl FromStr for OriginPubKey {
+    type Err = Error;
+
+    /// Parse `[fingerprint/path]xpub...`, any SLIP-132 version is accepted.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let (origin, key) = s
+            .strip_prefix('[')
+            .and_then(|s| s.split_once(']'))
+            .ok_or(Error::InvalidDescriptor)?;
+        OriginPubKey::new(origin.parse()?, PubKey::deserialize(key.to_string())?)
+    }
+}
+
+impl fmt::Display for OriginPubKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let key: String = self.key.serialize();
+        write!(f, "[{}]{}", self.origin, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // BIP-32 test vector 1, master fingerprint 3442193e
+    const MASTER_XPUB: &str = "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8";
+    const XPUB_0H: &str = "xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw";
+    const XPUB_0H_1: &str = "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ";
+    const XPUB_0H_1_2H: &str = "xpub6D4BDPcP2GT577Vvch3R8wDkScZWzQzMMUm3PWbmWvVJrZwQY4VUNgqFJPMM3No2dFDFGTsxxpG5uJh7n7epu4trkrX7x7DogT5Uv6fcLW5";
+
+    #[test]
+    fn test_origin_pub_key() {
+        for s in &[
+            format!("[3442193e]{}", MASTER_XPUB),
+            format!("[3442193e/0h]{}", XPUB_0H),
+            format!("[3442193e/0h/1]{}", XPUB_0H_1),
+            format!("[3442193e/0h/1/2h]{}", XPUB_0H_1_2H),
+        ] {
+            let key: OriginPubKey = s.parse().expect(s);
+            assert_eq!(&key.to_string(), s);
+        }
+
+        let key: OriginPubKey = format!("[3442193e/0'/1/2H]{}", XPUB_0H_1_2H)
+            .parse()
+            .expect("parse");
+        assert_eq!(key.origin.path.len(), 3);
+        let child = key.derive_path("m/2/1000000000".into()).expect("derive");
+        assert_eq!(
+            child.to_string(),
+            "[3442193e/0h/1/2h/2/1000000000]xpub6H1LXWLaKsWFhvm6RVpEL9P4KfRZSW7abD2ttkWP3SSQvnyA8FSVqNTEcYFgJS2UaFcxupHiYkro49S8yGasTvXEYBVPamhGW6cFJodrTHy"
+        );
+        assert_eq!(child.to_string().parse(), Ok(child));
+        assert!(key.derive_path("m/0H".into()).is_err());
+    }
+
+    #[test]
+    fn test_parse_key_index() {
+        assert_eq!(
+            parse_key_index("44h"),
+            Ok(KeyIndex::hardened_from_normalize_index(44).unwrap())
+        );
+        assert_eq!(parse_key_index("1"), Ok(KeyIndex::Normal(1)));
+        for s in &["+44h", "+1", "-1", " 1", "1 ", "", "h", "2147483648"] {
+            assert_eq!(parse_key_index(s), Err(Error::InvalidDescriptor), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_origin_mismatch() {
+        for s in &[
+            // depth
+            format!("[3442193e/0h]{}", XPUB_0H_1),
+            format!("[3442193e/0h/1/2]{}", XPUB_0H_1),
+            // child number
+            format!("[3442193e/0h/2]{}", XPUB_0H_1),
+            format!("[3442193e/0]{}", XPUB_0H),
+            // fingerprint
+            format!("[00000000]{}", MASTER_XPUB),
+            format!("[deadbeef/0h]{}", XPUB_0H),
+        ] {
+            assert_eq!(
+                s.parse::<OriginPubKey>(),
+                Err(Error::KeyOriginMismatch),
+                "{}",
+                s
+            );
+        }
+        for s in &[
+            XPUB_0H_1.to_string(),
+            format!("3442193e/0h/1]{}", XPUB_0H_1),
+            format!("[3442193e/0h/1{}", XPUB_0H_1),
+            format!("[3442193/0h/1]{}", XPUB_0H_1),
+            format!("[3442193e/0x/1]{}", XPUB_0H_1),
+            format!("[3442193e/+0h/1]{}", XPUB_0H_1),
+            format!("[3442193e/0h/+1]{}", XPUB_0H_1),
+        ] {
+            assert_eq!(
+                s.parse::<OriginPubKey>(),
+                Err(Error::InvalidDescriptor),
+                "{}",
+                s
+            );
+        }
+    }
+}
@@ -0,0 +1,408 @@
+//! BIP-47 reusable payment codes.
+
+use crate::{
+    account::{derive_public_key, derive_secret_key},
+    hash::sha256,
+    secp256k1_sign_only, secp256k1_verify_only,
+    serialize::{encode_checksum, verify_checksum},
+    Address, AddressType, Error, Network, OutPoint, ScriptType,
+};
+use base58::{FromBase58, ToBase58};
+use hdwallet::{
+    backend::{Backend, DefaultBackend},
+    secp256k1::{self, ecdh, PublicKey, Scalar, SecretKey},
+    ExtendedPubKey, KeyChain, KeyIndex,
+};
+use std::{fmt, str::FromStr};
+
+/// Base58check version byte of payment codes, they start with `PM8T`.
+const VERSION_BYTE: u8 = 0x47;
+/// Length of a binary payment code.
+const PAYMENT_CODE_LEN: usize = 80;
+/// Payment code version, the only one supported.
+const PAYMENT_CODE_VERSION: u8 = 1;
+
+/// BIP-47 version 1 payment code, the public key and chain code of a
+/// `m/47'/coin_type'/account'` account.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct PaymentCode {
+    /// Features bit field, bit 0 asks for Bitmessage notifications.
+    pub features: u8,
+    pub public_key: PublicKey,
+    pub chain_code: [u8; 32],
+}
+
+impl PaymentCode {
+    /// Parse the 80 bytes binary serialization.
+    ///
+    /// Versions other than 1 and invalid public keys are [`Error::InvalidPaymentCode`].
+    pub fn from_bytes(data: &[u8]) -> Result<Self, Error> {
+        if data.len() != PAYMENT_CODE_LEN || data[0] != PAYMENT_CODE_VERSION {
+            return Err(Error::InvalidPaymentCode);
+        }
+        let public_key =
+            PublicKey::from_slice(&data[2..35]).map_err(|_| Error::InvalidPaymentCode)?;
+        let mut chain_code = [0; 32];
+        chain_code.copy_from_slice(&data[35..67]);
+        Ok(PaymentCode {
+            features: data[1],
+            public_key,
+            chain_code,
+        })
+    }
+
+    /// The 80 bytes binary serialization, the reserved bytes are zero.
+    pub fn to_bytes(&self) -> [u8; PAYMENT_CODE_LEN] {
+        let mut data = [0; PAYMENT_CODE_LEN];
+        data[0] = PAYMENT_CODE_VERSION;
+        data[1] = self.features;
+        data[2..35].copy_from_slice(&self.public_key.serialize());
+        data[35..67].copy_from_slice(&self.chain_code);
+        data
+    }
+
+    /// Public key at `index` of the payment code, the notification key is at index 0 and the
+    /// payment keys from index 0 on.
+    pub fn derive_public_key(&self, index: u32) -> Result<PublicKey, Error> {
+        let key = ExtendedPubKey {
+            public_key: self.public_key,
+            chain_code: self.chain_code.to_vec(),
+        };
+        Ok(key.derive_public_key(KeyIndex::Normal(index))?.public_key)
+    }
+
+    /// P2PKH address watched for the notification transactions of senders.
+    pub fn notification_address(&self, network: Network) -> Result<Address, Error> {
+        Address::p2pkh(&self.derive_public_key(0)?, network)
+    }
+
+    /// Blinded payment code in the `OP_RETURN` output of the notification transaction from
+    /// `self` to `recipient`.
+    ///
+    /// `designated_key` is the private key of the designated input, usually the first input,
+    /// and `outpoint` is the outpoint it spends.
+    pub fn notification_payload(
+        &self,
+        recipient: &PaymentCode,
+        designated_key: &SecretKey,
+        outpoint: &OutPoint,
+    ) -> Result<[u8; PAYMENT_CODE_LEN], Error> {
+        let secret = shared_secret(&recipient.derive_public_key(0)?, designated_key);
+        let mut payload = self.to_bytes();
+        blind(&mut payload, &secret, outpoint);
+        Ok(payload)
+    }
+}
+
+impl FromStr for PaymentCode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let data = s.from_base58().map_err(|_| Error::InvalidBase58)?;
+        match verify_checksum(&data)? {
+            [VERSION_BYTE, payload @ ..] => PaymentCode::from_bytes(payload),
+            _ => Err(Error::InvalidPaymentCode),
+        }
+    }
+}
+
+impl fmt::Display for PaymentCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut data = vec![VERSION_BYTE];
+        data.extend_from_slice(&self.to_bytes());
+        encode_checksum(&mut data);
+        f.write_str(&data.to_base58())
+    }
+}
+
+/// Payment code account of a BIP-47 wallet at `m/47'/coin_type'/account'`.
+///
+/// Only the payment code is kept, the methods computing shared secrets derive the private keys
+/// from the key chain the account was created from.
+///
+/// ```rust
+/// use hdwallet::{DefaultKeyChain, ExtendedPrivKey};
+/// use hdwallet_bitcoin::{AddressType, Network, PaymentCodeAccount};
+///
+/// let alice = DefaultKeyChain::new(ExtendedPrivKey::random(&mut rand::thread_rng()).unwrap());
+/// let bob = DefaultKeyChain::new(ExtendedPrivKey::random(&mut rand::thread_rng()).unwrap());
+/// let alice_account = PaymentCodeAccount::new(&alice, 0, 0).unwrap();
+/// let bob_account = PaymentCodeAccount::new(&bob, 0, 0).unwrap();
+/// let sent = alice_account
+///     .send_address(&alice, &bob_account.payment_code, 0, AddressType::P2wpkh, Network::MainNet)
+///     .unwrap();
+/// let received = bob_account
+///     .receive_address(&bob, &alice_account.payment_code, 0, AddressType::P2wpkh, Network::MainNet)
+///     .unwrap();
+/// assert_eq!(sent, received);
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaymentCodeAccount {
+    pub coin_type: u32,
+    /// Account number, the hardened index of the third derivation step.
+    pub index: u32,
+    /// Version 1 payment code of the account.
+    pub payment_code: PaymentCode,
+}
+
+impl PaymentCodeAccount {
+    /// Derive account `index` of `coin_type` from `key_chain`, e.g. coin type 0 for Bitcoin.
+    pub fn new(key_chain: &impl KeyChain, coin_type: u32, index: u32) -> Result<Self, Error> {
+        let path = format!("m/47H/{}H/{}H", coin_type, index);
+        let key = derive_public_key(key_chain, &path, ScriptType::Legacy, Network::MainNet)?;
+        let mut chain_code = [0; 32];
+        chain_code.copy_from_slice(&key.extended_key.chain_code);
+        Ok(PaymentCodeAccount {
+            coin_type,
+            index,
+            payment_code: PaymentCode {
+                features: 0,
+                public_key: key.extended_key.public_key,
+                chain_code,
+            },
+        })
+    }
+
+    /// Derivation path of the account, e.g. `m/47H/0H/0H`.
+    pub fn path(&self) -> String {
+        format!("m/47H/{}H/{}H", self.coin_type, self.index)
+    }
+
+    /// Payment code of the sender of a notification transaction to this account.
+    ///
+    /// `designated_key` is the public key of the designated input, `outpoint` the outpoint it
+    /// spends and `payload` the data of the `OP_RETURN` output.
+    pub fn read_notification(
+        &self,
+        key_chain: &impl KeyChain,
+        designated_key: &PublicKey,
+        outpoint: &OutPoint,
+        payload: &[u8],
+    ) -> Result<PaymentCode, Error> {
+        if payload.len() != PAYMENT_CODE_LEN {
+            return Err(Error::InvalidPaymentCode);
+        }
+        let secret = shared_secret(designated_key, &self.secret_key(key_chain, 0)?);
+        let mut data = [0; PAYMENT_CODE_LEN];
+        data.copy_from_slice(payload);
+        blind(&mut data, &secret, outpoint);
+        PaymentCode::from_bytes(&data)
+    }
+
+    /// Address of the `index`th payment to `recipient`.
+    ///
+    /// A shared secret out of the curve order, with negligible probability, is
+    /// [`Error::Secp`]; BIP-47 skips to the next index then.
+    pub fn send_address(
+        &self,
+        key_chain: &impl KeyChain,
+        recipient: &PaymentCode,
+        index: u32,
+        address_type: AddressType,
+        network: Network,
+    ) -> Result<Address, Error> {
+        let public_key = recipient.derive_public_key(index)?;
+        let tweak = shared_scalar(&public_key, &self.secret_key(key_chain, 0)?)?;
+        let public_key = public_key.add_exp_tweak(secp256k1_verify_only(), &tweak)?;
+        Address::from_public_key(&public_key, address_type, network)
+    }
+
+    /// Private key of the `index`th payment from `sender`.
+    pub fn receive_key(
+        &self,
+        key_chain: &impl KeyChain,
+        sender: &PaymentCode,
+        index: u32,
+    ) -> Result<SecretKey, Error> {
+        let secret_key = self.secret_key(key_chain, index)?;
+        let tweak = shared_scalar(&sender.derive_public_key(0)?, &secret_key)?;
+        Ok(secret_key.add_tweak(&tweak)?)
+    }
+
+    /// Address of the `index`th payment from `sender`, the counterpart of
+    /// [`PaymentCodeAccount::send_address`].
+    pub fn receive_address(
+        &self,
+        key_chain: &impl KeyChain,
+        sender: &PaymentCode,
+        index: u32,
+        address_type: AddressType,
+        network: Network,
+    ) -> Result<Address, Error> {
+        let secret_key = self.receive_key(key_chain, sender, index)?;
+        let public_key = PublicKey::from_secret_key(secp256k1_sign_only(), &secret_key);
+        Address::from_public_key(&public_key, address_type, network)
+    }
+
+    fn secret_key(&self, key_chain: &impl KeyChain, index: u32) -> Result<SecretKey, Error> {
+        derive_secret_key(key_chain, &format!("{}/{}", self.path(), index))
+    }
+}
+
+/// X coordinate of the ECDH point.
+fn shared_secret(public_key: &PublicKey, secret_key: &SecretKey) -> [u8; 32] {
+    let mut secret = [0; 32];
+    secret.copy_from_slice(&ecdh::shared_secret_point(public_key, secret_key)[..32]);
+    secret
+}
+
+/// SHA256 of the shared secret as a scalar tweaking the payment keys.
+fn shared_scalar(public_key: &PublicKey, secret_key: &SecretKey) -> Result<Scalar, Error> {
+    Scalar::from_be_bytes(sha256(&shared_secret(public_key, secret_key)))
+        .map_err(|_| Error::Secp(secp256k1::Error::InvalidTweak))
+}
+
+/// XOR the public key x coordinate and the chain code with `HMAC-SHA512(outpoint, secret)`,
+/// blinding and unblinding alike.
+fn blind(data: &mut [u8; PAYMENT_CODE_LEN], secret: &[u8; 32], outpoint: &OutPoint) {
+    let mut key = outpoint.txid.to_vec();
+    key.extend_from_slice(&outpoint.vout.to_le_bytes());
+    let mask = DefaultBackend::hmac_sha512(&DefaultBackend::hmac_sha512_key(&key), &[secret]);
+    for (byte, mask) in data[3..67].iter_mut().zip(mask.iter()) {
+        *byte ^= mask;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hdwallet::{DefaultKeyChain, ExtendedPrivKey};
+
+    // BIP-47 test vectors
+    const ALICE_SEED: &str = "64dca76abc9c6f0cf3d212d248c380c4622c8f93b2c425ec6a5567fd5db57e10d3e6f94a2f6af4ac2edb8998072aad92098db73558c323777abf5bd1082d970a";
+    const ALICE_CODE: &str = "PM8TJTLJbPRGxSbc8EJi42Wrr6QbNSaSSVJ5Y3E4pbCYiTHUskHg13935Ubb7q8tx9GVbh2UuRnBc3WSyJHhUrw8KhprKnn9eDznYGieTzFcwQRya4GA";
+    const BOB_SEED: &str = "87eaaac5a539ab028df44d9110defbef3797ddb805ca309f61a69ff96dbaa7ab5b24038cf029edec5235d933110f0aea8aeecf939ed14fc20730bba71e4b1110";
+    const BOB_CODE: &str = "PM8TJS2JxQ5ztXUpBBRnpTbcUXbUHy2T1abfrb3KkAAtMEGNbey4oumH7Hc578WgQJhPjBxteQ5GHHToTYHE3A1w6p7tU6KSoFmWBVbFGjKPisZDbP97";
+    const ADDRESSES: [&str; 10] = [
+        "141fi7TY3h936vRUKh1qfUZr8rSBuYbVBK",
+        "12u3Uued2fuko2nY4SoSFGCoGLCBUGPkk6",
+        "1FsBVhT5dQutGwaPePTYMe5qvYqqjxyftc",
+        "1CZAmrbKL6fJ7wUxb99aETwXhcGeG3CpeA",
+        "1KQvRShk6NqPfpr4Ehd53XUhpemBXtJPTL",
+        "1KsLV2F47JAe6f8RtwzfqhjVa8mZEnTM7t",
+        "1DdK9TknVwvBrJe7urqFmaxEtGF2TMWxzD",
+        "16DpovNuhQJH7JUSZQFLBQgQYS4QB9Wy8e",
+        "17qK2RPGZMDcci2BLQ6Ry2PDGJErrNojT5",
+        "1GxfdfP286uE24qLZ9YRP3EWk2urqXgC4s",
+    ];
+
+    fn key_chain(seed: &str) -> DefaultKeyChain {
+        let seed = hex::decode(seed).expect("decode");
+        DefaultKeyChain::new(ExtendedPrivKey::with_seed(&seed).expect("master key"))
+    }
+
+    #[test]
+    fn test_payment_code() {
+        for (seed, code, notification_address) in &[
+            (ALICE_SEED, ALICE_CODE, "1JDdmqFLhpzcUwPeinhJbUPw4Co3aWLyzW"),
+            (BOB_SEED, BOB_CODE, "1ChvUUvht2hUQufHBXF8NgLhW8SwE2ecGV"),
+        ] {
+            let account = PaymentCodeAccount::new(&key_chain(seed), 0, 0).unwrap();
+            assert_eq!(account.path(), "m/47H/0H/0H");
+            assert_eq!(account.payment_code.to_string(), *code);
+            assert_eq!(
+                code.parse::<PaymentCode>(),
+                Ok(account.payment_code.clone())
+            );
+            assert_eq!(
+                account
+                    .payment_code
+                    .notification_address(Network::MainNet)
+                    .unwrap()
+                    .to_string(),
+                *notification_address
+            );
+        }
+
+        let mut data = ALICE_CODE.parse::<PaymentCode>().unwrap().to_bytes();
+        // versions 2 and 3 use a different notification
+        for version in 2..=3 {
+            data[0] = version;
+            assert_eq!(
+                PaymentCode::from_bytes(&data),
+                Err(Error::InvalidPaymentCode)
+            );
+        }
+        assert_eq!(
+            PaymentCode::from_bytes(&data[..79]),
+            Err(Error::InvalidPaymentCode)
+        );
+        // an xpub is no payment code
+        assert_eq!(
+            "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8"
+                .parse::<PaymentCode>(),
+            Err(Error::InvalidPaymentCode)
+        );
+    }
+
+    #[test]
+    fn test_notification() {
+        let alice = PaymentCodeAccount::new(&key_chain(ALICE_SEED), 0, 0).unwrap();
+        let bob_key_chain = key_chain(BOB_SEED);
+        let bob = PaymentCodeAccount::new(&bob_key_chain, 0, 0).unwrap();
+        let designated_key = SecretKey::from_slice(
+            &hex::decode("1b7a10f45118e2519a8dd46ef81591c1ae501d082b6610fdda3de7a3c932880d")
+                .unwrap(),
+        )
+        .unwrap();
+        let mut txid = [0; 32];
+        txid.copy_from_slice(
+            &hex::decode("86f411ab1c8e70ae8a0795ab7a6757aea6e4d5ae1826fc7b8f00c597d500609c")
+                .unwrap(),
+        );
+        let outpoint = OutPoint { txid, vout: 1 };
+
+        let payload = alice
+            .payment_code
+            .notification_payload(&bob.payment_code, &designated_key, &outpoint)
+            .unwrap();
+        assert_eq!(hex::encode(payload), "010002063e4eb95e62791b06c50e1a3a942e1ecaaa9afbbeb324d16ae6821e091611fa96c0cf048f607fe51a0327f5e2528979311c78cb2de0d682c61e1180fc3d543b00000000000000000000000000");
+
+        let designated_public_key =
+            PublicKey::from_secret_key(secp256k1_sign_only(), &designated_key);
+        assert_eq!(
+            bob.read_notification(&bob_key_chain, &designated_public_key, &outpoint, &payload),
+            Ok(alice.payment_code.clone())
+        );
+        assert_eq!(
+            bob.read_notification(
+                &bob_key_chain,
+                &designated_public_key,
+                &outpoint,
+                &payload[1..]
+            ),
+            Err(Error::InvalidPaymentCode)
+        );
+    }
+
+    #[test]
+    fn test_payment_addresses() {
+        let alice_key_chain = key_chain(ALICE_SEED);
+        let alice = PaymentCodeAccount::new(&alice_key_chain, 0, 0).unwrap();
+        let bob_key_chain = key_chain(BOB_SEED);
+        let bob = PaymentCodeAccount::new(&bob_key_chain, 0, 0).unwrap();
+        for (index, address) in ADDRESSES.iter().enumerate() {
+            let index = index as u32;
+            let sent = alice
+                .send_address(
+                    &alice_key_chain,
+                    &bob.payment_code,
+                    index,
+                    AddressType::P2pkh,
+                    Network::MainNet,
+                )
+                .unwrap();
+            assert_eq!(sent.to_string(), *address);
+            let received = bob
+                .receive_address(
+                    &bob_key_chain,
+                    &alice.payment_code,
+                    index,
+                    AddressType::P2pkh,
+                    Network::MainNet,
+                )
+                .unwrap();
+            assert_eq!(received, sent);
+        }
+    }
+}
@@ -0,0 +1,841 @@
+mod sign;
+
+use crate::{
+    base64,
+    transaction::{write_var_bytes, OutPoint, Reader, Transaction, TxIn, TxOut},
+    Error, KeyOrigin,
+};
+use hdwallet::{
+    secp256k1::{PublicKey, XOnlyPublicKey},
+    traits::{Deserialize, Serialize},
+    KeyIndex,
+};
+use std::{fmt, str::FromStr};
+
+const MAGIC: &[u8] = b"psbt\xff";
+
+const PSBT_GLOBAL_UNSIGNED_TX: u8 = 0x00;
+const PSBT_GLOBAL_XPUB: u8 = 0x01;
+const PSBT_GLOBAL_TX_VERSION: u8 = 0x02;
+const PSBT_GLOBAL_FALLBACK_LOCKTIME: u8 = 0x03;
+const PSBT_GLOBAL_INPUT_COUNT: u8 = 0x04;
+const PSBT_GLOBAL_OUTPUT_COUNT: u8 = 0x05;
+const PSBT_GLOBAL_VERSION: u8 = 0xfb;
+
+const PSBT_IN_NON_WITNESS_UTXO: u8 = 0x00;
+const PSBT_IN_WITNESS_UTXO: u8 = 0x01;
+const PSBT_IN_PARTIAL_SIG: u8 = 0x02;
+const PSBT_IN_SIGHASH_TYPE: u8 = 0x03;
+const PSBT_IN_REDEEM_SCRIPT: u8 = 0x04;
+const PSBT_IN_WITNESS_SCRIPT: u8 = 0x05;
+const PSBT_IN_BIP32_DERIVATION: u8 = 0x06;
+const PSBT_IN_FINAL_SCRIPTSIG: u8 = 0x07;
+const PSBT_IN_FINAL_SCRIPTWITNESS: u8 = 0x08;
+const PSBT_IN_PREVIOUS_TXID: u8 = 0x0e;
+const PSBT_IN_OUTPUT_INDEX: u8 = 0x0f;
+const PSBT_IN_SEQUENCE: u8 = 0x10;
+const PSBT_IN_REQUIRED_TIME_LOCKTIME: u8 = 0x11;
+const PSBT_IN_REQUIRED_HEIGHT_LOCKTIME: u8 = 0x12;
+const PSBT_IN_TAP_KEY_SIG: u8 = 0x13;
+const PSBT_IN_TAP_BIP32_DERIVATION: u8 = 0x16;
+const PSBT_IN_TAP_INTERNAL_KEY: u8 = 0x17;
+const PSBT_IN_TAP_MERKLE_ROOT: u8 = 0x18;
+
+const PSBT_OUT_REDEEM_SCRIPT: u8 = 0x00;
+const PSBT_OUT_WITNESS_SCRIPT: u8 = 0x01;
+const PSBT_OUT_BIP32_DERIVATION: u8 = 0x02;
+const PSBT_OUT_AMOUNT: u8 = 0x03;
+const PSBT_OUT_SCRIPT: u8 = 0x04;
+const PSBT_OUT_TAP_INTERNAL_KEY: u8 = 0x05;
+const PSBT_OUT_TAP_BIP32_DERIVATION: u8 = 0x07;
+
+/// Global types of version 0, the rest are version 2 only.
+const GLOBAL_SINGLE_TYPES: &[u8] = &[
+    PSBT_GLOBAL_UNSIGNED_TX,
+    PSBT_GLOBAL_TX_VERSION,
+    PSBT_GLOBAL_FALLBACK_LOCKTIME,
+    PSBT_GLOBAL_INPUT_COUNT,
+    PSBT_GLOBAL_OUTPUT_COUNT,
+    PSBT_GLOBAL_VERSION,
+];
+const INPUT_SINGLE_TYPES: &[u8] = &[
+    PSBT_IN_NON_WITNESS_UTXO,
+    PSBT_IN_WITNESS_UTXO,
+    PSBT_IN_SIGHASH_TYPE,
+    PSBT_IN_REDEEM_SCRIPT,
+    PSBT_IN_WITNESS_SCRIPT,
+    PSBT_IN_FINAL_SCRIPTSIG,
+    PSBT_IN_FINAL_SCRIPTWITNESS,
+    PSBT_IN_PREVIOUS_TXID,
+    PSBT_IN_OUTPUT_INDEX,
+    PSBT_IN_SEQUENCE,
+    PSBT_IN_REQUIRED_TIME_LOCKTIME,
+    PSBT_IN_REQUIRED_HEIGHT_LOCKTIME,
+    PSBT_IN_TAP_KEY_SIG,
+    PSBT_IN_TAP_INTERNAL_KEY,
+    PSBT_IN_TAP_MERKLE_ROOT,
+];
+const OUTPUT_SINGLE_TYPES: &[u8] = &[
+    PSBT_OUT_REDEEM_SCRIPT,
+    PSBT_OUT_WITNESS_SCRIPT,
+    PSBT_OUT_AMOUNT,
+    PSBT_OUT_SCRIPT,
+    PSBT_OUT_TAP_INTERNAL_KEY,
+];
+
+/// Taproot key derivation, the x-only key, the hashes of the leaves it is used in and its origin.
+type TapDerivation = (XOnlyPublicKey, Vec<[u8; 32]>, KeyOrigin);
+
+/// Key-value pairs of a PSBT map in serialization order, each key starts with its key type.
+///
+/// Fields this crate doesn't interpret are kept as is.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PsbtMap {
+    pairs: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl PsbtMap {
+    pub fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        self.pairs
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_slice())
+    }
+
+    /// Insert a pair, replacing and returning the value of an existing key.
+    pub fn insert(&mut self, key: Vec<u8>, value: Vec<u8>) -> Option<Vec<u8>> {
+        match self.pairs.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => Some(std::mem::replace(v, value)),
+            None => {
+                self.pairs.push((key, value));
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &[u8]) -> Option<Vec<u8>> {
+        let position = self.pairs.iter().position(|(k, _)| k == key)?;
+        Some(self.pairs.remove(position).1)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8], &[u8])> {
+        self.pairs
+            .iter()
+            .map(|(key, value)| (key.as_slice(), value.as_slice()))
+    }
+
+    /// Key data and value of the pairs of `key_type`.
+    fn entries(&self, key_type: u8) -> impl Iterator<Item = (&[u8], &[u8])> {
+        self.iter()
+            .filter(move |(key, _)| key.first() == Some(&key_type))
+            .map(|(key, value)| (&key[1..], value))
+    }
+
+    fn insert_keyed(&mut self, key_type: u8, key_data: &[u8], value: Vec<u8>) {
+        let mut key = vec![key_type];
+        key.extend_from_slice(key_data);
+        self.insert(key, value);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, Error> {
+        let mut map = PsbtMap::default();
+        loop {
+            let key = reader.read_var_bytes()?;
+            if key.is_empty() {
+                return Ok(map);
+            }
+            let value = reader.read_var_bytes()?;
+            if map.insert(key.to_vec(), value.to_vec()).is_some() {
+                return Err(Error::InvalidPsbt);
+            }
+        }
+    }
+
+    fn write(&self, buf: &mut Vec<u8>) {
+        for (key, value) in self.iter() {
+            write_var_bytes(buf, key);
+            write_var_bytes(buf, value);
+        }
+        buf.push(0x00);
+    }
+
+    /// Check the key data length of known key types, `keyed` lists types keyed by a public key
+    /// of the given lengths.
+    fn check_keys(&self, single: &[u8], keyed: &[(u8, &[usize])]) -> Result<(), Error> {
+        for (key, _) in self.iter() {
+            let key_data_len = key.len() - 1;
+            let valid = if single.contains(&key[0]) {
+                key_data_len == 0
+            } else {
+                keyed
+                    .iter()
+                    .filter(|(key_type, _)| *key_type == key[0])
+                    .all(|(_, lens)| lens.contains(&key_data_len))
+            };
+            if !valid {
+                return Err(Error::InvalidPsbt);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Partially signed bitcoin transaction, BIP-174 version 0 or BIP-370 version 2.
+///
+/// Parsed from and serialized to bytes, or base64 with [`FromStr`] and [`fmt::Display`].
+/// [`Psbt::sign`] adds the signatures of a key chain to the inputs it has derivations for.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Psbt {
+    pub global: PsbtMap,
+    pub inputs: Vec<PsbtMap>,
+    pub outputs: Vec<PsbtMap>,
+}
+
+impl Psbt {
+    /// Version 0 PSBT of a transaction without signatures.
+    pub fn from_unsigned_tx(tx: &Transaction) -> Result<Self, Error> {
+        if tx
+            .inputs
+            .iter()
+            .any(|input| !input.script_sig.is_empty() || !input.witness.is_empty())
+        {
+            return Err(Error::InvalidPsbt);
+        }
+        let mut global = PsbtMap::default();
+        global.insert(vec![PSBT_GLOBAL_UNSIGNED_TX], tx.serialize());
+        Ok(Psbt {
+            global,
+            inputs: vec![PsbtMap::default(); tx.inputs.len()],
+            outputs: vec![PsbtMap::default(); tx.outputs.len()],
+        })
+    }
+
+    /// `PSBT_GLOBAL_VERSION`, 0 if absent.
+    pub fn version(&self) -> Result<u32, Error> {
+        self.global
+            .get(&[PSBT_GLOBAL_VERSION])
+            .map_or(Ok(0), decode_u32)
+    }
+
+    /// Transaction to sign, with the lock time determined per BIP-370 for version 2.
+    pub fn unsigned_tx(&self) -> Result<Transaction, Error> {
+        if self.version()? == 0 {
+            let tx = self
+                .global
+                .get(&[PSBT_GLOBAL_UNSIGNED_TX])
+                .ok_or(Error::InvalidPsbt)?;
+            return Transaction::deserialize(tx).map_err(|_| Error::InvalidPsbt);
+        }
+        let version = self
+            .global
+            .get(&[PSBT_GLOBAL_TX_VERSION])
+            .ok_or(Error::InvalidPsbt)
+            .and_then(decode_u32)?;
+        let mut inputs = Vec::new();
+        for input in &self.inputs {
+            let mut txid = [0u8; 32];
+            match input.get(&[PSBT_IN_PREVIOUS_TXID]) {
+                Some(data) if data.len() == 32 => txid.copy_from_slice(data),
+                _ => return Err(Error::InvalidPsbt),
+            }
+            let vout = input
+                .get(&[PSBT_IN_OUTPUT_INDEX])
+                .ok_or(Error::InvalidPsbt)
+                .and_then(decode_u32)?;
+            inputs.push(TxIn {
+                previous_output: OutPoint { txid, vout },
+                script_sig: Vec::new(),
+                sequence: input
+                    .get(&[PSBT_IN_SEQUENCE])
+                    .map_or(Ok(0xffff_ffff), decode_u32)?,
+                witness: Vec::new(),
+            });
+        }
+        let mut outputs = Vec::new();
+        for output in &self.outputs {
+            let value = match output.get(&[PSBT_OUT_AMOUNT]) {
+                Some(data) if data.len() == 8 => {
+                    let mut buf = [0u8; 8];
+                    buf.copy_from_slice(data);
+                    u64::try_from(i64::from_le_bytes(buf)).map_err(|_| Error::InvalidPsbt)?
+                }
+                _ => return Err(Error::InvalidPsbt),
+            };
+            let script_pubkey = output.get(&[PSBT_OUT_SCRIPT]).ok_or(Error::InvalidPsbt)?;
+            outputs.push(TxOut {
+                value,
+                script_pubkey: script_pubkey.to_vec(),
+            });
+        }
+        Ok(Transaction {
+            version: version as i32,
+            inputs,
+            outputs,
+            lock_time: self.lock_time()?,
+        })
+    }
+
+    /// BIP-370 lock time, the maximum required lock time of the inputs if they agree on height
+    /// or time based, else the fallback lock time.
+    fn lock_time(&self) -> Result<u32, Error> {
+        let mut height = None;
+        let mut time = None;
+        let mut height_only = false;
+        let mut time_only = false;
+        for input in &self.inputs {
+            let input_height = input
+                .get(&[PSBT_IN_REQUIRED_HEIGHT_LOCKTIME])
+                .map(decode_u32)
+                .transpose()?;
+            let input_time = input
+                .get(&[PSBT_IN_REQUIRED_TIME_LOCKTIME])
+                .map(decode_u32)
+                .transpose()?;
+            height_only |= input_height.is_some() && input_time.is_none();
+            time_only |= input_time.is_some() && input_height.is_none();
+            height = height.max(input_height);
+            time = time.max(input_time);
+        }
+        match (height, time) {
+            (_, _) if height_only && time_only => Err(Error::InvalidPsbt),
+            (Some(height), _) if !time_only => Ok(height),
+            (_, Some(time)) => Ok(time),
+            _ => self
+                .global
+                .get(&[PSBT_GLOBAL_FALLBACK_LOCKTIME])
+                .map_or(Ok(0), decode_u32),
+        }
+    }
+
+    /// `PSBT_IN_BIP32_DERIVATION` entries of input `index`, [`Error::InvalidPsbt`] if there is
+    /// no such input.
+    pub fn input_derivations(&self, index: usize) -> Result<Vec<(PublicKey, KeyOrigin)>, Error> {
+        let input = self.inputs.get(index).ok_or(Error::InvalidPsbt)?;
+        derivations(input, PSBT_IN_BIP32_DERIVATION)
+    }
+
+    /// `PSBT_IN_TAP_BIP32_DERIVATION` entries of input `index`, each with the hashes of the
+    /// leaves the key is used in.
+    pub fn input_tap_derivations(&self, index: usize) -> Result<Vec<TapDerivation>, Error> {
+        let input = self.inputs.get(index).ok_or(Error::InvalidPsbt)?;
+        tap_derivations(input, PSBT_IN_TAP_BIP32_DERIVATION)
+    }
+
+    /// `PSBT_OUT_BIP32_DERIVATION` entries of output `index`, [`Error::InvalidPsbt`] if there is
+    /// no such output.
+    pub fn output_derivations(&self, index: usize) -> Result<Vec<(PublicKey, KeyOrigin)>, Error> {
+        let output = self.outputs.get(index).ok_or(Error::InvalidPsbt)?;
+        derivations(output, PSBT_OUT_BIP32_DERIVATION)
+    }
+
+    /// `PSBT_OUT_TAP_BIP32_DERIVATION` entries of output `index`.
+    pub fn output_tap_derivations(&self, index: usize) -> Result<Vec<TapDerivation>, Error> {
+        let output = self.outputs.get(index).ok_or(Error::InvalidPsbt)?;
+        tap_derivations(output, PSBT_OUT_TAP_BIP32_DERIVATION)
+    }
+
+    /// DER signature with the sighash byte of the compressed `public_key` in input `index`.
+    pub fn partial_sig(&self, index: usize, public_key: &PublicKey) -> Option<&[u8]> {
+        let mut key = vec![PSBT_IN_PARTIAL_SIG];
+        key.extend_from_slice(&public_key.serialize());
+        self.inputs.get(index)?.get(&key)
+    }
+
+    /// Schnorr signature of the taproot key path spend of input `index`.
+    pub fn tap_key_sig(&self, index: usize) -> Option<&[u8]> {
+        self.inputs.get(index)?.get(&[PSBT_IN_TAP_KEY_SIG])
+    }
+
+    /// Unsigned transaction, [`Error::InvalidPsbt`] unless there is a map for each of its inputs
+    /// and outputs, as the fields are public.
+    fn unsigned_tx_with_maps(&self) -> Result<Transaction, Error> {
+        let tx = self.unsigned_tx()?;
+        if self.inputs.len() != tx.inputs.len() || self.outputs.len() != tx.outputs.len() {
+            return Err(Error::InvalidPsbt);
+        }
+        Ok(tx)
+    }
+
+    /// Number of inputs and outputs given by the global map.
+    fn counts(&self) -> Result<(usize, usize), Error> {
+        if self.version()? == 0 {
+            let tx = self.unsigned_tx()?;
+            return Ok((tx.inputs.len(), tx.outputs.len()));
+        }
+        let count = |key_type| {
+            let data = self.global.get(&[key_type]).ok_or(Error::InvalidPsbt)?;
+            let mut reader = Reader::new(data);
+            let count = reader.read_compact_size()?;
+            if !reader.is_empty() {
+                return Err(Error::InvalidPsbt);
+            }
+            usize::try_from(count).map_err(|_| Error::InvalidPsbt)
+        };
+        Ok((
+            count(PSBT_GLOBAL_INPUT_COUNT)?,
+            count(PSBT_GLOBAL_OUTPUT_COUNT)?,
+        ))
+    }
+
+    /// Check the fields required by the version are present and the fields of other versions
+    /// are not, and the fields this crate interprets are well formed.
+    fn validate(&self) -> Result<(), Error> {
+        let is_v0 = match self.version()? {
+            0 => true,
+            2 => false,
+            _ => return Err(Error::InvalidPsbt),
+        };
+        let has = |map: &PsbtMap, key_type| map.get(&[key_type]).is_some();
+        let v0_only = has(&self.global, PSBT_GLOBAL_UNSIGNED_TX);
+        let v2_only = [
+            PSBT_GLOBAL_TX_VERSION,
+            PSBT_GLOBAL_FALLBACK_LOCKTIME,
+            PSBT_GLOBAL_INPUT_COUNT,
+            PSBT_GLOBAL_OUTPUT_COUNT,
+        ]
+        .iter()
+        .any(|key_type| has(&self.global, *key_type))
+            || self.inputs.iter().any(|input| {
+                [
+                    PSBT_IN_PREVIOUS_TXID,
+                    PSBT_IN_OUTPUT_INDEX,
+                    PSBT_IN_SEQUENCE,
+                    PSBT_IN_REQUIRED_TIME_LOCKTIME,
+                    PSBT_IN_REQUIRED_HEIGHT_LOCKTIME,
+                ]
+                .iter()
+                .any(|key_type| has(input, *key_type))
+            })
+            || self
+                .outputs
+                .iter()
+                .any(|output| has(output, PSBT_OUT_AMOUNT) || has(output, PSBT_OUT_SCRIPT));
+        if (is_v0 && v2_only) || (!is_v0 && v0_only) {
+            return Err(Error::InvalidPsbt);
+        }
+
+        self.global
+            .check_keys(GLOBAL_SINGLE_TYPES, &[(PSBT_GLOBAL_XPUB, &[78])])?;
+        for input in &self.inputs {
+            input.check_keys(
+                INPUT_SINGLE_TYPES,
+                &[
+                    (PSBT_IN_PARTIAL_SIG, &[33, 65]),
+                    (PSBT_IN_BIP32_DERIVATION, &[33, 65]),
+                    (PSBT_IN_TAP_BIP32_DERIVATION, &[32]),
+                ],
+            )?;
+        }
+        for output in &self.outputs {
+            output.check_keys(
+                OUTPUT_SINGLE_TYPES,
+                &[
+                    (PSBT_OUT_BIP32_DERIVATION, &[33, 65]),
+                    (PSBT_OUT_TAP_BIP32_DERIVATION, &[32]),
+                ],
+            )?;
+        }
+
+        let tx = self.unsigned_tx()?;
+        if tx
+            .inputs
+            .iter()
+            .any(|input| !input.script_sig.is_empty() || !input.witness.is_empty())
+        {
+            return Err(Error::InvalidPsbt);
+        }
+        for index in 0..self.inputs.len() {
+            self.input_derivations(index)?;
+            self.input_tap_derivations(index)?;
+        }
+        for index in 0..self.outputs.len() {
+            self.output_derivations(index)?;
+            self.output_tap_derivations(index)?;
+        }
+        Ok(())
+    }
+}
+
+fn decode_u32(data: &[u8]) -> Result<u32, Error> {
+    let mut buf = [0u8; 4];
+    if data.len() != buf.len() {
+        return Err(Error::InvalidPsbt);
+    }
+    buf.copy_from_slice(data);
+    Ok(u32::from_le_bytes(buf))
+}
+
+/// Decode a key source, the master key fingerprint followed by the path.
+fn decode_key_source(data: &[u8]) -> Result<KeyOrigin, Error> {
+    let mut chunks = data.chunks_exact(4);
+    let mut fingerprint = [0u8; 4];
+    match chunks.next() {
+        Some(data) if chunks.remainder().is_empty() => fingerprint.copy_from_slice(data),
+        _ => return Err(Error::InvalidPsbt),
+    }
+    let path = chunks
+        .map(|index| Ok(KeyIndex::from_index(decode_u32(index)?)?))
+        .collect::<Result<_, Error>>()?;
+    Ok(KeyOrigin { fingerprint, path })
+}
+
+fn encode_key_source(origin: &KeyOrigin) -> Vec<u8> {
+    let mut buf = origin.fingerprint.to_vec();
+    for index in &origin.path {
+        buf.extend_from_slice(&index.raw_index().to_le_bytes());
+    }
+    buf
+}
+
+fn derivations(map: &PsbtMap, key_type: u8) -> Result<Vec<(PublicKey, KeyOrigin)>, Error> {
+    map.entries(key_type)
+        .map(|(key, value)| {
+            let key = PublicKey::from_slice(key).map_err(|_| Error::InvalidPsbt)?;
+            Ok((key, decode_key_source(value)?))
+        })
+        .collect()
+}
+
+fn tap_derivations(map: &PsbtMap, key_type: u8) -> Result<Vec<TapDerivation>, Error> {
+    map.entries(key_type)
+        .map(|(key, value)| {
+            let key = XOnlyPublicKey::from_slice(key).map_err(|_| Error::InvalidPsbt)?;
+            let mut reader = Reader::new(value);
+            let mut leaf_hashes = Vec::new();
+            for _ in 0..reader.read_compact_size()? {
+                let mut leaf_hash = [0u8; 32];
+                leaf_hash.copy_from_slice(reader.read(32)?);
+                leaf_hashes.push(leaf_hash);
+            }
+            Ok((key, leaf_hashes, decode_key_source(reader.read_to_end())?))
+        })
+        .collect()
+}
+
+impl Serialize<Vec<u8>> for Psbt {
+    fn serialize(&self) -> Vec<u8> {
+        let mut buf = MAGIC.to_vec();
+        self.global.write(&mut buf);
+        for map in self.inputs.iter().chain(&self.outputs) {
+            map.write(&mut buf);
+        }
+        buf
+    }
+}
+
+impl Deserialize<&[u8], Error> for Psbt {
+    fn deserialize(data: &[u8]) -> Result<Self, Error> {
+        let data = data.strip_prefix(MAGIC).ok_or(Error::InvalidPsbt)?;
+        let mut reader = Reader::new(data);
+        let mut psbt = Psbt {
+            global: PsbtMap::read(&mut reader)?,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        };
+        let (input_count, output_count) = psbt.counts()?;
+        for _ in 0..input_count {
+            psbt.inputs.push(PsbtMap::read(&mut reader)?);
+        }
+        for _ in 0..output_count {
+            psbt.outputs.push(PsbtMap::read(&mut reader)?);
+        }
+        if !reader.is_empty() {
+            return Err(Error::InvalidPsbt);
+        }
+        psbt.validate()?;
+        Ok(psbt)
+    }
+}
+
+impl FromStr for Psbt {
+    type Err = Error;
+
+    /// Parse a base64 encoded PSBT.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Psbt::deserialize(base64::decode(s).ok_or(Error::InvalidPsbt)?.as_slice())
+    }
+}
+
+impl fmt::Display for Psbt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&base64::encode(&self.serialize()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{sighash, Address, AddressType, Network, PrivKey, TaprootPubKeyExt};
+    use hdwallet::{
+        secp256k1::schnorr::Signature, DefaultKeyChain, ExtendedPrivKey, ExtendedPubKey, KeyChain,
+    };
+
+    // BIP-174 signer test vector, signed with the keys of TPRV at m/0h/0h/0h to m/0h/0h/3h
+    const UNSIGNED: &str = "cHNidP8BAJoCAAAAAljoeiG1ba8MI76OcHBFbDNvfLqlyHV5JPVFiHuyq911AAAAAAD/////g40EJ9DsZQpoqka7CwmK6kQiwHGyyng1Kgd5WdB86h0BAAAAAP////8CcKrwCAAAAAAWABTYXCtx0AYLCcmIauuBXlCZHdoSTQDh9QUAAAAAFgAUAK6pouXw+HaliN9VRuh0LR2HAI8AAAAAAAEAuwIAAAABqtc5MQGL0l+ErkALaISL4J23BurCrBgpi6vucatlb4sAAAAASEcwRAIgWPb8fGoz4bMVSNSByCbAFb0wE1qtQs1neQ2rZtKtJDsCIEoc7SYExnNbY5PltBaR3XiwDwxZQvufdRhW+qk4FX26Af7///8CgPD6AgAAAAAXqRQPuUY0IWlrgsgzryQceMF9295JNIfQ8gonAQAAABepFCnKdPigj4GZlCgYXJe12FLkBj9hh2UAAAABBEdSIQKVg785rgpgl0etGZrd1jT6YQhVnWxc05tMIYPxq5bgfyEC2rYf9JoU22p9ArDNH7t4/EsYMStbTlTa5Nui+/71NtdSriIGApWDvzmuCmCXR60Zmt3WNPphCFWdbFzTm0whg/GrluB/ENkMak8AAACAAAAAgAAAAIAiBgLath/0mhTban0CsM0fu3j8SxgxK1tOVNrk26L7/vU21xDZDGpPAAAAgAAAAIABAACAAQMEAQAAAAABASAAwusLAAAAABepFLf1+vQOPUClpFmx2zU18rcvqSHohwEEIgAgjCNTFzdDtZXftKB7crqOQuN5fadOh/59nXSX47ICiQMBBUdSIQMIncEMesbbVPkTKa9hczPbOIzq0MIx9yM3nRuZAwsC3CECOt2QTz1tz1nduQaw3uI1Kbf/ue1Q5ehhUZJoYCIfDnNSriIGAjrdkE89bc9Z3bkGsN7iNSm3/7ntUOXoYVGSaGAiHw5zENkMak8AAACAAAAAgAMAAIAiBgMIncEMesbbVPkTKa9hczPbOIzq0MIx9yM3nRuZAwsC3BDZDGpPAAAAgAAAAIACAACAAQMEAQAAAAAiAgOppMN/WZbTqiXbrGtXCvBlA5RJKUJGCzVHU+2e7KWHcRDZDGpPAAAAgAAAAIAEAACAACICAn9jmXV9Lv9VoTatAsaEsYOLZVbl8bazQoKpS2tQBRCWENkMak8AAACAAAAAgAUAAIAA";
+    const TPRV: &str = "tprv8ZgxMBicQKsPd9TeAdPADNnSyH9SSUUbTVeFszDE23Ki6TBB5nCefAdHkK8Fm3qMQR6sHwA56zqRmKmxnHk37JkiFzvncDqoKmPWubu7hDF";
+    const SIGNATURES: [(usize, &str, &str); 4] = [
+        (0, "029583bf39ae0a609747ad199addd634fa6108559d6c5cd39b4c2183f1ab96e07f", "3044022074018ad4180097b873323c0015720b3684cc8123891048e7dbcd9b55ad679c99022073d369b740e3eb53dcefa33823c8070514ca55a7dd9544f157c167913261118c01"),
+        (0, "02dab61ff49a14db6a7d02b0cd1fbb78fc4b18312b5b4e54dae4dba2fbfef536d7", "30440220631a989fe738a92ad01986023312c19214fe2802b39e5cbc1ac3678806c692c3022039db6c387bd267716dfdb3d4d8da50b8e85d213326ba7c7daaa4c0ce41eb922301"),
+        (1, "03089dc10c7ac6db54f91329af617333db388cead0c231f723379d1b99030b02dc", "3044022062eb7a556107a7c73f45ac4ab5a1dddf6f7075fb1275969a7f383efff784bcb202200c05dbb7470dbf2f08557dd356c7325c1ed30913e996cd3840945db12228da5f01"),
+        (1, "023add904f3d6dcf59ddb906b0dee23529b7ffb9ed50e5e86151926860221f0e73", "3044022065f45ba5998b59a27ffe1a7bed016af1f1f90d54b3aa8f7450aa5f56a25103bd02207f724703ad1edb96680b284b56d4ffcb88f7fb759eabbe08aa30f29b851383d201"),
+    ];
+
+    fn key_chain() -> DefaultKeyChain {
+        DefaultKeyChain::new(
+            PrivKey::deserialize(TPRV.to_string())
+                .expect("deserialize")
+                .extended_key,
+        )
+    }
+
+    fn assert_signatures(psbt: &Psbt) {
+        for (index, public_key, signature) in &SIGNATURES {
+            let public_key = PublicKey::from_slice(&hex::decode(public_key).unwrap()).unwrap();
+            assert_eq!(
+                psbt.partial_sig(*index, &public_key).map(hex::encode),
+                Some(signature.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_bip174_signer() {
+        let mut psbt: Psbt = UNSIGNED.parse().expect("parse");
+        assert_eq!(psbt.to_string(), UNSIGNED);
+        assert_eq!(psbt.input_derivations(1).expect("derivations").len(), 2);
+        assert_eq!(
+            psbt.output_derivations(0).expect("derivations")[0]
+                .1
+                .to_string(),
+            "d90c6a4f/0h/0h/4h"
+        );
+        assert_eq!(psbt.sign(&key_chain()), Ok(4));
+        assert_signatures(&psbt);
+
+        // keys of another master key are skipped
+        let mut psbt: Psbt = UNSIGNED.parse().expect("parse");
+        let other = DefaultKeyChain::new(ExtendedPrivKey::with_seed(&[0u8; 32]).unwrap());
+        assert_eq!(psbt.sign(&other), Ok(0));
+        assert_eq!(psbt.to_string(), UNSIGNED);
+    }
+
+    #[test]
+    fn test_fill_output_derivations() {
+        let expected: Psbt = UNSIGNED.parse().expect("parse");
+        let mut psbt = expected.clone();
+        for output in &mut psbt.outputs {
+            *output = PsbtMap::default();
+        }
+        let filled = psbt.fill_output_derivations(
+            &key_chain(),
+            vec![
+                "m/0H/0H/4H".into(),
+                "m/0H/0H/5H".into(),
+                "m/0H/0H/6H".into(),
+            ],
+        );
+        assert_eq!(filled, Ok(2));
+        assert_eq!(psbt, expected);
+    }
+
+    #[test]
+    fn test_version_2() {
+        let v0: Psbt = UNSIGNED.parse().expect("parse");
+        let tx = v0.unsigned_tx().expect("tx");
+        let mut psbt = v0.clone();
+        psbt.global = PsbtMap::default();
+        psbt.global
+            .insert(vec![PSBT_GLOBAL_VERSION], 2u32.to_le_bytes().to_vec());
+        psbt.global.insert(
+            vec![PSBT_GLOBAL_TX_VERSION],
+            tx.version.to_le_bytes().to_vec(),
+        );
+        psbt.global.insert(vec![PSBT_GLOBAL_INPUT_COUNT], vec![2]);
+        psbt.global.insert(vec![PSBT_GLOBAL_OUTPUT_COUNT], vec![2]);
+        for (input, tx_input) in psbt.inputs.iter_mut().zip(&tx.inputs) {
+            let previous_output = tx_input.previous_output;
+            input.insert(vec![PSBT_IN_PREVIOUS_TXID], previous_output.txid.to_vec());
+            let vout = previous_output.vout.to_le_bytes().to_vec();
+            input.insert(vec![PSBT_IN_OUTPUT_INDEX], vout);
+        }
+        for (output, tx_output) in psbt.outputs.iter_mut().zip(&tx.outputs) {
+            let amount = tx_output.value.to_le_bytes().to_vec();
+            output.insert(vec![PSBT_OUT_AMOUNT], amount);
+            output.insert(vec![PSBT_OUT_SCRIPT], tx_output.script_pubkey.clone());
+        }
+        let mut psbt = Psbt::deserialize(psbt.serialize().as_slice()).expect("deserialize");
+        assert_eq!(psbt.unsigned_tx(), Ok(tx));
+        assert_eq!(psbt.sign(&key_chain()), Ok(4));
+        assert_signatures(&psbt);
+
+        // lock time
+        psbt.global.insert(
+            vec![PSBT_GLOBAL_FALLBACK_LOCKTIME],
+            7u32.to_le_bytes().to_vec(),
+        );
+        assert_eq!(psbt.unsigned_tx().map(|tx| tx.lock_time), Ok(7));
+        let height = 800_000u32.to_le_bytes().to_vec();
+        let time = 1_700_000_000u32.to_le_bytes().to_vec();
+        psbt.inputs[0].insert(vec![PSBT_IN_REQUIRED_HEIGHT_LOCKTIME], height.clone());
+        psbt.inputs[1].insert(vec![PSBT_IN_REQUIRED_HEIGHT_LOCKTIME], height);
+        psbt.inputs[1].insert(vec![PSBT_IN_REQUIRED_TIME_LOCKTIME], time.clone());
+        assert_eq!(psbt.unsigned_tx().map(|tx| tx.lock_time), Ok(800_000));
+        psbt.inputs[0].insert(vec![PSBT_IN_REQUIRED_TIME_LOCKTIME], time);
+        psbt.inputs[1].remove(&[PSBT_IN_REQUIRED_HEIGHT_LOCKTIME]);
+        assert_eq!(psbt.unsigned_tx().map(|tx| tx.lock_time), Ok(1_700_000_000));
+        psbt.inputs[0].remove(&[PSBT_IN_REQUIRED_TIME_LOCKTIME]);
+        assert_eq!(psbt.unsigned_tx(), Err(Error::InvalidPsbt));
+    }
+
+    #[test]
+    fn test_sign_taproot() {
+        // BIP-86 key of BIP-32 test vector 1
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let key_chain = DefaultKeyChain::new(ExtendedPrivKey::with_seed(&seed).unwrap());
+        let (key, _) = key_chain
+            .derive_private_key("m/86H/0H/0H/0/0".into())
+            .unwrap();
+        let (internal_key, _) = key.public_key().x_only_public_key();
+        let script_pubkey =
+            Address::from_public_key(&key.public_key(), AddressType::P2tr, Network::MainNet)
+                .unwrap()
+                .script_pubkey();
+        let tx = Transaction {
+            version: 2,
+            inputs: vec![TxIn {
+                previous_output: OutPoint {
+                    txid: [1; 32],
+                    vout: 0,
+                },
+                script_sig: Vec::new(),
+                sequence: 0xffff_fffd,
+                witness: Vec::new(),
+            }],
+            outputs: vec![TxOut {
+                value: 90_000,
+                script_pubkey: script_pubkey.clone(),
+            }],
+            lock_time: 0,
+        };
+        let spent_output = TxOut {
+            value: 100_000,
+            script_pubkey,
+        };
+        let mut witness_utxo = Vec::new();
+        spent_output.encode(&mut witness_utxo);
+        let mut psbt = Psbt::from_unsigned_tx(&tx).expect("psbt");
+        psbt.inputs[0].insert(vec![PSBT_IN_WITNESS_UTXO], witness_utxo);
+        let origin: KeyOrigin = "3442193e/86h/0h/0h/0/0".parse().unwrap();
+        let mut value = vec![0x00];
+        value.extend_from_slice(&encode_key_source(&origin));
+        psbt.inputs[0].insert_keyed(
+            PSBT_IN_TAP_BIP32_DERIVATION,
+            &internal_key.serialize(),
+            value,
+        );
+        let mut psbt = Psbt::deserialize(psbt.serialize().as_slice()).expect("deserialize");
+        assert_eq!(psbt.sign(&key_chain), Ok(1));
+
+        let signature = psbt.tap_key_sig(0).expect("signature");
+        assert_eq!(signature.len(), 64);
+        let hash = sighash::taproot_key_path(&tx, 0, &[spent_output], 0).unwrap();
+        ExtendedPubKey::from_private_key(&key)
+            .verify_taproot(&hash, &Signature::from_slice(signature).unwrap(), None)
+            .expect("valid signature");
+
+        // the output pays back to the same key
+        psbt.outputs[0] = PsbtMap::default();
+        assert_eq!(
+            psbt.fill_output_derivations(&key_chain, vec!["m/86H/0H/0H/0/0".into()]),
+            Ok(1)
+        );
+        assert_eq!(
+            psbt.output_tap_derivations(0),
+            Ok(vec![(internal_key, Vec::new(), origin)])
+        );
+
+        // maps missing from the public fields are invalid rather than a panic
+        assert_eq!(psbt.output_derivations(1), Err(Error::InvalidPsbt));
+        assert_eq!(psbt.tap_key_sig(1), None);
+        psbt.outputs.clear();
+        assert_eq!(
+            psbt.fill_output_derivations(&key_chain, vec!["m/86H/0H/0H/0/0".into()]),
+            Err(Error::InvalidPsbt)
+        );
+        psbt.inputs.clear();
+        assert_eq!(psbt.sign(&key_chain), Err(Error::InvalidPsbt));
+    }
+
+    #[test]
+    fn test_invalid_psbt() {
+        let data = base64::decode(UNSIGNED).unwrap();
+        let mut bad_magic = data.clone();
+        bad_magic[4] = 0;
+        let mut truncated = data.clone();
+        truncated.pop();
+        let mut trailing = data.clone();
+        trailing.push(0);
+        for data in &[bad_magic, trailing] {
+            assert_eq!(Psbt::deserialize(data.as_slice()), Err(Error::InvalidPsbt));
+        }
+        assert_eq!(
+            Psbt::deserialize(truncated.as_slice()),
+            Err(Error::InvalidLength)
+        );
+
+        let psbt: Psbt = UNSIGNED.parse().unwrap();
+        let invalid = |f: &dyn Fn(&mut Psbt)| {
+            let mut psbt = psbt.clone();
+            f(&mut psbt);
+            Psbt::deserialize(psbt.serialize().as_slice())
+        };
+        // no unsigned transaction
+        assert_eq!(
+            invalid(&|psbt| {
+                psbt.global.remove(&[PSBT_GLOBAL_UNSIGNED_TX]);
+            }),
+            Err(Error::InvalidPsbt)
+        );
+        // version 2 field in version 0
+        assert_eq!(
+            invalid(&|psbt| {
+                psbt.outputs[0].insert(vec![PSBT_OUT_AMOUNT], vec![0; 8]);
+            }),
+            Err(Error::InvalidPsbt)
+        );
+        // unsupported version
+        assert_eq!(
+            invalid(&|psbt| {
+                psbt.global
+                    .insert(vec![PSBT_GLOBAL_VERSION], vec![1, 0, 0, 0]);
+            }),
+            Err(Error::InvalidPsbt)
+        );
+        // key data on a single value type
+        assert_eq!(
+            invalid(&|psbt| {
+                psbt.inputs[0].insert(vec![PSBT_IN_SIGHASH_TYPE, 0], vec![1, 0, 0, 0]);
+            }),
+            Err(Error::InvalidPsbt)
+        );
+        // derivation of an invalid key
+        assert_eq!(
+            invalid(&|psbt| {
+                psbt.inputs[0].insert_keyed(PSBT_IN_BIP32_DERIVATION, &[4; 33], vec![0; 4]);
+            }),
+            Err(Error::InvalidPsbt)
+        );
+        // unsigned transaction with a signature
+        let mut tx = psbt.unsigned_tx().unwrap();
+        tx.inputs[0].script_sig = vec![0x00];
+        assert_eq!(Psbt::from_unsigned_tx(&tx), Err(Error::InvalidPsbt));
+
+        // non-witness UTXO of another transaction
+        let mut psbt = psbt;
+        let previous_tx = psbt.inputs[0].get(&[PSBT_IN_NON_WITNESS_UTXO]).unwrap();
+        let mut previous_tx = Transaction::deserialize(previous_tx).unwrap();
+        previous_tx.lock_time += 1;
+        psbt.inputs[0].insert(vec![PSBT_IN_NON_WITNESS_UTXO], previous_tx.serialize());
+        assert_eq!(psbt.sign(&key_chain()), Err(Error::InvalidPsbt));
+    }
+}
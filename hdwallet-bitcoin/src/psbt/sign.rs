@@ -0,0 +1,338 @@
+use super::*;
+use crate::{
+    hash::{hash160, sha256},
+    secp256k1_sign_only,
+    serialize::fingerprint,
+    sighash::{self, SIGHASH_ALL, SIGHASH_ANYONECANPAY, SIGHASH_DEFAULT},
+    transaction::write_compact_size,
+    Address, AddressType, Network, TaprootPrivKeyExt,
+};
+use hdwallet::{secp256k1::Message, ChainPath, ChainPathError, KeyChain, SubPath};
+
+impl Psbt {
+    /// Sign the inputs with the keys of `key_chain`, returns the number of signatures added.
+    ///
+    /// `PSBT_IN_BIP32_DERIVATION` and `PSBT_IN_TAP_BIP32_DERIVATION` entries with the master key
+    /// fingerprint of `key_chain` are derived, and the key signs when it matches the entry.
+    /// Segwit v0 and legacy inputs get an ECDSA partial signature, taproot inputs a Schnorr key
+    /// path signature; script path and finalized inputs are skipped.
+    pub fn sign(&mut self, key_chain: &impl KeyChain) -> Result<usize, Error> {
+        let master_fingerprint = master_fingerprint(key_chain)?;
+        let tx = self.unsigned_tx_with_maps()?;
+        let spent_outputs = (0..tx.inputs.len())
+            .map(|index| self.spent_output(&tx, index))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut count = 0;
+        for (index, spent_output) in spent_outputs.iter().enumerate() {
+            let input = &self.inputs[index];
+            let finalized = input.get(&[PSBT_IN_FINAL_SCRIPTSIG]).is_some()
+                || input.get(&[PSBT_IN_FINAL_SCRIPTWITNESS]).is_some();
+            let spent_output = match spent_output {
+                Some(spent_output) if !finalized => spent_output,
+                _ => continue,
+            };
+            if is_p2tr(&spent_output.script_pubkey) {
+                let prevouts = spent_outputs
+                    .iter()
+                    .cloned()
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or(Error::InvalidPsbt)?;
+                count += self.sign_taproot(key_chain, master_fingerprint, &tx, index, &prevouts)?;
+            } else {
+                count +=
+                    self.sign_ecdsa(key_chain, master_fingerprint, &tx, index, spent_output)?;
+            }
+        }
+        Ok(count)
+    }
+
+    /// Add derivations to the outputs paying to keys of `key_chain` at `chain_paths`, so that
+    /// signers recognize the change, returns the number of outputs filled.
+    ///
+    /// P2PKH, P2SH-P2WPKH, P2WPKH and BIP-86 P2TR outputs are recognized. P2SH-P2WPKH outputs
+    /// also get their redeem script and P2TR outputs their internal key.
+    pub fn fill_output_derivations<'a>(
+        &mut self,
+        key_chain: &impl KeyChain,
+        chain_paths: impl IntoIterator<Item = ChainPath<'a>>,
+    ) -> Result<usize, Error> {
+        let master_fingerprint = master_fingerprint(key_chain)?;
+        let tx = self.unsigned_tx_with_maps()?;
+        let mut filled = vec![false; tx.outputs.len()];
+        for chain_path in chain_paths {
+            let origin = KeyOrigin {
+                fingerprint: master_fingerprint,
+                path: key_path(&chain_path)?,
+            };
+            let (key, _) = key_chain.derive_private_key(chain_path)?;
+            let public_key = key.public_key();
+            let key_source = encode_key_source(&origin);
+            let scripts: Vec<_> = [
+                AddressType::P2pkh,
+                AddressType::P2shP2wpkh,
+                AddressType::P2wpkh,
+                AddressType::P2tr,
+            ]
+            .into_iter()
+            .map(|address_type| {
+                let address =
+                    Address::from_public_key(&public_key, address_type, Network::MainNet)?;
+                Ok((address_type, address.script_pubkey()))
+            })
+            .collect::<Result<_, Error>>()?;
+            for (index, output) in tx.outputs.iter().enumerate() {
+                let map = &mut self.outputs[index];
+                let address_type = scripts
+                    .iter()
+                    .find(|(_, script)| *script == output.script_pubkey)
+                    .map(|(address_type, _)| *address_type);
+                match address_type {
+                    Some(AddressType::P2tr) => {
+                        let (internal_key, _) = public_key.x_only_public_key();
+                        let mut value = Vec::new();
+                        write_compact_size(&mut value, 0);
+                        value.extend_from_slice(&key_source);
+                        map.insert(
+                            vec![PSBT_OUT_TAP_INTERNAL_KEY],
+                            internal_key.serialize().to_vec(),
+                        );
+                        map.insert_keyed(
+                            PSBT_OUT_TAP_BIP32_DERIVATION,
+                            &internal_key.serialize(),
+                            value,
+                        );
+                    }
+                    Some(address_type) => {
+                        if address_type == AddressType::P2shP2wpkh {
+                            map.insert(vec![PSBT_OUT_REDEEM_SCRIPT], p2wpkh_script(&public_key));
+                        }
+                        map.insert_keyed(
+                            PSBT_OUT_BIP32_DERIVATION,
+                            &public_key.serialize(),
+                            key_source.clone(),
+                        );
+                    }
+                    None => continue,
+                }
+                filled[index] = true;
+            }
+        }
+        Ok(filled.into_iter().filter(|filled| *filled).count())
+    }
+
+    /// Output spent by input `index`, taken from `PSBT_IN_NON_WITNESS_UTXO` after checking its
+    /// txid, or else from `PSBT_IN_WITNESS_UTXO`.
+    fn spent_output(&self, tx: &Transaction, index: usize) -> Result<Option<TxOut>, Error> {
+        let input = &self.inputs[index];
+        let previous_output = tx.inputs[index].previous_output;
+        if let Some(data) = input.get(&[PSBT_IN_NON_WITNESS_UTXO]) {
+            let previous_tx = Transaction::deserialize(data).map_err(|_| Error::InvalidPsbt)?;
+            if previous_tx.txid() != previous_output.txid {
+                return Err(Error::InvalidPsbt);
+            }
+            return previous_tx
+                .outputs
+                .get(previous_output.vout as usize)
+                .cloned()
+                .map(Some)
+                .ok_or(Error::InvalidPsbt);
+        }
+        match input.get(&[PSBT_IN_WITNESS_UTXO]) {
+            Some(data) => {
+                let mut reader = Reader::new(data);
+                let spent_output = TxOut {
+                    value: reader.read_u64()?,
+                    script_pubkey: reader.read_var_bytes()?.to_vec(),
+                };
+                if !reader.is_empty() {
+                    return Err(Error::InvalidPsbt);
+                }
+                Ok(Some(spent_output))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn sighash_type(&self, index: usize) -> Result<Option<u32>, Error> {
+        self.inputs[index]
+            .get(&[PSBT_IN_SIGHASH_TYPE])
+            .map(decode_u32)
+            .transpose()
+    }
+
+    fn sign_ecdsa(
+        &mut self,
+        key_chain: &impl KeyChain,
+        master_fingerprint: [u8; 4],
+        tx: &Transaction,
+        index: usize,
+        spent_output: &TxOut,
+    ) -> Result<usize, Error> {
+        let sighash_type = self.sighash_type(index)?.unwrap_or(SIGHASH_ALL);
+        if !matches!(sighash_type & !SIGHASH_ANYONECANPAY, 0x01..=0x03) {
+            return Err(Error::InvalidPsbt);
+        }
+        let input = &self.inputs[index];
+        let mut script = spent_output.script_pubkey.as_slice();
+        if is_p2sh(script) {
+            let redeem_script = input
+                .get(&[PSBT_IN_REDEEM_SCRIPT])
+                .ok_or(Error::InvalidPsbt)?;
+            if hash160(redeem_script) != script[2..22] {
+                return Err(Error::InvalidPsbt);
+            }
+            script = redeem_script;
+        }
+        let hash = match witness_program(script) {
+            Some((0, key_hash)) if key_hash.len() == 20 => {
+                // P2WPKH signs the P2PKH script of the key hash
+                let mut script_code = vec![0x76, 0xa9, 0x14];
+                script_code.extend_from_slice(key_hash);
+                script_code.extend_from_slice(&[0x88, 0xac]);
+                sighash::segwit_v0(tx, index, &script_code, spent_output.value, sighash_type)
+            }
+            Some((0, script_hash)) if script_hash.len() == 32 => {
+                let witness_script = input
+                    .get(&[PSBT_IN_WITNESS_SCRIPT])
+                    .ok_or(Error::InvalidPsbt)?;
+                if sha256(witness_script) != script_hash {
+                    return Err(Error::InvalidPsbt);
+                }
+                sighash::segwit_v0(tx, index, witness_script, spent_output.value, sighash_type)
+            }
+            // nested taproot or unknown witness program
+            Some(_) => return Ok(0),
+            None => {
+                // the spent output of legacy inputs must be verifiable
+                if input.get(&[PSBT_IN_NON_WITNESS_UTXO]).is_none() {
+                    return Err(Error::InvalidPsbt);
+                }
+                sighash::legacy(tx, index, script, sighash_type)
+            }
+        };
+        let message = Message::from_slice(&hash)?;
+
+        let mut count = 0;
+        for (public_key, origin) in self.input_derivations(index)? {
+            if origin.fingerprint != master_fingerprint {
+                continue;
+            }
+            let (key, _) = key_chain.derive_private_key(chain_path(&origin.path))?;
+            if key.public_key() != public_key {
+                continue;
+            }
+            let signature = secp256k1_sign_only().sign_ecdsa_low_r(&message, &key.private_key());
+            let mut value = signature.serialize_der().to_vec();
+            value.push(sighash_type as u8);
+            self.inputs[index].insert_keyed(PSBT_IN_PARTIAL_SIG, &public_key.serialize(), value);
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    fn sign_taproot(
+        &mut self,
+        key_chain: &impl KeyChain,
+        master_fingerprint: [u8; 4],
+        tx: &Transaction,
+        index: usize,
+        prevouts: &[TxOut],
+    ) -> Result<usize, Error> {
+        let sighash_type = self.sighash_type(index)?.unwrap_or(SIGHASH_DEFAULT);
+        let hash = sighash::taproot_key_path(tx, index, prevouts, sighash_type)
+            .map_err(|_| Error::InvalidPsbt)?;
+        let message = Message::from_slice(&hash)?;
+        let input = &self.inputs[index];
+        let internal_key = input.get(&[PSBT_IN_TAP_INTERNAL_KEY]);
+        let merkle_root = match input.get(&[PSBT_IN_TAP_MERKLE_ROOT]) {
+            Some(merkle_root) => {
+                Some(<[u8; 32]>::try_from(merkle_root).map_err(|_| Error::InvalidPsbt)?)
+            }
+            None => None,
+        };
+        let output_key = &prevouts[index].script_pubkey[2..];
+
+        for (public_key, leaf_hashes, origin) in self.input_tap_derivations(index)? {
+            // keys of script path spends are listed with the leaves they are used in
+            let internal_key_matches = internal_key
+                .iter()
+                .all(|key| *key == public_key.serialize());
+            if origin.fingerprint != master_fingerprint
+                || !leaf_hashes.is_empty()
+                || !internal_key_matches
+            {
+                continue;
+            }
+            let (key, _) = key_chain.derive_private_key(chain_path(&origin.path))?;
+            if key.x_only_public_key() != public_key {
+                continue;
+            }
+            let key_pair = key.tap_tweak(merkle_root.as_ref())?;
+            if key_pair.x_only_public_key().0.serialize() != output_key {
+                continue;
+            }
+            let signature = secp256k1_sign_only().sign_schnorr_no_aux_rand(&message, &key_pair);
+            let mut value = signature.as_ref().to_vec();
+            if sighash_type != SIGHASH_DEFAULT {
+                value.push(sighash_type as u8);
+            }
+            self.inputs[index].insert(vec![PSBT_IN_TAP_KEY_SIG], value);
+            return Ok(1);
+        }
+        Ok(0)
+    }
+}
+
+fn master_fingerprint(key_chain: &impl KeyChain) -> Result<[u8; 4], Error> {
+    let (master_key, _) = key_chain.derive_private_key("m".into())?;
+    Ok(fingerprint(&master_key.public_key()))
+}
+
+fn key_path(chain_path: &ChainPath) -> Result<Vec<KeyIndex>, Error> {
+    let mut iter = chain_path.iter();
+    // chain_path must start with root
+    if iter.next() != Some(Ok(SubPath::Root)) {
+        return Err(ChainPathError::Invalid.into());
+    }
+    iter.map(|sub_path| match sub_path? {
+        SubPath::Child(key_index) => Ok(key_index),
+        SubPath::Root => Err(ChainPathError::Invalid.into()),
+    })
+    .collect()
+}
+
+fn chain_path(path: &[KeyIndex]) -> ChainPath<'static> {
+    let mut s = String::from("m");
+    for index in path {
+        match index {
+            KeyIndex::Normal(i) => s.push_str(&format!("/{}", i)),
+            KeyIndex::Hardened(_) => s.push_str(&format!("/{}H", index.normalize_index())),
+        }
+    }
+    s.into()
+}
+
+fn p2wpkh_script(public_key: &PublicKey) -> Vec<u8> {
+    let mut script = vec![0x00, 0x14];
+    script.extend_from_slice(&hash160(&public_key.serialize()));
+    script
+}
+
+fn is_p2sh(script: &[u8]) -> bool {
+    script.len() == 23 && script[..2] == [0xa9, 0x14] && script[22] == 0x87
+}
+
+fn is_p2tr(script: &[u8]) -> bool {
+    matches!(witness_program(script), Some((1, program)) if program.len() == 32)
+}
+
+/// Witness version and program of a segwit `script_pubkey`.
+fn witness_program(script: &[u8]) -> Option<(u8, &[u8])> {
+    let (version, push, program) = match script {
+        [0x00, push, program @ ..] => (0, push, program),
+        [op @ 0x51..=0x60, push, program @ ..] => (op - 0x50, push, program),
+        _ => return None,
+    };
+    let valid = (2..=40).contains(&program.len()) && usize::from(*push) == program.len();
+    valid.then_some((version, program))
+}
@@ -1,12 +1,13 @@
-use crate::{Error, Network, PrivKey, PubKey};
+use crate::{
+    hash::{hash160, sha256d},
+    AddressType, Error, Network, PrivKey, PubKey,
+};
 use base58::{FromBase58, ToBase58};
-use hdwallet::ring::digest;
 use hdwallet::{
     secp256k1::{PublicKey, SecretKey},
     traits::{Deserialize, Serialize},
     Derivation, ExtendedPrivKey, ExtendedPubKey, KeyIndex,
 };
-use ripemd::{Digest, Ripemd160};
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 enum KeyType {
@@ -14,77 +15,134 @@ enum KeyType {
     PubKey,
 }
 
+/// Script type of the addresses derived from an extended key, encoded in its SLIP-132 version.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Default)]
+pub enum ScriptType {
+    /// `xpub`/`tpub`, P2PKH or P2SH.
+    #[default]
+    Legacy,
+    /// `ypub`/`upub`, P2WPKH nested in P2SH.
+    P2shP2wpkh,
+    /// `Ypub`/`Upub`, multisig P2WSH nested in P2SH.
+    P2shP2wsh,
+    /// `zpub`/`vpub`, P2WPKH.
+    P2wpkh,
+    /// `Zpub`/`Vpub`, multisig P2WSH.
+    P2wsh,
+}
+
+impl ScriptType {
+    /// Single-key address type of the derived keys, `None` for multisig script types.
+    pub fn address_type(self) -> Option<AddressType> {
+        match self {
+            ScriptType::Legacy => Some(AddressType::P2pkh),
+            ScriptType::P2shP2wpkh => Some(AddressType::P2shP2wpkh),
+            ScriptType::P2wpkh => Some(AddressType::P2wpkh),
+            ScriptType::P2shP2wsh | ScriptType::P2wsh => None,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 struct Version {
     network: Network,
+    script_type: ScriptType,
     key_type: KeyType,
 }
 
+/// SLIP-132 registered versions.
+const VERSIONS: [([u8; 4], Version); 20] = {
+    use self::{KeyType::*, Network::*, ScriptType::*};
+    const fn v(network: Network, script_type: ScriptType, key_type: KeyType) -> Version {
+        Version {
+            network,
+            script_type,
+            key_type,
+        }
+    }
+    [
+        ([0x04, 0x88, 0xad, 0xe4], v(MainNet, Legacy, PrivKey)),
+        ([0x04, 0x88, 0xb2, 0x1e], v(MainNet, Legacy, PubKey)),
+        ([0x04, 0x9d, 0x78, 0x78], v(MainNet, P2shP2wpkh, PrivKey)),
+        ([0x04, 0x9d, 0x7c, 0xb2], v(MainNet, P2shP2wpkh, PubKey)),
+        ([0x02, 0x95, 0xb0, 0x05], v(MainNet, P2shP2wsh, PrivKey)),
+        ([0x02, 0x95, 0xb4, 0x3f], v(MainNet, P2shP2wsh, PubKey)),
+        ([0x04, 0xb2, 0x43, 0x0c], v(MainNet, P2wpkh, PrivKey)),
+        ([0x04, 0xb2, 0x47, 0x46], v(MainNet, P2wpkh, PubKey)),
+        ([0x02, 0xaa, 0x7a, 0x99], v(MainNet, P2wsh, PrivKey)),
+        ([0x02, 0xaa, 0x7e, 0xd3], v(MainNet, P2wsh, PubKey)),
+        ([0x04, 0x35, 0x83, 0x94], v(TestNet, Legacy, PrivKey)),
+        ([0x04, 0x35, 0x87, 0xcf], v(TestNet, Legacy, PubKey)),
+        ([0x04, 0x4a, 0x4e, 0x28], v(TestNet, P2shP2wpkh, PrivKey)),
+        ([0x04, 0x4a, 0x52, 0x62], v(TestNet, P2shP2wpkh, PubKey)),
+        ([0x02, 0x42, 0x85, 0xb5], v(TestNet, P2shP2wsh, PrivKey)),
+        ([0x02, 0x42, 0x89, 0xef], v(TestNet, P2shP2wsh, PubKey)),
+        ([0x04, 0x5f, 0x18, 0xbc], v(TestNet, P2wpkh, PrivKey)),
+        ([0x04, 0x5f, 0x1c, 0xf6], v(TestNet, P2wpkh, PubKey)),
+        ([0x02, 0x57, 0x50, 0x48], v(TestNet, P2wsh, PrivKey)),
+        ([0x02, 0x57, 0x54, 0x83], v(TestNet, P2wsh, PubKey)),
+    ]
+};
+
 impl Version {
-    #[allow(dead_code)]
-    fn from_bytes(data: &[u8]) -> Result<Self, Error> {
-        let version = match hex::encode(data).to_uppercase().as_ref() {
-            "0488ADE4" => Version {
-                network: Network::MainNet,
-                key_type: KeyType::PrivKey,
-            },
-            "0488B21E" => Version {
-                network: Network::MainNet,
-                key_type: KeyType::PubKey,
-            },
-            "04358394" => Version {
-                network: Network::TestNet,
-                key_type: KeyType::PrivKey,
-            },
-            "043587CF" => Version {
-                network: Network::TestNet,
-                key_type: KeyType::PubKey,
-            },
-            _ => {
-                return Err(Error::UnknownVersion);
-            }
-        };
-        Ok(version)
+    /// Registered version of `key_type`, unregistered bytes are kept as an unknown network.
+    fn from_bytes(data: &[u8], key_type: KeyType) -> Result<Self, Error> {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(data);
+        match VERSIONS.iter().find(|(version, _)| *version == bytes) {
+            Some((_, version)) if version.key_type == key_type => Ok(*version),
+            // e.g. an xpub decoded as private key
+            Some(_) => Err(Error::UnknownVersion),
+            None => Ok(Version {
+                network: Network::Unknown(bytes),
+                script_type: ScriptType::Legacy,
+                key_type,
+            }),
+        }
     }
 
-    fn to_bytes(self) -> Vec<u8> {
-        let hex_str = match self.network {
-            Network::MainNet => match self.key_type {
-                KeyType::PrivKey => "0488ADE4",
-                KeyType::PubKey => "0488B21E",
-            },
-            Network::TestNet => match self.key_type {
-                KeyType::PrivKey => "04358394",
-                KeyType::PubKey => "043587CF",
-            },
+    fn to_bytes(self) -> [u8; 4] {
+        let params = match self.network {
+            Network::Unknown(version) => return version,
+            network => network.params().expect("known network"),
         };
-        hex::decode(hex_str).expect("bitcoin network")
+        let (xprv_version, xpub_version) = params.versions(self.script_type);
+        match self.key_type {
+            KeyType::PrivKey => xprv_version,
+            KeyType::PubKey => xpub_version,
+        }
     }
 }
 
-trait DerivationExt {
-    fn parent_fingerprint(&self) -> Vec<u8>;
+/// Key fingerprint, the first 32 bits of the key identifier.
+pub(crate) fn fingerprint(public_key: &PublicKey) -> [u8; 4] {
+    let mut fingerprint = [0u8; 4];
+    fingerprint.copy_from_slice(&hash160(&public_key.serialize())[0..4]);
+    fingerprint
 }
 
-impl DerivationExt for Derivation {
-    fn parent_fingerprint(&self) -> Vec<u8> {
-        match self.parent_key {
-            Some(ref key) => {
-                let pubkey = ExtendedPubKey::from_private_key(key);
-                let buf = digest::digest(&digest::SHA256, &pubkey.public_key.serialize());
-                let mut hasher = Ripemd160::new();
-                hasher.update(&buf.as_ref());
-                hasher.finalize()[0..4].to_vec()
-            }
-            None => vec![0; 4],
-        }
+/// Fingerprint of the parent key in `derivation`, or `parent_fingerprint` if the parent key is
+/// unknown, all zeros for master key.
+pub(crate) fn parent_fingerprint(
+    derivation: &Derivation,
+    parent_fingerprint: Option<[u8; 4]>,
+) -> [u8; 4] {
+    match (&derivation.parent_key, parent_fingerprint) {
+        (Some(key), _) => fingerprint(&key.public_key()),
+        (None, Some(parent_fingerprint)) => parent_fingerprint,
+        (None, None) => [0; 4],
     }
 }
 
-fn encode_derivation(buf: &mut Vec<u8>, version: Version, derivation: &Derivation) {
+fn encode_derivation(
+    buf: &mut Vec<u8>,
+    version: Version,
+    derivation: &Derivation,
+    parent_fingerprint: [u8; 4],
+) {
     buf.extend_from_slice(&version.to_bytes());
     buf.extend_from_slice(&derivation.depth.to_be_bytes());
-    buf.extend_from_slice(&derivation.parent_fingerprint());
+    buf.extend_from_slice(&parent_fingerprint);
     match derivation.key_index {
         Some(key_index) => {
             buf.extend_from_slice(&key_index.raw_index().to_be_bytes());
@@ -93,20 +151,23 @@ fn encode_derivation(buf: &mut Vec<u8>, version: Version, derivation: &Derivatio
     }
 }
 
-fn decode_derivation(buf: &[u8]) -> Result<(Version, Derivation), Error> {
-    let version = Version::from_bytes(&buf[0..4])?;
+/// Version, derivation and parent fingerprint of a serialized key.
+fn decode_derivation(
+    buf: &[u8],
+    key_type: KeyType,
+) -> Result<(Version, Derivation, Option<[u8; 4]>), Error> {
+    let version = Version::from_bytes(&buf[0..4], key_type)?;
     let depth = u8::from_be_bytes([buf[4]; 1]);
-    let parent_fingerprint = &buf[5..=8];
-    let key_index = {
-        // is master key
-        if parent_fingerprint == [0; 4] {
-            None
-        } else {
-            let mut key_index_buf = [0u8; 4];
-            key_index_buf.copy_from_slice(&buf[9..=12]);
-            let raw_index = u32::from_be_bytes(key_index_buf);
-            Some(KeyIndex::from(raw_index))
-        }
+    let mut parent_fingerprint = [0u8; 4];
+    parent_fingerprint.copy_from_slice(&buf[5..=8]);
+    // is master key
+    let (key_index, parent_fingerprint) = if parent_fingerprint == [0; 4] {
+        (None, None)
+    } else {
+        let mut key_index_buf = [0u8; 4];
+        key_index_buf.copy_from_slice(&buf[9..=12]);
+        let raw_index = u32::from_be_bytes(key_index_buf);
+        (Some(KeyIndex::from(raw_index)), Some(parent_fingerprint))
     };
     Ok((
         version,
@@ -115,25 +176,24 @@ fn decode_derivation(buf: &[u8]) -> Result<(Version, Derivation), Error> {
             parent_key: None,
             key_index,
         },
+        parent_fingerprint,
     ))
 }
 
-fn encode_checksum(buf: &mut Vec<u8>) {
-    let check_sum = {
-        let buf = digest::digest(&digest::SHA256, buf);
-        digest::digest(&digest::SHA256, buf.as_ref())
-    };
+pub(crate) fn encode_checksum(buf: &mut Vec<u8>) {
+    let check_sum = sha256d(buf);
 
-    buf.extend_from_slice(&check_sum.as_ref()[0..4]);
+    buf.extend_from_slice(&check_sum[0..4]);
 }
 
-fn verify_checksum(buf: &[u8]) -> Result<(), Error> {
-    let check_sum = {
-        let buf = digest::digest(&digest::SHA256, &buf[0..78]);
-        digest::digest(&digest::SHA256, buf.as_ref())
-    };
-    if check_sum.as_ref()[0..4] == buf[78..82] {
-        Ok(())
+/// Verify the base58check checksum, returns the payload without it.
+pub(crate) fn verify_checksum(buf: &[u8]) -> Result<&[u8], Error> {
+    if buf.len() < 4 {
+        return Err(Error::InvalidLength);
+    }
+    let (payload, check_sum) = buf.split_at(buf.len() - 4);
+    if sha256d(payload)[0..4] == *check_sum {
+        Ok(payload)
     } else {
         Err(Error::MisChecksum)
     }
@@ -146,13 +206,15 @@ impl Serialize<Vec<u8>> for PrivKey {
             &mut buf,
             Version {
                 network: self.network,
+                script_type: self.script_type,
                 key_type: KeyType::PrivKey,
             },
             &self.derivation,
+            self.parent_fingerprint(),
         );
-        buf.extend_from_slice(&self.extended_key.chain_code);
+        buf.extend_from_slice(&self.extended_key.chain_code());
         buf.extend_from_slice(&[0]);
-        buf.extend_from_slice(&self.extended_key.private_key[..]);
+        buf.extend_from_slice(&self.extended_key.private_key()[..]);
         assert_eq!(buf.len(), 78);
         encode_checksum(&mut buf);
         buf
@@ -172,9 +234,11 @@ impl Serialize<Vec<u8>> for PubKey {
             &mut buf,
             Version {
                 network: self.network,
+                script_type: self.script_type,
                 key_type: KeyType::PubKey,
             },
             &self.derivation,
+            self.parent_fingerprint(),
         );
         buf.extend_from_slice(&self.extended_key.chain_code);
         buf.extend_from_slice(&self.extended_key.public_key.serialize());
@@ -192,17 +256,23 @@ impl Serialize<String> for PubKey {
 
 impl Deserialize<Vec<u8>, Error> for PrivKey {
     fn deserialize(data: Vec<u8>) -> Result<PrivKey, Error> {
+        if data.len() != 82 {
+            return Err(Error::InvalidLength);
+        }
         verify_checksum(&data)?;
-        let (version, derivation) = decode_derivation(&data)?;
+        let (version, derivation, parent_fingerprint) = decode_derivation(&data, KeyType::PrivKey)?;
+        // public key data under an unknown version
+        if data[45] != 0 {
+            return Err(Error::UnknownVersion);
+        }
         let chain_code = data[13..45].to_vec();
         let private_key = SecretKey::from_slice(&data[46..78])?;
         Ok(PrivKey {
             network: version.network,
+            script_type: version.script_type,
             derivation,
-            extended_key: ExtendedPrivKey {
-                chain_code,
-                private_key,
-            },
+            parent_fingerprint,
+            extended_key: ExtendedPrivKey::new(private_key, chain_code),
         })
     }
 }
@@ -216,13 +286,18 @@ impl Deserialize<String, Error> for PrivKey {
 
 impl Deserialize<Vec<u8>, Error> for PubKey {
     fn deserialize(data: Vec<u8>) -> Result<PubKey, Error> {
+        if data.len() != 82 {
+            return Err(Error::InvalidLength);
+        }
         verify_checksum(&data)?;
-        let (version, derivation) = decode_derivation(&data)?;
+        let (version, derivation, parent_fingerprint) = decode_derivation(&data, KeyType::PubKey)?;
         let chain_code = data[13..45].to_vec();
         let public_key = PublicKey::from_slice(&data[45..78])?;
         Ok(PubKey {
             network: version.network,
+            script_type: version.script_type,
             derivation,
+            parent_fingerprint,
             extended_key: ExtendedPubKey {
                 chain_code,
                 public_key,
@@ -241,6 +316,7 @@ impl Deserialize<String, Error> for PubKey {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::NetworkParams;
     use hdwallet::{DefaultKeyChain, KeyChain};
 
     #[test]
@@ -252,7 +328,9 @@ mod tests {
             key_chain.derive_private_key("m".into()).expect("fetch key");
         let key = PrivKey {
             network: Network::MainNet,
+            script_type: ScriptType::Legacy,
             derivation,
+            parent_fingerprint: None,
             extended_key,
         };
         let serialized_key: String = key.serialize();
@@ -260,6 +338,43 @@ mod tests {
         assert_eq!(key, key2);
     }
 
+    #[test]
+    fn test_deserialize_child_key() {
+        // BIP-32 test vector 1, m/0H/1
+        for key in &[
+            "xprv9wTYmMFdV23N2TdNG573QoEsfRrWKQgWeibmLntzniatZvR9BmLnvSxqu53Kw1UmYPxLgboyZQaXwTCg8MSY3H2EU4pWcQDnRnrVA1xe8fs",
+            "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ",
+        ] {
+            let serialized_key = if key.starts_with("xprv") {
+                Serialize::<String>::serialize(&PrivKey::deserialize(key.to_string()).expect("deserialize"))
+            } else {
+                Serialize::<String>::serialize(&PubKey::deserialize(key.to_string()).expect("deserialize"))
+            };
+            assert_eq!(&serialized_key, key);
+        }
+        assert_eq!(
+            PubKey::deserialize(
+                "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29E".to_string()
+            ),
+            Err(Error::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn test_deserialize_wrong_key_type() {
+        // BIP-32 test vector 1, m/0H/1
+        let xprv = "xprv9wTYmMFdV23N2TdNG573QoEsfRrWKQgWeibmLntzniatZvR9BmLnvSxqu53Kw1UmYPxLgboyZQaXwTCg8MSY3H2EU4pWcQDnRnrVA1xe8fs";
+        let xpub = "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ";
+        assert_eq!(
+            PrivKey::deserialize(xpub.to_string()),
+            Err(Error::UnknownVersion)
+        );
+        assert_eq!(
+            PubKey::deserialize(xprv.to_string()),
+            Err(Error::UnknownVersion)
+        );
+    }
+
     #[test]
     fn test_deserialize_pub_key() {
         let mut rng = rand::thread_rng();
@@ -269,12 +384,157 @@ mod tests {
             key_chain.derive_private_key("m".into()).expect("fetch key");
         let key = PrivKey {
             network: Network::MainNet,
+            script_type: ScriptType::Legacy,
             derivation,
+            parent_fingerprint: None,
             extended_key,
         };
-        let key = PubKey::from_private_key(&key);
+        let key = PubKey::from_private_key(&key).expect("public key");
         let serialized_key: String = key.serialize();
         let key2 = PubKey::deserialize(serialized_key).expect("deserialize");
         assert_eq!(key, key2);
     }
+
+    #[test]
+    fn test_slip132_versions() {
+        // BIP-84 account m/84H/0H/0H of mnemonic `abandon abandon ... about`
+        let zprv = "zprvAdG4iTXWBoARxkkzNpNh8r6Qag3irQB8PzEMkAFeTRXxHpbF9z4QgEvBRmfvqWvGp42t42nvgGpNgYSJA9iefm1yYNZKEm7z6qUWCroSQnE";
+        let zpub = "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs";
+        let seed = hex::decode("5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc19a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4").expect("decode");
+        let account = PrivKey::from_master_key(
+            ExtendedPrivKey::with_seed(&seed).expect("master key"),
+            Network::MainNet,
+        )
+        .with_script_type(ScriptType::P2wpkh)
+        .derive_path("m/84H/0H/0H".into())
+        .expect("derive");
+        assert_eq!(Serialize::<String>::serialize(&account), zprv);
+        assert_eq!(
+            Serialize::<String>::serialize(
+                &PubKey::from_private_key(&account).expect("public key")
+            ),
+            zpub
+        );
+        let priv_key = PrivKey::deserialize(zprv.to_string()).expect("deserialize");
+        assert_eq!(priv_key.script_type, ScriptType::P2wpkh);
+        assert_eq!(priv_key.extended_key, account.extended_key);
+        let pub_key = PubKey::deserialize(zpub.to_string()).expect("deserialize");
+        assert_eq!(
+            pub_key.script_type.address_type(),
+            Some(AddressType::P2wpkh)
+        );
+
+        for (network, script_type, prv_prefix, pub_prefix) in &[
+            (Network::MainNet, ScriptType::Legacy, "xprv", "xpub"),
+            (Network::MainNet, ScriptType::P2shP2wpkh, "yprv", "ypub"),
+            (Network::MainNet, ScriptType::P2shP2wsh, "Yprv", "Ypub"),
+            (Network::MainNet, ScriptType::P2wpkh, "zprv", "zpub"),
+            (Network::MainNet, ScriptType::P2wsh, "Zprv", "Zpub"),
+            (Network::TestNet, ScriptType::Legacy, "tprv", "tpub"),
+            (Network::TestNet, ScriptType::P2shP2wpkh, "uprv", "upub"),
+            (Network::TestNet, ScriptType::P2shP2wsh, "Uprv", "Upub"),
+            (Network::TestNet, ScriptType::P2wpkh, "vprv", "vpub"),
+            (Network::TestNet, ScriptType::P2wsh, "Vprv", "Vpub"),
+        ] {
+            let key = PrivKey {
+                network: *network,
+                ..priv_key.clone().with_script_type(*script_type)
+            };
+            let encoded: String = key.serialize();
+            assert!(encoded.starts_with(prv_prefix), "{}", encoded);
+            let decoded = PrivKey::deserialize(encoded).expect("deserialize");
+            assert_eq!(decoded, key);
+            // conversion keeps the key data
+            assert_eq!(decoded.extended_key, priv_key.extended_key);
+            assert_eq!(decoded.derivation, priv_key.derivation);
+
+            let key = PubKey::from_private_key(&key).expect("public key");
+            let encoded: String = key.serialize();
+            assert!(encoded.starts_with(pub_prefix), "{}", encoded);
+            assert_eq!(PubKey::deserialize(encoded).expect("deserialize"), key);
+        }
+    }
+
+    #[test]
+    fn test_custom_and_unknown_versions() {
+        // BIP-32 test vector 1, m/0H/1
+        let xprv = "xprv9wTYmMFdV23N2TdNG573QoEsfRrWKQgWeibmLntzniatZvR9BmLnvSxqu53Kw1UmYPxLgboyZQaXwTCg8MSY3H2EU4pWcQDnRnrVA1xe8fs";
+        let key = PrivKey::deserialize(xprv.to_string()).expect("deserialize");
+        let litecoin = Network::Custom(NetworkParams {
+            xprv_version: [0x01, 0x9d, 0x9c, 0xfe],
+            xpub_version: [0x01, 0x9d, 0xa4, 0x62],
+            p2sh_p2wpkh_versions: ([0x01, 0xb2, 0x67, 0x92], [0x01, 0xb2, 0x6e, 0xf6]),
+            p2pkh_prefix: 0x30,
+            p2sh_prefix: 0x32,
+            bech32_hrp: "ltc",
+            wif_prefix: 0xb0,
+            ..NetworkParams::MAINNET
+        });
+        let key = PrivKey {
+            network: litecoin,
+            ..key
+        };
+        let ltpv: String = key.serialize();
+        assert!(ltpv.starts_with("Ltpv"), "{}", ltpv);
+        let ltub: String = PubKey::from_private_key(&key)
+            .expect("public key")
+            .serialize();
+        assert!(ltub.starts_with("Ltub"), "{}", ltub);
+        // custom networks keep the script type
+        let key = key.with_script_type(ScriptType::P2shP2wpkh);
+        let mtpv: String = key.serialize();
+        assert!(mtpv.starts_with("Mtpv"), "{}", mtpv);
+        let mtub: String = PubKey::from_private_key(&key)
+            .expect("public key")
+            .serialize();
+        assert!(mtub.starts_with("Mtub"), "{}", mtub);
+        let key = key.with_script_type(ScriptType::Legacy);
+
+        // unknown versions are kept, but have no public key version, addresses or WIF
+        let decoded = PrivKey::deserialize(ltpv.clone()).expect("deserialize");
+        assert_eq!(decoded.extended_key, key.extended_key);
+        assert_eq!(decoded.network, Network::Unknown([0x01, 0x9d, 0x9c, 0xfe]));
+        assert_eq!(Serialize::<String>::serialize(&decoded), ltpv);
+        assert_eq!(
+            PubKey::from_private_key(&decoded),
+            Err(Error::UnknownVersion)
+        );
+        assert_eq!(decoded.to_wif(), Err(Error::UnknownVersion));
+        let decoded = PubKey::deserialize(ltub.clone()).expect("deserialize");
+        assert_eq!(decoded.network, Network::Unknown([0x01, 0x9d, 0xa4, 0x62]));
+        assert_eq!(Serialize::<String>::serialize(&decoded), ltub);
+        assert_eq!(
+            decoded.address(AddressType::P2pkh),
+            Err(Error::UnknownVersion)
+        );
+        // until the network is set
+        let decoded = PubKey {
+            network: litecoin,
+            ..decoded
+        };
+        assert_eq!(Serialize::<String>::serialize(&decoded), ltub);
+        assert!(decoded
+            .address(AddressType::P2pkh)
+            .expect("address")
+            .to_string()
+            .starts_with('L'));
+        // the key data tells private and public keys apart
+        assert_eq!(
+            PrivKey::deserialize(ltub.clone()),
+            Err(Error::UnknownVersion)
+        );
+        assert!(PubKey::deserialize(ltpv).is_err());
+
+        // regtest and signet use the testnet versions
+        for network in &[Network::TestNet, Network::Regtest, Network::Signet] {
+            let key = PrivKey {
+                network: *network,
+                ..key.clone()
+            };
+            let tprv: String = key.serialize();
+            assert!(tprv.starts_with("tprv"), "{}", tprv);
+            let decoded = PrivKey::deserialize(tprv).expect("deserialize");
+            assert_eq!(decoded.network, Network::TestNet);
+        }
+    }
 }
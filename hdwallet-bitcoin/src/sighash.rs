@@ -0,0 +1,213 @@
+//! Signature hashes of transaction inputs.
+
+use crate::{
+    hash::{sha256, sha256d, tagged_hash},
+    transaction::{write_var_bytes, Transaction, TxOut},
+    Error,
+};
+
+/// Taproot only, signs as [`SIGHASH_ALL`] and the signature omits the sighash byte.
+pub(crate) const SIGHASH_DEFAULT: u32 = 0x00;
+pub(crate) const SIGHASH_ALL: u32 = 0x01;
+pub(crate) const SIGHASH_NONE: u32 = 0x02;
+pub(crate) const SIGHASH_SINGLE: u32 = 0x03;
+pub(crate) const SIGHASH_ANYONECANPAY: u32 = 0x80;
+
+/// Pre-segwit signature hash, `script_code` is the scriptPubKey or redeem script.
+///
+/// `SIGHASH_SINGLE` without an output at `input_index` signs the hash `1`, as consensus does.
+pub(crate) fn legacy(
+    tx: &Transaction,
+    input_index: usize,
+    script_code: &[u8],
+    sighash_type: u32,
+) -> [u8; 32] {
+    let base_type = sighash_type & 0x1f;
+    if base_type == SIGHASH_SINGLE && input_index >= tx.outputs.len() {
+        let mut one = [0u8; 32];
+        one[0] = 1;
+        return one;
+    }
+    let mut tx = tx.clone();
+    for (i, input) in tx.inputs.iter_mut().enumerate() {
+        input.witness.clear();
+        input.script_sig = if i == input_index {
+            script_code.to_vec()
+        } else {
+            Vec::new()
+        };
+        if i != input_index && (base_type == SIGHASH_NONE || base_type == SIGHASH_SINGLE) {
+            input.sequence = 0;
+        }
+    }
+    if base_type == SIGHASH_NONE {
+        tx.outputs.clear();
+    } else if base_type == SIGHASH_SINGLE {
+        tx.outputs.truncate(input_index + 1);
+        for output in &mut tx.outputs[..input_index] {
+            output.value = u64::MAX;
+            output.script_pubkey.clear();
+        }
+    }
+    if sighash_type & SIGHASH_ANYONECANPAY != 0 {
+        tx.inputs = vec![tx.inputs.swap_remove(input_index)];
+    }
+    let mut buf = Vec::new();
+    tx.encode(&mut buf, false);
+    buf.extend_from_slice(&sighash_type.to_le_bytes());
+    sha256d(&buf)
+}
+
+/// BIP-143 signature hash of a segwit v0 input spending `value` satoshis.
+///
+/// `script_code` is the witness script, or the P2PKH script of the key hash for P2WPKH.
+pub(crate) fn segwit_v0(
+    tx: &Transaction,
+    input_index: usize,
+    script_code: &[u8],
+    value: u64,
+    sighash_type: u32,
+) -> [u8; 32] {
+    let base_type = sighash_type & 0x1f;
+    let anyone_can_pay = sighash_type & SIGHASH_ANYONECANPAY != 0;
+    let mut hash_prevouts = [0u8; 32];
+    let mut hash_sequence = [0u8; 32];
+    let mut hash_outputs = [0u8; 32];
+    if !anyone_can_pay {
+        let mut buf = Vec::new();
+        for input in &tx.inputs {
+            input.previous_output.encode(&mut buf);
+        }
+        hash_prevouts = sha256d(&buf);
+    }
+    if !anyone_can_pay && base_type != SIGHASH_SINGLE && base_type != SIGHASH_NONE {
+        let mut buf = Vec::new();
+        for input in &tx.inputs {
+            buf.extend_from_slice(&input.sequence.to_le_bytes());
+        }
+        hash_sequence = sha256d(&buf);
+    }
+    if base_type != SIGHASH_SINGLE && base_type != SIGHASH_NONE {
+        let mut buf = Vec::new();
+        for output in &tx.outputs {
+            output.encode(&mut buf);
+        }
+        hash_outputs = sha256d(&buf);
+    } else if base_type == SIGHASH_SINGLE && input_index < tx.outputs.len() {
+        let mut buf = Vec::new();
+        tx.outputs[input_index].encode(&mut buf);
+        hash_outputs = sha256d(&buf);
+    }
+
+    let input = &tx.inputs[input_index];
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&tx.version.to_le_bytes());
+    buf.extend_from_slice(&hash_prevouts);
+    buf.extend_from_slice(&hash_sequence);
+    input.previous_output.encode(&mut buf);
+    write_var_bytes(&mut buf, script_code);
+    buf.extend_from_slice(&value.to_le_bytes());
+    buf.extend_from_slice(&input.sequence.to_le_bytes());
+    buf.extend_from_slice(&hash_outputs);
+    buf.extend_from_slice(&tx.lock_time.to_le_bytes());
+    buf.extend_from_slice(&sighash_type.to_le_bytes());
+    sha256d(&buf)
+}
+
+/// BIP-341 signature hash of a taproot key path spend, `prevouts` are the outputs spent by all
+/// inputs.
+///
+/// Undefined sighash types and `SIGHASH_SINGLE` without an output at `input_index` are
+/// [`Error::InvalidTransaction`].
+pub(crate) fn taproot_key_path(
+    tx: &Transaction,
+    input_index: usize,
+    prevouts: &[TxOut],
+    sighash_type: u32,
+) -> Result<[u8; 32], Error> {
+    let base_type = sighash_type & 0x1f;
+    let anyone_can_pay = sighash_type & SIGHASH_ANYONECANPAY != 0;
+    if !matches!(sighash_type, 0x00..=0x03 | 0x81..=0x83)
+        || prevouts.len() != tx.inputs.len()
+        || (base_type == SIGHASH_SINGLE && input_index >= tx.outputs.len())
+    {
+        return Err(Error::InvalidTransaction);
+    }
+    let mut buf = vec![0x00, sighash_type as u8];
+    buf.extend_from_slice(&tx.version.to_le_bytes());
+    buf.extend_from_slice(&tx.lock_time.to_le_bytes());
+    if !anyone_can_pay {
+        let mut outpoints = Vec::new();
+        let mut amounts = Vec::new();
+        let mut script_pubkeys = Vec::new();
+        let mut sequences = Vec::new();
+        for (input, prevout) in tx.inputs.iter().zip(prevouts) {
+            input.previous_output.encode(&mut outpoints);
+            amounts.extend_from_slice(&prevout.value.to_le_bytes());
+            write_var_bytes(&mut script_pubkeys, &prevout.script_pubkey);
+            sequences.extend_from_slice(&input.sequence.to_le_bytes());
+        }
+        buf.extend_from_slice(&sha256(&outpoints));
+        buf.extend_from_slice(&sha256(&amounts));
+        buf.extend_from_slice(&sha256(&script_pubkeys));
+        buf.extend_from_slice(&sha256(&sequences));
+    }
+    if base_type != SIGHASH_NONE && base_type != SIGHASH_SINGLE {
+        let mut outputs = Vec::new();
+        for output in &tx.outputs {
+            output.encode(&mut outputs);
+        }
+        buf.extend_from_slice(&sha256(&outputs));
+    }
+    // spend type, key path without annex
+    buf.push(0x00);
+    let input = &tx.inputs[input_index];
+    if anyone_can_pay {
+        input.previous_output.encode(&mut buf);
+        prevouts[input_index].encode(&mut buf);
+        buf.extend_from_slice(&input.sequence.to_le_bytes());
+    } else {
+        buf.extend_from_slice(&(input_index as u32).to_le_bytes());
+    }
+    if base_type == SIGHASH_SINGLE {
+        let mut output = Vec::new();
+        tx.outputs[input_index].encode(&mut output);
+        buf.extend_from_slice(&sha256(&output));
+    }
+    Ok(tagged_hash("TapSighash", &[&buf]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hdwallet::traits::Deserialize;
+
+    #[test]
+    fn test_segwit_v0() {
+        // BIP-143 native P2WPKH example, second input
+        let tx = Transaction::deserialize(hex::decode("0100000002fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f0000000000eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac11000000").unwrap().as_slice()).expect("deserialize");
+        let script_code =
+            hex::decode("76a9141d0f172a0ecb48aee1be1f2687d2963ae33f71a188ac").unwrap();
+        assert_eq!(
+            hex::encode(segwit_v0(&tx, 1, &script_code, 600_000_000, SIGHASH_ALL)),
+            "c37af31116d1b27caf68aae9e3ac82f1477929014d5b917657d0eb49478cb670"
+        );
+    }
+
+    #[test]
+    fn test_taproot_sighash_type() {
+        let tx = Transaction::deserialize(hex::decode("0100000002fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f0000000000eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac11000000").unwrap().as_slice()).expect("deserialize");
+        let prevouts = vec![
+            TxOut {
+                value: 1000,
+                script_pubkey: vec![0x51],
+            };
+            2
+        ];
+        let default = taproot_key_path(&tx, 0, &prevouts, SIGHASH_DEFAULT).expect("sighash");
+        let all = taproot_key_path(&tx, 0, &prevouts, SIGHASH_ALL).expect("sighash");
+        assert_ne!(default, all);
+        assert!(taproot_key_path(&tx, 0, &prevouts, 0x04).is_err());
+        assert!(taproot_key_path(&tx, 0, &prevouts[..1], SIGHASH_ALL).is_err());
+    }
+}
@@ -0,0 +1,887 @@
+//! BIP-352 silent payments.
+
+use crate::{
+    account::{derive_public_key, derive_secret_key},
+    hash::{hash160, tagged_hash},
+    secp256k1_sign_only, secp256k1_verify_only, AddressType, Error, Network, OutPoint, ScriptType,
+    Transaction, TxIn, TxOut,
+};
+use bech32::{FromBase32, ToBase32, Variant};
+use hdwallet::{
+    secp256k1::{self, Parity, PublicKey, Scalar, SecretKey, XOnlyPublicKey},
+    KeyChain,
+};
+use std::{fmt, str::FromStr};
+
+/// Maximum length of a silent payment address.
+const MAX_ADDRESS_LEN: usize = 1023;
+/// Taproot internal key without a known private key, inputs spending a script path with it
+/// don't contribute to the shared secret.
+const NUMS_H: [u8; 32] = [
+    0x50, 0x92, 0x9b, 0x74, 0xc1, 0xa0, 0x49, 0x54, 0xb7, 0x8b, 0x4b, 0x60, 0x35, 0xe9, 0x7a, 0x5e,
+    0x07, 0x8a, 0x5a, 0x0f, 0x28, 0xec, 0x96, 0xd5, 0x47, 0xbf, 0xee, 0x9a, 0xce, 0x80, 0x3a, 0xc0,
+];
+
+/// Silent payment address, `sp1...` on mainnet and `tsp1...` on test networks.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct SilentPaymentAddress {
+    pub network: Network,
+    pub scan_key: PublicKey,
+    /// Spend public key, tweaked by the label of a labeled address.
+    pub spend_key: PublicKey,
+}
+
+impl SilentPaymentAddress {
+    /// Parse an address and check it belongs to `network`.
+    ///
+    /// Custom networks have no silent payment addresses, they are [`Error::UnknownVersion`].
+    pub fn parse(s: &str, network: Network) -> Result<Self, Error> {
+        SilentPaymentAddress::decode(s, &[network])
+    }
+
+    fn decode(s: &str, networks: &[Network]) -> Result<Self, Error> {
+        if s.len() > MAX_ADDRESS_LEN {
+            return Err(Error::InvalidLength);
+        }
+        let (hrp, data, variant) = bech32::decode(s).map_err(|_| Error::InvalidAddress)?;
+        let network = *networks
+            .iter()
+            .find(|network| self::hrp(**network) == Some(hrp.as_str()))
+            .ok_or(Error::UnknownVersion)?;
+        let (version, data) = data.split_first().ok_or(Error::InvalidAddress)?;
+        let data = Vec::<u8>::from_base32(data).map_err(|_| Error::InvalidAddress)?;
+        // later versions keep the keys in front
+        let valid_length = match version.to_u8() {
+            0 => data.len() == 66,
+            1..=30 => data.len() >= 66,
+            _ => false,
+        };
+        if variant != Variant::Bech32m || !valid_length {
+            return Err(Error::InvalidAddress);
+        }
+        Ok(SilentPaymentAddress {
+            network,
+            scan_key: PublicKey::from_slice(&data[..33]).map_err(|_| Error::InvalidAddress)?,
+            spend_key: PublicKey::from_slice(&data[33..66]).map_err(|_| Error::InvalidAddress)?,
+        })
+    }
+}
+
+impl FromStr for SilentPaymentAddress {
+    type Err = Error;
+
+    /// Parse an address of a registered network, signet addresses are reported as testnet ones.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        SilentPaymentAddress::decode(s, &[Network::MainNet, Network::TestNet, Network::Regtest])
+    }
+}
+
+impl fmt::Display for SilentPaymentAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut data = self.scan_key.serialize().to_vec();
+        data.extend_from_slice(&self.spend_key.serialize());
+        let mut base32 = vec![bech32::u5::try_from_u8(0).expect("version 0")];
+        base32.extend(data.to_base32());
+        let hrp = hrp(self.network).ok_or(fmt::Error)?;
+        let s = bech32::encode(hrp, base32, Variant::Bech32m).map_err(|_| fmt::Error)?;
+        f.write_str(&s)
+    }
+}
+
+/// Silent payment output found by scanning a transaction.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SilentPaymentOutput {
+    /// Taproot output key of the output.
+    pub output_key: XOnlyPublicKey,
+    /// Tweak of the spend key, the private key of the output is the spend private key plus
+    /// the tweak.
+    pub tweak: SecretKey,
+    /// Label of the address paid to, `None` for the unlabeled address.
+    pub label: Option<u32>,
+}
+
+/// Silent payment account at `m/352'/coin_type'/account'`.
+///
+/// The account keeps the scan private key at `1'/0`, enough to scan transactions and build
+/// labeled addresses, and the spend public key at `0'/0`. The private keys of received outputs
+/// are derived from the key chain the account was created from.
+///
+/// ```rust
+/// use hdwallet::{DefaultKeyChain, ExtendedPrivKey};
+/// use hdwallet_bitcoin::{Network, SilentPaymentAccount};
+///
+/// let key_chain = DefaultKeyChain::new(ExtendedPrivKey::random(&mut rand::thread_rng()).unwrap());
+/// let account = SilentPaymentAccount::new(&key_chain, 0, 0, Network::MainNet).unwrap();
+/// assert_eq!(account.path(), "m/352H/0H/0H");
+/// assert!(account.address(None).unwrap().to_string().starts_with("sp1q"));
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SilentPaymentAccount {
+    pub coin_type: u32,
+    /// Account number, the hardened index of the third derivation step.
+    pub index: u32,
+    pub network: Network,
+    pub scan_key: SecretKey,
+    pub spend_key: PublicKey,
+}
+
+impl SilentPaymentAccount {
+    /// Derive account `index` of `coin_type` from `key_chain`, e.g. coin type 0 for Bitcoin and 1
+    /// for all test networks.
+    pub fn new(
+        key_chain: &impl KeyChain,
+        coin_type: u32,
+        index: u32,
+        network: Network,
+    ) -> Result<Self, Error> {
+        let path = format!("m/352H/{}H/{}H", coin_type, index);
+        let spend_path = format!("{}/0H/0", path);
+        let spend_key = derive_public_key(key_chain, &spend_path, ScriptType::Legacy, network)?;
+        Ok(SilentPaymentAccount {
+            coin_type,
+            index,
+            network,
+            scan_key: derive_secret_key(key_chain, &format!("{}/1H/0", path))?,
+            spend_key: spend_key.extended_key.public_key,
+        })
+    }
+
+    /// Derivation path of the account, e.g. `m/352H/0H/0H`.
+    pub fn path(&self) -> String {
+        format!("m/352H/{}H/{}H", self.coin_type, self.index)
+    }
+
+    /// Address of the account, labeled with `label` if any. Label 0 is reserved for change.
+    pub fn address(&self, label: Option<u32>) -> Result<SilentPaymentAddress, Error> {
+        if hrp(self.network).is_none() {
+            return Err(Error::UnknownVersion);
+        }
+        let spend_key = match label {
+            Some(label) => self
+                .spend_key
+                .add_exp_tweak(secp256k1_verify_only(), &self.label_tweak(label)?.into())?,
+            None => self.spend_key,
+        };
+        Ok(SilentPaymentAddress {
+            network: self.network,
+            scan_key: self.scan_key.public_key(secp256k1_sign_only()),
+            spend_key,
+        })
+    }
+
+    /// Outputs of a transaction paying to the account or one of its `labels`.
+    ///
+    /// `input_keys` are the public keys of the inputs eligible for silent payments, see
+    /// [`SilentPaymentAccount::scan_transaction`], and `outpoints` the outpoints of all inputs.
+    /// `outputs` are the taproot output keys of the transaction.
+    pub fn scan(
+        &self,
+        labels: &[u32],
+        input_keys: &[PublicKey],
+        outpoints: &[OutPoint],
+        outputs: &[XOnlyPublicKey],
+    ) -> Result<Vec<SilentPaymentOutput>, Error> {
+        let input_key = match input_keys.iter().collect::<Vec<_>>().as_slice() {
+            [] => return Ok(Vec::new()),
+            keys => match PublicKey::combine_keys(keys) {
+                Ok(key) => key,
+                // the input keys cancel out
+                Err(_) => return Ok(Vec::new()),
+            },
+        };
+        let tweak = input_hash(outpoints, &input_key)?;
+        let shared_secret = input_key.mul_tweak(
+            secp256k1_verify_only(),
+            &self.scan_key.mul_tweak(&tweak)?.into(),
+        )?;
+        let secp = secp256k1_verify_only();
+        let labels = labels
+            .iter()
+            .map(|label| {
+                let tweak = self.label_tweak(*label)?;
+                Ok((tweak.public_key(secp256k1_sign_only()), *label, tweak))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let mut outputs = outputs.to_vec();
+        let mut found = Vec::new();
+        for k in 0.. {
+            let t_k = output_tweak(&shared_secret, k)?;
+            let p_k = self.spend_key.add_exp_tweak(secp, &t_k.into())?;
+            let negated_p_k = p_k.negate(secp);
+            let position = outputs.iter().enumerate().find_map(|(i, output)| {
+                if *output == p_k.x_only_public_key().0 {
+                    return Some((i, t_k, None));
+                }
+                let output = PublicKey::from_x_only_public_key(*output, Parity::Even);
+                [output, output.negate(secp)].iter().find_map(|output| {
+                    let label_key = output.combine(&negated_p_k).ok()?;
+                    let (_, label, label_tweak) =
+                        labels.iter().find(|(key, _, _)| *key == label_key)?;
+                    let tweak = t_k.add_tweak(&(*label_tweak).into()).ok()?;
+                    Some((i, tweak, Some(*label)))
+                })
+            });
+            match position {
+                Some((i, tweak, label)) => found.push(SilentPaymentOutput {
+                    output_key: outputs.remove(i),
+                    tweak,
+                    label,
+                }),
+                None => break,
+            }
+        }
+        Ok(found)
+    }
+
+    /// Outputs of `tx` paying to the account or one of its `labels`, `prevouts` are the outputs
+    /// spent by the inputs of `tx`.
+    ///
+    /// P2PKH, P2SH-P2WPKH, P2WPKH and taproot inputs with compressed keys are eligible for
+    /// silent payments. Transactions spending witness versions above 1 are not scanned.
+    pub fn scan_transaction(
+        &self,
+        labels: &[u32],
+        tx: &Transaction,
+        prevouts: &[TxOut],
+    ) -> Result<Vec<SilentPaymentOutput>, Error> {
+        if prevouts.len() != tx.inputs.len() {
+            return Err(Error::InvalidTransaction);
+        }
+        if spends_unknown_witness_version(prevouts) {
+            return Ok(Vec::new());
+        }
+        let input_keys = tx
+            .inputs
+            .iter()
+            .zip(prevouts)
+            .filter_map(|(input, prevout)| input_public_key(input, prevout))
+            .collect::<Vec<_>>();
+        let outpoints = tx
+            .inputs
+            .iter()
+            .map(|input| input.previous_output)
+            .collect::<Vec<_>>();
+        let outputs = tx
+            .outputs
+            .iter()
+            .filter_map(|output| match output.script_pubkey.as_slice() {
+                [0x51, 0x20, key @ ..] => XOnlyPublicKey::from_slice(key).ok(),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        self.scan(labels, &input_keys, &outpoints, &outputs)
+    }
+
+    /// Private key of a received output, the spend private key tweaked by the output tweak.
+    pub fn output_secret_key(
+        &self,
+        key_chain: &impl KeyChain,
+        output: &SilentPaymentOutput,
+    ) -> Result<SecretKey, Error> {
+        let spend_key = derive_secret_key(key_chain, &format!("{}/0H/0", self.path()))?;
+        Ok(spend_key.add_tweak(&output.tweak.into())?)
+    }
+
+    fn label_tweak(&self, label: u32) -> Result<SecretKey, Error> {
+        let hash = tagged_hash(
+            "BIP0352/Label",
+            &[&self.scan_key.secret_bytes(), &label.to_be_bytes()],
+        );
+        Ok(SecretKey::from_slice(&hash)?)
+    }
+}
+
+/// Taproot output keys paying to `recipients`, in the order of `recipients`.
+///
+/// `inputs` are the private keys and address types of the inputs eligible for silent payments,
+/// and `outpoints` the outpoints of all inputs of the transaction. Input keys summing to zero
+/// are [`Error::InvalidTransaction`].
+pub fn silent_payment_outputs(
+    inputs: &[(SecretKey, AddressType)],
+    outpoints: &[OutPoint],
+    recipients: &[SilentPaymentAddress],
+) -> Result<Vec<XOnlyPublicKey>, Error> {
+    let mut input_key: Option<SecretKey> = None;
+    for (key, address_type) in inputs {
+        let key = match address_type {
+            // taproot keys have even y
+            AddressType::P2tr if key.x_only_public_key(secp256k1_sign_only()).1 == Parity::Odd => {
+                key.negate()
+            }
+            _ => *key,
+        };
+        input_key = match input_key {
+            // a zero sum is no secret key
+            Some(sum) => sum.add_tweak(&key.into()).ok(),
+            None => Some(key),
+        };
+    }
+    let input_key = input_key.ok_or(Error::InvalidTransaction)?;
+    let tweak = input_hash(outpoints, &input_key.public_key(secp256k1_sign_only()))?;
+    let input_key = input_key.mul_tweak(&tweak)?;
+    let secp = secp256k1_verify_only();
+    // shared secret and output count of each scan key
+    let mut shared_secrets: Vec<(PublicKey, PublicKey, u32)> = Vec::new();
+    let mut output_keys = Vec::with_capacity(recipients.len());
+    for recipient in recipients {
+        let t_k = match shared_secrets
+            .iter_mut()
+            .find(|(scan_key, _, _)| *scan_key == recipient.scan_key)
+        {
+            Some((_, shared_secret, k)) => {
+                *k += 1;
+                output_tweak(shared_secret, *k)?
+            }
+            None => {
+                let shared_secret = recipient.scan_key.mul_tweak(secp, &input_key.into())?;
+                shared_secrets.push((recipient.scan_key, shared_secret, 0));
+                output_tweak(&shared_secret, 0)?
+            }
+        };
+        let output_key = recipient.spend_key.add_exp_tweak(secp, &t_k.into())?;
+        output_keys.push(output_key.x_only_public_key().0);
+    }
+    Ok(output_keys)
+}
+
+fn hrp(network: Network) -> Option<&'static str> {
+    match network {
+        Network::MainNet => Some("sp"),
+        Network::TestNet | Network::Signet => Some("tsp"),
+        Network::Regtest => Some("sprt"),
+        Network::Custom(_) | Network::Unknown(_) => None,
+    }
+}
+
+/// `hash(smallest outpoint || input key)`, tweaking the ECDH of the input and scan keys.
+fn input_hash(outpoints: &[OutPoint], input_key: &PublicKey) -> Result<Scalar, Error> {
+    let outpoint = outpoints
+        .iter()
+        .map(|outpoint| {
+            let mut data = outpoint.txid.to_vec();
+            data.extend_from_slice(&outpoint.vout.to_le_bytes());
+            data
+        })
+        .min()
+        .ok_or(Error::InvalidTransaction)?;
+    let hash = tagged_hash("BIP0352/Inputs", &[&outpoint, &input_key.serialize()]);
+    Scalar::from_be_bytes(hash).map_err(|_| Error::Secp(secp256k1::Error::InvalidTweak))
+}
+
+/// `t_k`, tweak of the spend key of the `k`th output paid to a scan key.
+fn output_tweak(shared_secret: &PublicKey, k: u32) -> Result<SecretKey, Error> {
+    let hash = tagged_hash(
+        "BIP0352/SharedSecret",
+        &[&shared_secret.serialize(), &k.to_be_bytes()],
+    );
+    Ok(SecretKey::from_slice(&hash)?)
+}
+
+/// Whether one of `prevouts` is a witness program above version 1, such transactions carry no
+/// silent payments.
+fn spends_unknown_witness_version(prevouts: &[TxOut]) -> bool {
+    prevouts.iter().any(|prevout| {
+        matches!(prevout.script_pubkey.as_slice(), [0x52..=0x60, len, program @ ..]
+            if *len as usize == program.len() && (2..=40).contains(len))
+    })
+}
+
+/// Public key of an input eligible for silent payments.
+fn input_public_key(input: &TxIn, prevout: &TxOut) -> Option<PublicKey> {
+    let compressed = |data: &[u8]| match data {
+        [0x02 | 0x03, ..] if data.len() == 33 => PublicKey::from_slice(data).ok(),
+        _ => None,
+    };
+    match prevout.script_pubkey.as_slice() {
+        // P2PKH, the last pushed key matching the hash, scriptSig may be malleated
+        [0x76, 0xa9, 0x14, hash @ .., 0x88, 0xac] if hash.len() == 20 => {
+            let script_sig = &input.script_sig;
+            (33..=script_sig.len())
+                .rev()
+                .map(|end| &script_sig[end - 33..end])
+                .find(|key| hash160(key) == hash)
+                .and_then(compressed)
+        }
+        // P2SH-P2WPKH
+        [0xa9, 0x14, _, .., 0x87] if prevout.script_pubkey.len() == 23 => {
+            match input.script_sig.as_slice() {
+                [0x16, 0x00, 0x14, ..] if input.script_sig.len() == 23 => {
+                    input.witness.last().and_then(|key| compressed(key))
+                }
+                _ => None,
+            }
+        }
+        // P2WPKH
+        [0x00, 0x14, ..] if prevout.script_pubkey.len() == 22 => {
+            input.witness.last().and_then(|key| compressed(key))
+        }
+        // P2TR, the output key unless a script path with the NUMS internal key
+        [0x51, 0x20, key @ ..] if key.len() == 32 => {
+            let mut witness = input.witness.as_slice();
+            if let [rest @ .., annex] = witness {
+                if !rest.is_empty() && annex.first() == Some(&0x50) {
+                    witness = rest;
+                }
+            }
+            if let [_, .., control_block] = witness {
+                if control_block.get(1..33) == Some(&NUMS_H[..]) {
+                    return None;
+                }
+            }
+            XOnlyPublicKey::from_slice(key)
+                .ok()
+                .map(|key| PublicKey::from_x_only_public_key(key, Parity::Even))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::Reader;
+    use hdwallet::{DefaultKeyChain, ExtendedPrivKey};
+    use serde_json::Value;
+
+    // BIP-352 test vector "Simple send: two inputs"
+    const ADDRESS: &str = "sp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqjuexzk6murw56suy3e0rd2cgqvycxttddwsvgxe2usfpxumr70xc9pkqwv";
+    const SCAN_KEY: &str = "0f694e068028a717f8af6b9411f9a133dd3565258714cc226594b34db90c1f2c";
+    const SPEND_KEY: &str = "9d6ad855ce3417ef84e836892e5a56392bfba05fa5d97ccea30e266f540e08b3";
+    const INPUTS: [(&str, &str, &str, &str); 2] = [
+        (
+            "f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16",
+            "483046022100ad79e6801dd9a8727f342f31c71c4912866f59dc6e7981878e92c5844a0ce929022100fb0d2393e813968648b9753b7e9871d90ab3d815ebf91820d704b19f4ed224d621025a1e61f898173040e20616d43e9f496fba90338a39faa1ed98fcbaeee4dd9be5",
+            "76a91419c2f3ae0ca3b642bd3e49598b8da89f50c1416188ac",
+            "eadc78165ff1f8ea94ad7cfdc54990738a4c53f6e0507b42154201b8e5dff3b1",
+        ),
+        (
+            "a1075db55d416d3ca199f55b6084e2115b9345e16c5cf302fc80e9d5fbf5d48d",
+            "48304602210086783ded73e961037e77d49d9deee4edc2b23136e9728d56e4491c80015c3a63022100fda4c0f21ea18de29edbce57f7134d613e044ee150a89e2e64700de2d4e83d4e2103bd85685d03d111699b15d046319febe77f8de5286e9e512703cdee1bf3be3792",
+            "76a914d9317c66f54ff0a152ec50b1d19c25be50c8e15988ac",
+            "93f5ed907ad5b2bdbbdcb5d9116ebc0a4e1f92f910d5260237fa45a9408aad16",
+        ),
+    ];
+    const OUTPUT: &str = "3e9fce73d4e77a4809908e3c3a2e54ee147b9312dc5044a193d1fc85de46e3c1";
+    const TWEAK: &str = "f438b40179a3c4262de12986c0e6cce0634007cdc79c1dcd3e20b9ebc2e7eef6";
+
+    fn secret_key(s: &str) -> SecretKey {
+        SecretKey::from_slice(&hex::decode(s).unwrap()).unwrap()
+    }
+
+    fn outpoint(txid: &str, vout: u32) -> OutPoint {
+        let mut txid = hex::decode(txid).unwrap();
+        txid.reverse();
+        OutPoint {
+            txid: txid.try_into().unwrap(),
+            vout,
+        }
+    }
+
+    fn taproot_output(key: &XOnlyPublicKey) -> TxOut {
+        let mut script_pubkey = vec![0x51, 0x20];
+        script_pubkey.extend_from_slice(&key.serialize());
+        TxOut {
+            value: 1000,
+            script_pubkey,
+        }
+    }
+
+    fn transaction(inputs: Vec<TxIn>, outputs: &[XOnlyPublicKey]) -> Transaction {
+        Transaction {
+            version: 2,
+            inputs,
+            outputs: outputs.iter().map(taproot_output).collect(),
+            lock_time: 0,
+        }
+    }
+
+    /// Key chain of BIP-39 mnemonic `abandon abandon ... about` without passphrase.
+    fn key_chain() -> DefaultKeyChain {
+        let seed = hex::decode("5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc19a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4").expect("decode");
+        DefaultKeyChain::new(ExtendedPrivKey::with_seed(&seed).expect("master key"))
+    }
+
+    #[test]
+    fn test_address() {
+        let address = ADDRESS.parse::<SilentPaymentAddress>().unwrap();
+        assert_eq!(address.network, Network::MainNet);
+        assert_eq!(address.to_string(), ADDRESS);
+        assert_eq!(
+            SilentPaymentAddress::parse(ADDRESS, Network::TestNet),
+            Err(Error::UnknownVersion)
+        );
+        // a segwit address is no silent payment address
+        assert_eq!(
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu".parse::<SilentPaymentAddress>(),
+            Err(Error::UnknownVersion)
+        );
+
+        let account = SilentPaymentAccount {
+            coin_type: 0,
+            index: 0,
+            network: Network::MainNet,
+            scan_key: secret_key(SCAN_KEY),
+            spend_key: secret_key(SPEND_KEY).public_key(secp256k1_sign_only()),
+        };
+        assert_eq!(account.address(None).unwrap(), address);
+        assert_eq!(account.address(Some(1)).unwrap().to_string(), "sp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqaxww2fnhrx05cghth75n0qcj59e3e2anscr0q9wyknjxtxycg07y3pevyj");
+
+        let key_chain = key_chain();
+        for (coin_type, network, address) in &[
+            (0, Network::MainNet, "sp1qqfqnnv8czppwysafq3uwgwvsc638hc8rx3hscuddh0xa2yd746s7xqh6yy9ncjnqhqxazct0fzh98w7lpkm5fvlepqec2yy0sxlq4j6ccc3h6t0g"),
+            (1, Network::TestNet, "tsp1qqdpels3srq45dlezqvk20t3dlueftry6p5thc7msjm0s6jm3g84jzq5rxzzunfck6d45va2jcqxk429agt3e4klf3vzmcgp3zqthryhhqgnz4k3n"),
+        ] {
+            let account = SilentPaymentAccount::new(&key_chain, *coin_type, 0, *network).unwrap();
+            assert_eq!(account.address(None).unwrap().to_string(), *address);
+        }
+    }
+
+    #[test]
+    fn test_send_and_receive() {
+        let recipient = ADDRESS.parse::<SilentPaymentAddress>().unwrap();
+        let outpoints = INPUTS
+            .iter()
+            .map(|(txid, _, _, _)| outpoint(txid, 0))
+            .collect::<Vec<_>>();
+        let inputs = INPUTS
+            .iter()
+            .map(|(_, _, _, key)| (secret_key(key), AddressType::P2pkh))
+            .collect::<Vec<_>>();
+        let outputs = silent_payment_outputs(&inputs, &outpoints, &[recipient]).unwrap();
+        assert_eq!(hex::encode(outputs[0].serialize()), OUTPUT);
+
+        let tx = transaction(
+            INPUTS
+                .iter()
+                .zip(&outpoints)
+                .map(|((_, script_sig, _, _), outpoint)| TxIn {
+                    previous_output: *outpoint,
+                    script_sig: hex::decode(script_sig).unwrap(),
+                    sequence: 0xffff_ffff,
+                    witness: Vec::new(),
+                })
+                .collect(),
+            &outputs,
+        );
+        let prevouts = INPUTS
+            .iter()
+            .map(|(_, _, script_pubkey, _)| TxOut {
+                value: 1000,
+                script_pubkey: hex::decode(script_pubkey).unwrap(),
+            })
+            .collect::<Vec<_>>();
+        let account = SilentPaymentAccount {
+            coin_type: 0,
+            index: 0,
+            network: Network::MainNet,
+            scan_key: secret_key(SCAN_KEY),
+            spend_key: secret_key(SPEND_KEY).public_key(secp256k1_sign_only()),
+        };
+        let found = account.scan_transaction(&[], &tx, &prevouts).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].output_key, outputs[0]);
+        assert_eq!(hex::encode(found[0].tweak.secret_bytes()), TWEAK);
+        assert_eq!(found[0].label, None);
+        assert_eq!(
+            account.scan_transaction(&[], &tx, &prevouts[1..]),
+            Err(Error::InvalidTransaction)
+        );
+    }
+
+    /// Input and spent output of a `vin` entry of the BIP-352 test vectors.
+    fn vector_input(vin: &Value) -> (TxIn, TxOut) {
+        let hex = |value: &Value| hex::decode(value.as_str().unwrap()).unwrap();
+        let data = hex(&vin["txinwitness"]);
+        let mut witness = Vec::new();
+        if !data.is_empty() {
+            let mut reader = Reader::new(&data);
+            for _ in 0..reader.read_compact_size().unwrap() {
+                witness.push(reader.read_var_bytes().unwrap().to_vec());
+            }
+        }
+        let input = TxIn {
+            previous_output: outpoint(
+                vin["txid"].as_str().unwrap(),
+                vin["vout"].as_u64().unwrap() as u32,
+            ),
+            script_sig: hex(&vin["scriptSig"]),
+            sequence: 0xffff_ffff,
+            witness,
+        };
+        let prevout = TxOut {
+            value: 1000,
+            script_pubkey: hex(&vin["prevout"]["scriptPubKey"]["hex"]),
+        };
+        (input, prevout)
+    }
+
+    #[test]
+    fn test_vectors() {
+        // cases of the BIP-352 send_and_receive_test_vectors.json, in its format
+        let cases: Value = serde_json::from_str(include_str!("silent_payment/test_vectors.json"))
+            .expect("test vectors");
+        for case in cases.as_array().unwrap() {
+            let comment = case["comment"].as_str().unwrap();
+            for sending in case["sending"].as_array().unwrap() {
+                let given = &sending["given"];
+                let vin = given["vin"].as_array().unwrap();
+                let outpoints = vin
+                    .iter()
+                    .map(|vin| vector_input(vin).0.previous_output)
+                    .collect::<Vec<_>>();
+                // the sender skips the inputs the receiver can't see
+                let inputs = vin
+                    .iter()
+                    .filter(|vin| {
+                        let (input, prevout) = vector_input(vin);
+                        input_public_key(&input, &prevout).is_some()
+                    })
+                    .map(|vin| {
+                        let address_type = match vector_input(vin).1.script_pubkey[0] {
+                            0x51 => AddressType::P2tr,
+                            _ => AddressType::P2wpkh,
+                        };
+                        (
+                            secret_key(vin["private_key"].as_str().unwrap()),
+                            address_type,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                let recipients = given["recipients"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|recipient| {
+                        // older versions of the vectors pair addresses with amounts
+                        let address = recipient.as_str().or_else(|| recipient[0].as_str());
+                        address.unwrap().parse().unwrap()
+                    })
+                    .collect::<Vec<SilentPaymentAddress>>();
+                let prevouts = vin
+                    .iter()
+                    .map(|vin| vector_input(vin).1)
+                    .collect::<Vec<_>>();
+                let mut outputs = if inputs.is_empty() || spends_unknown_witness_version(&prevouts)
+                {
+                    Vec::new()
+                } else {
+                    match silent_payment_outputs(&inputs, &outpoints, &recipients) {
+                        Ok(outputs) => outputs
+                            .iter()
+                            .map(|output| hex::encode(output.serialize()))
+                            .collect(),
+                        Err(Error::InvalidTransaction) => Vec::new(),
+                        Err(err) => panic!("{}: {:?}", comment, err),
+                    }
+                };
+                outputs.sort();
+                // outputs to the same scan key may come in any order
+                let expected = sending["expected"]["outputs"].as_array().unwrap();
+                let matches = |set: &[Value]| {
+                    let mut set = set
+                        .iter()
+                        .map(|output| output.as_str().unwrap().to_string())
+                        .collect::<Vec<_>>();
+                    set.sort();
+                    set == outputs
+                };
+                let valid = match expected.first() {
+                    Some(Value::Array(_)) => {
+                        expected.iter().any(|set| matches(set.as_array().unwrap()))
+                    }
+                    _ => matches(expected),
+                };
+                assert!(valid, "{}: sending {:?}", comment, outputs);
+            }
+
+            for receiving in case["receiving"].as_array().unwrap() {
+                let given = &receiving["given"];
+                let expected = &receiving["expected"];
+                let key_material = &given["key_material"];
+                let spend_key = secret_key(key_material["spend_priv_key"].as_str().unwrap());
+                let account = SilentPaymentAccount {
+                    coin_type: 0,
+                    index: 0,
+                    network: Network::MainNet,
+                    scan_key: secret_key(key_material["scan_priv_key"].as_str().unwrap()),
+                    spend_key: spend_key.public_key(secp256k1_sign_only()),
+                };
+                let labels = given["labels"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|label| label.as_u64().unwrap() as u32)
+                    .collect::<Vec<_>>();
+                let mut addresses = vec![account.address(None).unwrap().to_string()];
+                for label in &labels {
+                    addresses.push(account.address(Some(*label)).unwrap().to_string());
+                }
+                for address in expected["addresses"].as_array().unwrap() {
+                    assert!(
+                        addresses.iter().any(|a| a == address.as_str().unwrap()),
+                        "{}: address {}",
+                        comment,
+                        address
+                    );
+                }
+
+                let (inputs, prevouts): (Vec<_>, Vec<_>) = given["vin"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(vector_input)
+                    .unzip();
+                let outputs = given["outputs"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|output| {
+                        let key = hex::decode(output.as_str().unwrap()).unwrap();
+                        XOnlyPublicKey::from_slice(&key).unwrap()
+                    })
+                    .collect::<Vec<_>>();
+                let tx = transaction(inputs, &outputs);
+                let found = account.scan_transaction(&labels, &tx, &prevouts).unwrap();
+                let mut found = found
+                    .iter()
+                    .map(|output| {
+                        // the tweaked spend key controls the output
+                        let key = spend_key.add_tweak(&output.tweak.into()).unwrap();
+                        assert_eq!(
+                            key.x_only_public_key(secp256k1_sign_only()).0,
+                            output.output_key,
+                            "{}",
+                            comment
+                        );
+                        (
+                            hex::encode(output.output_key.serialize()),
+                            hex::encode(output.tweak.secret_bytes()),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                found.sort();
+                let mut outputs = expected["outputs"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|output| {
+                        (
+                            output["pub_key"].as_str().unwrap().to_string(),
+                            output["priv_key_tweak"].as_str().unwrap().to_string(),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                outputs.sort();
+                assert_eq!(found, outputs, "{}: receiving", comment);
+            }
+        }
+    }
+
+    #[test]
+    fn test_labels_and_taproot_inputs() {
+        let key_chain = key_chain();
+        let account = SilentPaymentAccount::new(&key_chain, 0, 0, Network::MainNet).unwrap();
+        let other = SilentPaymentAccount {
+            scan_key: secret_key(SCAN_KEY),
+            ..account.clone()
+        };
+        let recipients = [
+            account.address(None).unwrap(),
+            other.address(None).unwrap(),
+            account.address(Some(1)).unwrap(),
+            account.address(Some(0)).unwrap(),
+        ];
+
+        // taproot keys of both parities and a P2WPKH key
+        let secp = secp256k1_sign_only();
+        let taproot_keys = (1u8..=8)
+            .map(|i| secret_key(&hex::encode([i; 32])))
+            .collect::<Vec<_>>();
+        let odd = taproot_keys
+            .iter()
+            .find(|key| key.x_only_public_key(secp).1 == Parity::Odd)
+            .unwrap();
+        let even = taproot_keys
+            .iter()
+            .find(|key| key.x_only_public_key(secp).1 == Parity::Even)
+            .unwrap();
+        let wpkh = secret_key(INPUTS[0].3);
+        let inputs = [
+            (*odd, AddressType::P2tr),
+            (*even, AddressType::P2tr),
+            (wpkh, AddressType::P2wpkh),
+        ];
+        let outpoints = (0..3)
+            .map(|vout| outpoint(INPUTS[1].0, vout))
+            .collect::<Vec<_>>();
+        let outputs = silent_payment_outputs(&inputs, &outpoints, &recipients).unwrap();
+
+        let wpkh = wpkh.public_key(secp);
+        let mut wpkh_script = vec![0x00, 0x14];
+        wpkh_script.extend_from_slice(&hash160(&wpkh.serialize()));
+        let prevouts = vec![
+            taproot_output(&odd.x_only_public_key(secp).0),
+            taproot_output(&even.x_only_public_key(secp).0),
+            TxOut {
+                value: 1000,
+                script_pubkey: wpkh_script,
+            },
+        ];
+        let witnesses = [
+            vec![vec![0; 64]],
+            vec![vec![0; 64]],
+            vec![vec![0; 72], wpkh.serialize().to_vec()],
+        ];
+        let tx = transaction(
+            outpoints
+                .iter()
+                .zip(witnesses.iter())
+                .map(|(outpoint, witness)| TxIn {
+                    previous_output: *outpoint,
+                    script_sig: Vec::new(),
+                    sequence: 0xffff_ffff,
+                    witness: witness.clone(),
+                })
+                .collect(),
+            &outputs,
+        );
+
+        let mut found = account.scan_transaction(&[0, 1], &tx, &prevouts).unwrap();
+        found.sort_by_key(|output| output.label);
+        assert_eq!(
+            found.iter().map(|output| output.label).collect::<Vec<_>>(),
+            [None, Some(0), Some(1)]
+        );
+        for output in &found {
+            let secret_key = account.output_secret_key(&key_chain, output).unwrap();
+            assert_eq!(secret_key.x_only_public_key(secp).0, output.output_key);
+        }
+        // the labeled outputs follow the unlabeled one
+        assert_eq!(
+            account.scan_transaction(&[], &tx, &prevouts).unwrap().len(),
+            1
+        );
+        assert_eq!(
+            other.scan_transaction(&[], &tx, &prevouts).unwrap()[0].output_key,
+            outputs[1]
+        );
+
+        // a script path spend of the NUMS key doesn't count
+        let mut script_path = tx.clone();
+        let mut control_block = vec![0xc0];
+        control_block.extend_from_slice(&NUMS_H);
+        script_path.inputs[0].witness = vec![vec![0; 64], vec![0x51], control_block];
+        assert!(account
+            .scan_transaction(&[0, 1], &script_path, &prevouts)
+            .unwrap()
+            .is_empty());
+
+        // transactions spending later witness versions are not scanned
+        let mut prevouts = prevouts;
+        prevouts[1].script_pubkey[0] = 0x52;
+        assert!(account
+            .scan_transaction(&[0, 1], &tx, &prevouts)
+            .unwrap()
+            .is_empty());
+    }
+}
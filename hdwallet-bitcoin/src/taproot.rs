@@ -0,0 +1,272 @@
+//! BIP-340 Schnorr signatures and the BIP-341 taproot tweak of extended keys.
+
+use crate::{hash::tagged_hash, secp256k1_sign_only, secp256k1_verify_only, Error};
+use hdwallet::{
+    secp256k1::{self, schnorr::Signature, KeyPair, Message, Parity, Scalar, XOnlyPublicKey},
+    ExtendedPrivKey, ExtendedPubKey,
+};
+
+/// Taproot operations on extended private keys.
+///
+/// The tweaked methods take the merkle root of the script tree, `None` for an output without
+/// script tree as BIP-86 does.
+pub trait TaprootPrivKeyExt {
+    /// X-only public key, the internal key of a taproot output.
+    fn x_only_public_key(&self) -> XOnlyPublicKey;
+
+    /// Key pair of the output key, the internal key tweaked with `merkle_root`.
+    fn tap_tweak(&self, merkle_root: Option<&[u8; 32]>) -> Result<KeyPair, Error>;
+
+    /// BIP-340 signature of `message` with the untweaked key, without `aux_rand` the nonce is
+    /// derived from the key and message only.
+    fn sign_schnorr(&self, message: &[u8; 32], aux_rand: Option<&[u8; 32]>) -> Signature;
+
+    /// BIP-340 signature of `message` with the output key, as a key path spend.
+    fn sign_taproot(
+        &self,
+        message: &[u8; 32],
+        merkle_root: Option<&[u8; 32]>,
+        aux_rand: Option<&[u8; 32]>,
+    ) -> Result<Signature, Error>;
+}
+
+/// Taproot operations on extended public keys, the parity of the key is ignored.
+pub trait TaprootPubKeyExt {
+    /// X-only public key, the internal key of a taproot output.
+    fn x_only_public_key(&self) -> XOnlyPublicKey;
+
+    /// Output key and its parity, the internal key tweaked with `merkle_root`.
+    fn tap_tweak(&self, merkle_root: Option<&[u8; 32]>) -> Result<(XOnlyPublicKey, Parity), Error>;
+
+    /// Verify a BIP-340 signature of `message` with the untweaked key.
+    fn verify_schnorr(&self, message: &[u8; 32], signature: &Signature) -> Result<(), Error>;
+
+    /// Verify a BIP-340 signature of `message` with the output key.
+    fn verify_taproot(
+        &self,
+        message: &[u8; 32],
+        signature: &Signature,
+        merkle_root: Option<&[u8; 32]>,
+    ) -> Result<(), Error>;
+}
+
+impl TaprootPrivKeyExt for ExtendedPrivKey {
+    fn x_only_public_key(&self) -> XOnlyPublicKey {
+        self.public_key().x_only_public_key().0
+    }
+
+    fn tap_tweak(&self, merkle_root: Option<&[u8; 32]>) -> Result<KeyPair, Error> {
+        let key_pair = KeyPair::from_secret_key(secp256k1_sign_only(), &self.private_key());
+        let tweak = tap_tweak_hash(&key_pair.x_only_public_key().0, merkle_root)?;
+        Ok(key_pair.add_xonly_tweak(secp256k1_verify_only(), &tweak)?)
+    }
+
+    fn sign_schnorr(&self, message: &[u8; 32], aux_rand: Option<&[u8; 32]>) -> Signature {
+        let key_pair = KeyPair::from_secret_key(secp256k1_sign_only(), &self.private_key());
+        sign(&key_pair, message, aux_rand)
+    }
+
+    fn sign_taproot(
+        &self,
+        message: &[u8; 32],
+        merkle_root: Option<&[u8; 32]>,
+        aux_rand: Option<&[u8; 32]>,
+    ) -> Result<Signature, Error> {
+        Ok(sign(&self.tap_tweak(merkle_root)?, message, aux_rand))
+    }
+}
+
+impl TaprootPubKeyExt for ExtendedPubKey {
+    fn x_only_public_key(&self) -> XOnlyPublicKey {
+        self.public_key.x_only_public_key().0
+    }
+
+    fn tap_tweak(&self, merkle_root: Option<&[u8; 32]>) -> Result<(XOnlyPublicKey, Parity), Error> {
+        output_key(&self.x_only_public_key(), merkle_root)
+    }
+
+    fn verify_schnorr(&self, message: &[u8; 32], signature: &Signature) -> Result<(), Error> {
+        verify(&self.x_only_public_key(), message, signature)
+    }
+
+    fn verify_taproot(
+        &self,
+        message: &[u8; 32],
+        signature: &Signature,
+        merkle_root: Option<&[u8; 32]>,
+    ) -> Result<(), Error> {
+        verify(&self.tap_tweak(merkle_root)?.0, message, signature)
+    }
+}
+
+/// Output key of `internal_key` tweaked with `merkle_root`, BIP-341.
+pub(crate) fn output_key(
+    internal_key: &XOnlyPublicKey,
+    merkle_root: Option<&[u8; 32]>,
+) -> Result<(XOnlyPublicKey, Parity), Error> {
+    let tweak = tap_tweak_hash(internal_key, merkle_root)?;
+    Ok(internal_key.add_tweak(secp256k1_verify_only(), &tweak)?)
+}
+
+/// `TapTweak` tagged hash of the internal key and the merkle root.
+pub(crate) fn tap_tweak_hash(
+    internal_key: &XOnlyPublicKey,
+    merkle_root: Option<&[u8; 32]>,
+) -> Result<Scalar, Error> {
+    let key = internal_key.serialize();
+    let hash = match merkle_root {
+        Some(merkle_root) => tagged_hash("TapTweak", &[&key, merkle_root]),
+        None => tagged_hash("TapTweak", &[&key]),
+    };
+    // fails with negligible probability
+    Scalar::from_be_bytes(hash).map_err(|_| Error::Secp(secp256k1::Error::InvalidTweak))
+}
+
+fn sign(key_pair: &KeyPair, message: &[u8; 32], aux_rand: Option<&[u8; 32]>) -> Signature {
+    let message = Message::from_slice(message).expect("32 bytes");
+    match aux_rand {
+        Some(aux_rand) => {
+            secp256k1_sign_only().sign_schnorr_with_aux_rand(&message, key_pair, aux_rand)
+        }
+        None => secp256k1_sign_only().sign_schnorr_no_aux_rand(&message, key_pair),
+    }
+}
+
+fn verify(key: &XOnlyPublicKey, message: &[u8; 32], signature: &Signature) -> Result<(), Error> {
+    let message = Message::from_slice(message).expect("32 bytes");
+    Ok(secp256k1_verify_only().verify_schnorr(signature, &message, key)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hdwallet::{
+        secp256k1::{PublicKey, SecretKey},
+        DefaultKeyChain, KeyChain,
+    };
+
+    fn decode32(s: &str) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&hex::decode(s).unwrap());
+        bytes
+    }
+
+    fn pub_key(x_only: &str) -> ExtendedPubKey {
+        let key = XOnlyPublicKey::from_slice(&hex::decode(x_only).unwrap()).unwrap();
+        ExtendedPubKey {
+            public_key: PublicKey::from_x_only_public_key(key, Parity::Even),
+            chain_code: vec![0; 32],
+        }
+    }
+
+    #[test]
+    fn test_bip340() {
+        // BIP-340 signing test vectors 0 to 3
+        for (secret_key, public_key, aux_rand, message, expected) in &[
+            ("0000000000000000000000000000000000000000000000000000000000000003", "f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9", "0000000000000000000000000000000000000000000000000000000000000000", "0000000000000000000000000000000000000000000000000000000000000000", "e907831f80848d1069a5371b402410364bdf1c5f8307b0084c55f1ce2dca821525f66a4a85ea8b71e482a74f382d2ce5ebeee8fdb2172f477df4900d310536c0"),
+            ("b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef", "dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659", "0000000000000000000000000000000000000000000000000000000000000001", "243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89", "6896bd60eeae296db48a229ff71dfe071bde413e6d43f917dc8dcf8c78de33418906d11ac976abccb20b091292bff4ea897efcb639ea871cfa95f6de339e4b0a"),
+            ("c90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74020bbea63b14e5c9", "dd308afec5777e13121fa72b9cc1b7cc0139715309b086c960e18fd969774eb8", "c87aa53824b4d7ae2eb035a2b5bbbccc080e76cdc6d1692c4b0b62d798e6d906", "7e2d58d8b3bcdf1abadec7829054f90dda9805aab56c77333024b9d0a508b75c", "5831aaeed7b44bb74e5eab94ba9d4294c49bcf2a60728d8b4c200f50dd313c1bab745879a5ad954a72c45a91c3a51d3c7adea98d82f8481e0e1e03674a6f3fb7"),
+            ("0b432b2677937381aef05bb02a66ecd012773062cf3fa2549e44f58ed2401710", "25d1dff95105f5253c4022f628a996ad3a0d95fbf21d468a1b33f8c160d8f517", "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", "7eb0509757e246f19449885651611cb965ecc1a187dd51b64fda1edc9637d5ec97582b9cb13db3933705b32ba982af5af25fd78881ebb32771fc5922efc66ea3"),
+        ] {
+            let priv_key = ExtendedPrivKey::new(
+                SecretKey::from_slice(&hex::decode(secret_key).unwrap()).unwrap(),
+                vec![0; 32],
+            );
+            assert_eq!(
+                hex::encode(priv_key.x_only_public_key().serialize()),
+                *public_key
+            );
+            let message = decode32(message);
+            let signature = priv_key.sign_schnorr(&message, Some(&decode32(aux_rand)));
+            assert_eq!(hex::encode(signature.as_ref()), *expected);
+            assert_eq!(
+                pub_key(public_key).verify_schnorr(&message, &signature),
+                Ok(())
+            );
+        }
+
+        // verification test vectors 4 and 6, the latter has an odd R
+        let message = "4df3c3f68fcc83b27e9d42c90431a72499f17875c81a599b566c9889b9696703";
+        let signature = Signature::from_slice(&hex::decode("00000000000000000000003b78ce563f89a0ed9414f5aa28ad0d96d6795f9c6376afb1548af603b3eb45c9f8207dee1060cb71c04e80f593060b07d28308d7f4").unwrap()).unwrap();
+        assert_eq!(
+            pub_key("d69c3509bb99e412e68b0fe8544e72837dfa30746d8be2aa65975f29d22dc7b9")
+                .verify_schnorr(&decode32(message), &signature),
+            Ok(())
+        );
+        let message = "243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89";
+        let signature = Signature::from_slice(&hex::decode("fff97bd5755eeea420453a14355235d382f6472f8568a18b2f057a14602975563cc27944640ac607cd107ae10923d9ef7a73c643e166be5ebeafa34b1ac553e2").unwrap()).unwrap();
+        assert!(
+            pub_key("dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659")
+                .verify_schnorr(&decode32(message), &signature)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_bip86() {
+        // BIP-86 test vectors of BIP-39 mnemonic `abandon abandon ... about`
+        let seed = hex::decode("5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc19a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4").unwrap();
+        let key_chain = DefaultKeyChain::new(ExtendedPrivKey::with_seed(&seed).unwrap());
+        for (chain_path, internal_key, output_key) in &[
+            (
+                "m/86H/0H/0H/0/0",
+                "cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115",
+                "a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c",
+            ),
+            (
+                "m/86H/0H/0H/0/1",
+                "83dfe85a3151d2517290da461fe2815591ef69f2b18a2ce63f01697a8b313145",
+                "a82f29944d65b86ae6b5e5cc75e294ead6c59391a1edc5e016e3498c67fc7bbb",
+            ),
+            (
+                "m/86H/0H/0H/1/0",
+                "399f1b2f4393f29a18c937859c5dd8a77350103157eb880f02e8c08214277cef",
+                "882d74e5d0572d5a816cef0041a96b6c1de832f6f9676d9605c44d5e9a97d3dc",
+            ),
+        ] {
+            let (priv_key, _) = key_chain.derive_private_key((*chain_path).into()).unwrap();
+            let pub_key = ExtendedPubKey::from_private_key(&priv_key);
+            assert_eq!(
+                hex::encode(pub_key.x_only_public_key().serialize()),
+                *internal_key
+            );
+            let (tweaked, _) = pub_key.tap_tweak(None).unwrap();
+            assert_eq!(hex::encode(tweaked.serialize()), *output_key);
+            assert_eq!(
+                priv_key.tap_tweak(None).unwrap().x_only_public_key().0,
+                tweaked
+            );
+
+            let message = [7; 32];
+            let signature = priv_key.sign_taproot(&message, None, None).unwrap();
+            assert_eq!(pub_key.verify_taproot(&message, &signature, None), Ok(()));
+            assert!(pub_key.verify_schnorr(&message, &signature).is_err());
+            assert!(pub_key
+                .verify_taproot(&message, &signature, Some(&[0; 32]))
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_script_tree_tweak() {
+        // BIP-341 wallet test vectors, without and with a script tree
+        for (internal_key, merkle_root, output_key) in &[
+            (
+                "d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d",
+                None,
+                "53a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343",
+            ),
+            (
+                "187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27",
+                Some("5b75adecf53548f3ec6ad7d78383bf84cc57b55a3127c72b9a2481752dd88b21"),
+                "147c9c57132f6e7ecddba9800bb0c4449251c92a1e60371ee77557b6620f3ea3",
+            ),
+        ] {
+            let merkle_root = merkle_root.map(decode32);
+            let (tweaked, _) = pub_key(internal_key)
+                .tap_tweak(merkle_root.as_ref())
+                .unwrap();
+            assert_eq!(hex::encode(tweaked.serialize()), *output_key);
+        }
+    }
+}
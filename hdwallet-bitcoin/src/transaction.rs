@@ -0,0 +1,277 @@
+use crate::{hash::sha256d, Error};
+use hdwallet::traits::{Deserialize, Serialize};
+
+/// Output spent by a transaction input, the txid is in internal byte order.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct OutPoint {
+    pub txid: [u8; 32],
+    pub vout: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TxIn {
+    pub previous_output: OutPoint,
+    pub script_sig: Vec<u8>,
+    pub sequence: u32,
+    pub witness: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TxOut {
+    /// Amount in satoshis.
+    pub value: u64,
+    pub script_pubkey: Vec<u8>,
+}
+
+/// Bitcoin transaction, serialized with the BIP-144 witness encoding when any input has a
+/// witness.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Transaction {
+    pub version: i32,
+    pub inputs: Vec<TxIn>,
+    pub outputs: Vec<TxOut>,
+    pub lock_time: u32,
+}
+
+impl Transaction {
+    /// Transaction id in internal byte order, displayed reversed.
+    pub fn txid(&self) -> [u8; 32] {
+        let mut buf = Vec::new();
+        self.encode(&mut buf, false);
+        sha256d(&buf)
+    }
+
+    pub(crate) fn encode(&self, buf: &mut Vec<u8>, with_witness: bool) {
+        let with_witness = with_witness && self.inputs.iter().any(|i| !i.witness.is_empty());
+        buf.extend_from_slice(&self.version.to_le_bytes());
+        if with_witness {
+            buf.extend_from_slice(&[0x00, 0x01]);
+        }
+        write_compact_size(buf, self.inputs.len() as u64);
+        for input in &self.inputs {
+            input.previous_output.encode(buf);
+            write_var_bytes(buf, &input.script_sig);
+            buf.extend_from_slice(&input.sequence.to_le_bytes());
+        }
+        write_compact_size(buf, self.outputs.len() as u64);
+        for output in &self.outputs {
+            output.encode(buf);
+        }
+        if with_witness {
+            for input in &self.inputs {
+                write_compact_size(buf, input.witness.len() as u64);
+                for item in &input.witness {
+                    write_var_bytes(buf, item);
+                }
+            }
+        }
+        buf.extend_from_slice(&self.lock_time.to_le_bytes());
+    }
+}
+
+impl OutPoint {
+    pub(crate) fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.txid);
+        buf.extend_from_slice(&self.vout.to_le_bytes());
+    }
+}
+
+impl TxOut {
+    pub(crate) fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.value.to_le_bytes());
+        write_var_bytes(buf, &self.script_pubkey);
+    }
+}
+
+impl Serialize<Vec<u8>> for Transaction {
+    fn serialize(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.encode(&mut buf, true);
+        buf
+    }
+}
+
+impl Deserialize<&[u8], Error> for Transaction {
+    fn deserialize(data: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(data);
+        let version = reader.read_u32()? as i32;
+        let mut input_count = reader.read_compact_size()?;
+        // BIP-144 marker and flag
+        let with_witness = input_count == 0;
+        if with_witness {
+            if reader.read_u8()? != 1 {
+                return Err(Error::InvalidTransaction);
+            }
+            input_count = reader.read_compact_size()?;
+        }
+        let mut inputs = Vec::new();
+        for _ in 0..input_count {
+            let mut txid = [0u8; 32];
+            txid.copy_from_slice(reader.read(32)?);
+            inputs.push(TxIn {
+                previous_output: OutPoint {
+                    txid,
+                    vout: reader.read_u32()?,
+                },
+                script_sig: reader.read_var_bytes()?.to_vec(),
+                sequence: reader.read_u32()?,
+                witness: Vec::new(),
+            });
+        }
+        let mut outputs = Vec::new();
+        for _ in 0..reader.read_compact_size()? {
+            outputs.push(TxOut {
+                value: reader.read_u64()?,
+                script_pubkey: reader.read_var_bytes()?.to_vec(),
+            });
+        }
+        if with_witness {
+            for input in &mut inputs {
+                for _ in 0..reader.read_compact_size()? {
+                    input.witness.push(reader.read_var_bytes()?.to_vec());
+                }
+            }
+            if inputs.iter().all(|input| input.witness.is_empty()) {
+                return Err(Error::InvalidTransaction);
+            }
+        }
+        let lock_time = reader.read_u32()?;
+        if !reader.is_empty() {
+            return Err(Error::InvalidTransaction);
+        }
+        Ok(Transaction {
+            version,
+            inputs,
+            outputs,
+            lock_time,
+        })
+    }
+}
+
+pub(crate) fn write_compact_size(buf: &mut Vec<u8>, n: u64) {
+    match n {
+        0..=0xfc => buf.push(n as u8),
+        0xfd..=0xffff => {
+            buf.push(0xfd);
+            buf.extend_from_slice(&(n as u16).to_le_bytes());
+        }
+        0x10000..=0xffff_ffff => {
+            buf.push(0xfe);
+            buf.extend_from_slice(&(n as u32).to_le_bytes());
+        }
+        _ => {
+            buf.push(0xff);
+            buf.extend_from_slice(&n.to_le_bytes());
+        }
+    }
+}
+
+pub(crate) fn write_var_bytes(buf: &mut Vec<u8>, data: &[u8]) {
+    write_compact_size(buf, data.len() as u64);
+    buf.extend_from_slice(data);
+}
+
+/// Cursor over serialized data, reading past the end is [`Error::InvalidLength`].
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Reader { data }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn read(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if len > self.data.len() {
+            return Err(Error::InvalidLength);
+        }
+        let (data, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(data)
+    }
+
+    /// Read the remaining data.
+    pub fn read_to_end(&mut self) -> &'a [u8] {
+        std::mem::take(&mut self.data)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, Error> {
+        Ok(self.read(1)?[0])
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, Error> {
+        let mut buf = [0u8; 4];
+        buf.copy_from_slice(self.read(4)?);
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, Error> {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(self.read(8)?);
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    /// Read a compact size, non-canonical encodings are rejected.
+    pub fn read_compact_size(&mut self) -> Result<u64, Error> {
+        let (n, min) = match self.read_u8()? {
+            0xfd => {
+                let mut buf = [0u8; 2];
+                buf.copy_from_slice(self.read(2)?);
+                (u64::from(u16::from_le_bytes(buf)), 0xfd)
+            }
+            0xfe => (u64::from(self.read_u32()?), 0x10000),
+            0xff => (self.read_u64()?, 0x1_0000_0000),
+            n => return Ok(u64::from(n)),
+        };
+        if n < min {
+            return Err(Error::InvalidLength);
+        }
+        Ok(n)
+    }
+
+    pub fn read_var_bytes(&mut self) -> Result<&'a [u8], Error> {
+        let len = self.read_compact_size()?;
+        self.read(usize::try_from(len).map_err(|_| Error::InvalidLength)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction() {
+        // BIP-143 native P2WPKH example, signed
+        let data = hex::decode("01000000000102fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f00000000494830450221008b9d1dc26ba6a9cb62127b02742fa9d754cd3bebf337f7a55d114c8e5cdd30be022040529b194ba3f9281a99f2b1c0a19c0489bc22ede944ccf4ecbab4cc618ef3ed01eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac000247304402203609e17b84f6a7d30c80bfa610b5b4542f32a8a0d5447a12fb1366d7f01cc44a0220573a954c4518331561406f90300e8f3358f51928d43c212a8caed02de67eebee0121025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee635711000000").unwrap();
+        let tx = Transaction::deserialize(data.as_slice()).expect("deserialize");
+        assert_eq!(tx.inputs.len(), 2);
+        assert!(tx.inputs[0].witness.is_empty());
+        assert_eq!(tx.inputs[1].witness.len(), 2);
+        assert_eq!(tx.outputs[1].value, 223_450_000);
+        assert_eq!(tx.lock_time, 17);
+        assert_eq!(Serialize::<Vec<u8>>::serialize(&tx), data);
+        let mut txid = tx.txid();
+        txid.reverse();
+        assert_eq!(
+            hex::encode(txid),
+            "e8151a2af31c368a35053ddd4bdb285a8595c769a3ad83e0fa02314a602d4609"
+        );
+
+        let mut truncated = data.clone();
+        truncated.pop();
+        assert_eq!(
+            Transaction::deserialize(truncated.as_slice()),
+            Err(Error::InvalidLength)
+        );
+        let mut trailing = data;
+        trailing.push(0);
+        assert_eq!(
+            Transaction::deserialize(trailing.as_slice()),
+            Err(Error::InvalidTransaction)
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use crate::{
+    secp256k1_sign_only,
+    serialize::{encode_checksum, verify_checksum},
+    Error, Network, PrivKey,
+};
+use base58::{FromBase58, ToBase58};
+use hdwallet::{
+    secp256k1::{PublicKey, SecretKey},
+    traits::{Deserialize, Serialize},
+    zeroize::Zeroize,
+};
+
+/// Single private key in Wallet Import Format.
+///
+/// Regtest and Signet share the testnet WIF prefix, so decoding without an expected network
+/// gives [`Network::TestNet`] for them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Wif {
+    pub(crate) network: Network,
+    pub(crate) private_key: SecretKey,
+    pub(crate) compressed: bool,
+}
+
+impl Wif {
+    /// WIF of a compressed public key, the form used by all keys derived from BIP-32 wallets.
+    ///
+    /// [`Error::UnknownVersion`] for a [`Network::Unknown`] network, which has no WIF prefix.
+    pub fn new(private_key: SecretKey, network: Network) -> Result<Self, Error> {
+        network.params()?;
+        Ok(Wif {
+            network,
+            private_key,
+            compressed: true,
+        })
+    }
+
+    /// Same private key with its public key used in compressed form or not.
+    pub fn with_compressed(self, compressed: bool) -> Self {
+        Wif { compressed, ..self }
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    pub fn private_key(&self) -> SecretKey {
+        self.private_key
+    }
+
+    /// Whether the public key of this private key is used in compressed form.
+    pub fn compressed(&self) -> bool {
+        self.compressed
+    }
+
+    /// Public key of the private key.
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey::from_secret_key(secp256k1_sign_only(), &self.private_key)
+    }
+
+    /// Decode a WIF of `network`, the only way to decode a WIF of a custom network.
+    pub fn parse(s: &str, network: Network) -> Result<Self, Error> {
+        let data = s.from_base58().map_err(|_| Error::InvalidBase58)?;
+        Wif::decode(&data, &[network])
+    }
+
+    fn decode(data: &[u8], networks: &[Network]) -> Result<Self, Error> {
+        let payload = verify_checksum(data)?;
+        let compressed = match payload.len() {
+            33 => false,
+            34 if payload[33] == 1 => true,
+            _ => return Err(Error::InvalidLength),
+        };
+        let network = networks
+            .iter()
+            .find(
+                |network| matches!(network.params(), Ok(params) if params.wif_prefix == payload[0]),
+            )
+            .ok_or(Error::UnknownNetworkByte(payload[0]))?;
+        Ok(Wif {
+            network: *network,
+            private_key: SecretKey::from_slice(&payload[1..33])?,
+            compressed,
+        })
+    }
+}
+
+impl Serialize<Vec<u8>> for Wif {
+    fn serialize(&self) -> Vec<u8> {
+        let mut buf: Vec<u8> = Vec::with_capacity(38);
+        // checked on construction
+        buf.push(
+            self.network
+                .params()
+                .expect("network parameters")
+                .wif_prefix,
+        );
+        buf.extend_from_slice(&self.private_key[..]);
+        if self.compressed {
+            buf.push(1);
+        }
+        encode_checksum(&mut buf);
+        buf
+    }
+}
+
+impl Serialize<String> for Wif {
+    fn serialize(&self) -> String {
+        let mut data: Vec<u8> = self.serialize();
+        let wif = data.to_base58();
+        data.zeroize();
+        wif
+    }
+}
+
+impl Deserialize<Vec<u8>, Error> for Wif {
+    fn deserialize(data: Vec<u8>) -> Result<Wif, Error> {
+        Wif::decode(&data, &[Network::MainNet, Network::TestNet])
+    }
+}
+
+impl Deserialize<String, Error> for Wif {
+    fn deserialize(data: String) -> Result<Wif, Error> {
+        let data = data.from_base58().map_err(|_| Error::InvalidBase58)?;
+        Wif::deserialize(data)
+    }
+}
+
+impl Zeroize for Wif {
+    fn zeroize(&mut self) {
+        self.private_key.non_secure_erase();
+    }
+}
+
+impl PrivKey {
+    /// The private key of this extended key in Wallet Import Format, [`Error::UnknownVersion`]
+    /// for a key decoded with unregistered version bytes.
+    pub fn to_wif(&self) -> Result<Wif, Error> {
+        Wif::new(self.extended_key.private_key(), self.network)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hdwallet::ExtendedPrivKey;
+
+    fn secret_key(hex: &str) -> SecretKey {
+        SecretKey::from_slice(&hex::decode(hex).expect("hex")).expect("secret key")
+    }
+
+    #[test]
+    fn test_wif() {
+        let private_key =
+            secret_key("0c28fca386c7a227600b2fe50b7cae11ec86d3bf1fbe471be89827e19d72aa1d");
+        for (compressed, expected) in &[
+            (false, "5HueCGU8rMjxEXxiPuD5BDku4MkFqeZyd4dZ1jvhTVqvbTLvyTJ"),
+            (true, "KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617"),
+        ] {
+            let wif = Wif {
+                network: Network::MainNet,
+                private_key,
+                compressed: *compressed,
+            };
+            let serialized: String = wif.serialize();
+            assert_eq!(&serialized, expected);
+            assert_eq!(Wif::deserialize(serialized.clone()), Ok(wif.clone()));
+            assert_eq!(Wif::parse(&serialized, Network::MainNet), Ok(wif));
+            assert_eq!(
+                Wif::parse(&serialized, Network::TestNet),
+                Err(Error::UnknownNetworkByte(0x80))
+            );
+        }
+
+        let wif = Wif::new(private_key, Network::Regtest).expect("wif");
+        let serialized: String = wif.serialize();
+        assert!(serialized.starts_with('c'));
+        assert_eq!(
+            Wif::deserialize(serialized.clone()).map(|wif| wif.network),
+            Ok(Network::TestNet)
+        );
+        assert_eq!(Wif::parse(&serialized, Network::Regtest), Ok(wif));
+    }
+
+    #[test]
+    fn test_priv_key_to_wif() {
+        // BIP-32 test vector 1, chain m
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").expect("hex");
+        let master_key = ExtendedPrivKey::with_seed(&seed).expect("master key");
+        let key = PrivKey::from_master_key(master_key, Network::MainNet);
+        let wif: String = key.to_wif().expect("wif").serialize();
+        assert_eq!(wif, "L52XzL2cMkHxqxBXRyEpnPQZGUs3uKiL3R11XbAdHigRzDozKZeW");
+    }
+
+    #[test]
+    fn test_invalid_wif() {
+        let private_key =
+            secret_key("0c28fca386c7a227600b2fe50b7cae11ec86d3bf1fbe471be89827e19d72aa1d");
+        let wif = Wif::new(private_key, Network::MainNet).expect("wif");
+        let mut data: Vec<u8> = wif.serialize();
+
+        // unknown network byte
+        data[0] = 0x30;
+        data.truncate(34);
+        encode_checksum(&mut data);
+        assert_eq!(
+            Wif::deserialize(data.clone()),
+            Err(Error::UnknownNetworkByte(0x30))
+        );
+
+        // compression flag other than 0x01
+        data[0] = 0x80;
+        data[33] = 2;
+        data.truncate(34);
+        encode_checksum(&mut data);
+        assert_eq!(Wif::deserialize(data.clone()), Err(Error::InvalidLength));
+
+        data[37] ^= 1;
+        assert_eq!(Wif::deserialize(data), Err(Error::MisChecksum));
+        assert_eq!(
+            Wif::deserialize("0OIl".to_string()),
+            Err(Error::InvalidBase58)
+        );
+    }
+}
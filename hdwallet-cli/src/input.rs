@@ -0,0 +1,66 @@
+//! Secrets and keys are read from stdin so they stay out of shell history.
+
+use crate::Result;
+use bip39::Mnemonic;
+use hdwallet::{traits::Deserialize, zeroize::Zeroize, ExtendedPrivKey};
+use hdwallet_bitcoin::{Network, PrivKey, PubKey};
+use std::io::{self, Read};
+
+/// Private or public extended key.
+pub enum Key {
+    Private(PrivKey),
+    Public(PubKey),
+}
+
+impl Key {
+    /// Decode a base58 xprv/xpub, the key type is detected by its version.
+    pub fn parse(s: &str) -> Result<Key> {
+        let s = s.trim();
+        match PrivKey::deserialize(s.to_string()) {
+            Ok(key) => Ok(Key::Private(key)),
+            Err(hdwallet_bitcoin::Error::UnknownVersion) => {
+                Ok(Key::Public(PubKey::deserialize(s.to_string())?))
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// Read all of stdin, the caller should zeroize the returned string.
+pub fn read_stdin() -> Result<String> {
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input)?;
+    Ok(input)
+}
+
+/// Read a single line from stdin, e.g. an extended key.
+pub fn read_line() -> Result<String> {
+    let mut input = read_stdin()?;
+    let line = input.lines().next().unwrap_or_default().trim().to_string();
+    input.zeroize();
+    if line.is_empty() {
+        return Err("expected input on stdin".into());
+    }
+    Ok(line)
+}
+
+/// Master key of a hex seed.
+pub fn master_key_from_seed(seed_hex: &str, network: Network) -> Result<PrivKey> {
+    let mut seed = hex::decode(seed_hex.trim()).map_err(|_| "seed must be hex encoded")?;
+    let extended_key = ExtendedPrivKey::with_seed(&seed);
+    seed.zeroize();
+    Ok(PrivKey::from_master_key(extended_key?, network))
+}
+
+/// Master key of a BIP-39 mnemonic and optional passphrase.
+pub fn master_key_from_mnemonic(
+    mnemonic: &str,
+    passphrase: &str,
+    network: Network,
+) -> Result<PrivKey> {
+    let mnemonic = Mnemonic::parse(mnemonic.trim())?;
+    let mut seed = mnemonic.to_seed(passphrase);
+    let extended_key = ExtendedPrivKey::with_seed(&seed);
+    seed.zeroize();
+    Ok(PrivKey::from_master_key(extended_key?, network))
+}
@@ -0,0 +1,395 @@
+//! `hdwallet` command-line tool.
+//!
+//! Secrets (seeds, mnemonics, xprv) are always read from stdin:
+//!
+//! ``` bash
+//! hdwallet generate --mnemonic > mnemonic.txt
+//! hdwallet derive --from mnemonic m/84H/0H/0H < mnemonic.txt
+//! echo xpub... | hdwallet inspect --format json
+//! echo xpub... | hdwallet export m/0/0-99 --format csv > addresses.csv
+//! ```
+
+mod input;
+mod output;
+mod template;
+
+use bip39::{Language, Mnemonic};
+use clap::{Parser, Subcommand, ValueEnum};
+use hdwallet::{zeroize::Zeroize, ExtendedPrivKey, ExtendedPubKey, KeyIndex, KeySeed};
+use hdwallet_bitcoin::{AddressType, Network, PrivKey, PubKey, ScriptType};
+use input::Key;
+use output::{Format, Report, Table};
+use serde_json::Value;
+use std::{
+    io::{self, Write},
+    process,
+};
+use template::PathTemplate;
+
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Parser)]
+#[clap(
+    name = "hdwallet",
+    version,
+    about = "BIP-32 key derivation and inspection"
+)]
+struct Cli {
+    /// Output format
+    #[clap(long, short, global = true, value_enum, default_value = "text")]
+    format: Format,
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate a random master key, or a BIP-39 mnemonic and its master key
+    Generate {
+        /// Generate a BIP-39 mnemonic instead of a raw seed
+        #[clap(long)]
+        mnemonic: bool,
+        /// Number of mnemonic words
+        #[clap(long, default_value = "24", requires = "mnemonic")]
+        words: usize,
+        /// Read the mnemonic passphrase from stdin
+        #[clap(long, requires = "mnemonic")]
+        passphrase: bool,
+        /// Bits of the random seed
+        #[clap(long, default_value = "256", conflicts_with = "mnemonic")]
+        seed_bits: u32,
+        #[clap(long, value_enum, default_value = "mainnet")]
+        network: NetworkArg,
+    },
+    /// Derive PATH from a seed, mnemonic or extended key read from stdin
+    Derive {
+        /// Chain path, `m` denotes the input key, e.g. m/44H/0H/0H
+        path: String,
+        /// Kind of the stdin input, a mnemonic may be followed by a passphrase line
+        #[clap(long, value_enum, default_value = "key")]
+        from: Source,
+        /// Only print the public key
+        #[clap(long)]
+        public: bool,
+        /// Network of the master key derived from a seed or mnemonic
+        #[clap(long, value_enum, default_value = "mainnet")]
+        network: NetworkArg,
+    },
+    /// Convert an xprv read from stdin to xpub
+    Public,
+    /// Print the fields of an extended key read from stdin
+    Inspect,
+    /// Export the public key and address of each child of an xpub or xprv read from stdin
+    Export {
+        /// Chain path with one inclusive range, e.g. m/0/0-19 or m/0-4H/0
+        template: PathTemplate,
+        /// Address type of the exported addresses, implied by the SLIP-132 version by default
+        #[clap(long, value_enum)]
+        address_type: Option<AddressTypeArg>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Source {
+    /// Hex encoded seed
+    Seed,
+    /// BIP-39 mnemonic
+    Mnemonic,
+    /// xprv or xpub
+    Key,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum NetworkArg {
+    Mainnet,
+    Testnet,
+    Regtest,
+    Signet,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum AddressTypeArg {
+    /// Legacy, BIP-44
+    P2pkh,
+    /// Nested SegWit, BIP-49
+    P2shP2wpkh,
+    /// Native SegWit, BIP-84
+    P2wpkh,
+    /// Taproot, BIP-86
+    P2tr,
+}
+
+impl From<AddressTypeArg> for AddressType {
+    fn from(address_type: AddressTypeArg) -> Self {
+        match address_type {
+            AddressTypeArg::P2pkh => AddressType::P2pkh,
+            AddressTypeArg::P2shP2wpkh => AddressType::P2shP2wpkh,
+            AddressTypeArg::P2wpkh => AddressType::P2wpkh,
+            AddressTypeArg::P2tr => AddressType::P2tr,
+        }
+    }
+}
+
+impl From<NetworkArg> for Network {
+    fn from(network: NetworkArg) -> Self {
+        match network {
+            NetworkArg::Mainnet => Network::MainNet,
+            NetworkArg::Testnet => Network::TestNet,
+            NetworkArg::Regtest => Network::Regtest,
+            NetworkArg::Signet => Network::Signet,
+        }
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let stdout = io::stdout();
+    let result = run(cli.command, cli.format, &mut stdout.lock());
+    if let Err(err) = result {
+        // stdout closed early, e.g. piped to `head`
+        let broken_pipe = matches!(
+            err.downcast_ref::<io::Error>(),
+            Some(err) if err.kind() == io::ErrorKind::BrokenPipe
+        );
+        if !broken_pipe {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+fn run(command: Command, format: Format, w: &mut impl Write) -> Result<()> {
+    let report = match command {
+        Command::Generate {
+            mnemonic,
+            words,
+            passphrase,
+            seed_bits,
+            network,
+        } => {
+            if mnemonic {
+                generate_mnemonic(words, passphrase, network.into())
+            } else {
+                generate(seed_bits, network.into())
+            }
+        }
+        Command::Derive {
+            path,
+            from,
+            public,
+            network,
+        } => derive(&path, from, public, network.into()),
+        Command::Public => match Key::parse(&input::read_line()?)? {
+            Key::Private(key) => Ok(pub_key_report(
+                Report::default(),
+                &PubKey::from_private_key(&key)?,
+            )),
+            Key::Public(_) => Err("expected an xprv".into()),
+        },
+        Command::Inspect => inspect(&input::read_line()?),
+        Command::Export {
+            template,
+            address_type,
+        } => {
+            return export(
+                &template,
+                address_type.map(Into::into),
+                Table::new(w, format),
+            );
+        }
+    }?;
+    Ok(report.write(w, format)?)
+}
+
+fn generate(seed_bits: u32, network: Network) -> Result<Report> {
+    let seed_size = match seed_bits {
+        128 => KeySeed::S128,
+        256 => KeySeed::S256,
+        512 => KeySeed::S512,
+        _ => return Err("seed bits must be 128, 256 or 512".into()),
+    };
+    let extended_key = ExtendedPrivKey::random_with_seed_size(&mut rand::thread_rng(), seed_size)?;
+    priv_key_report(
+        Report::default(),
+        &PrivKey::from_master_key(extended_key, network),
+    )
+}
+
+fn generate_mnemonic(words: usize, passphrase: bool, network: Network) -> Result<Report> {
+    let mnemonic = Mnemonic::generate_in_with(&mut rand::thread_rng(), Language::English, words)?;
+    let mut passphrase = if passphrase {
+        input::read_line()?
+    } else {
+        String::new()
+    };
+    let mut phrase = mnemonic.to_string();
+    let key = input::master_key_from_mnemonic(&phrase, &passphrase, network);
+    passphrase.zeroize();
+    let report = priv_key_report(Report::default().field("mnemonic", phrase.as_str()), &key?);
+    phrase.zeroize();
+    report
+}
+
+fn derive(path: &str, from: Source, public: bool, network: Network) -> Result<Report> {
+    let mut input = input::read_stdin()?;
+    let key = match from {
+        Source::Seed => input::master_key_from_seed(&input, network).map(Key::Private),
+        Source::Mnemonic => {
+            let mut lines = input.lines();
+            let mnemonic = lines.next().unwrap_or_default();
+            let passphrase = lines.next().unwrap_or_default();
+            input::master_key_from_mnemonic(mnemonic, passphrase, network).map(Key::Private)
+        }
+        Source::Key => Key::parse(&input),
+    };
+    input.zeroize();
+    let report = Report::default().field("path", path);
+    match key? {
+        Key::Private(key) => {
+            let child = key.derive_path(path.into())?;
+            if public {
+                Ok(pub_key_report(report, &PubKey::from_private_key(&child)?))
+            } else {
+                priv_key_report(report, &child)
+            }
+        }
+        Key::Public(key) => Ok(pub_key_report(report, &key.derive_path(path.into())?)),
+    }
+}
+
+fn export(
+    template: &PathTemplate,
+    address_type: Option<AddressType>,
+    mut table: Table<impl Write>,
+) -> Result<()> {
+    let prefix = template.prefix.as_str();
+    let key = Key::parse(&input::read_line()?)?;
+    let script_type = match &key {
+        Key::Private(key) => key.script_type,
+        Key::Public(key) => key.script_type,
+    };
+    let address_type = address_type
+        .or_else(|| script_type.address_type())
+        .ok_or("multisig key, specify --address-type")?;
+    // hardened children are derived from the private key, only public keys are exported
+    match key {
+        Key::Private(key) => {
+            let parent = key.derive_path(prefix.into())?;
+            for (index, path) in template.children() {
+                let child = PubKey::from_private_key(&parent.derive_path(path.as_str().into())?)?;
+                table.push(&export_report(
+                    template,
+                    index,
+                    &path,
+                    &child,
+                    address_type,
+                )?)?;
+            }
+        }
+        Key::Public(key) => {
+            let parent = key.derive_path(prefix.into())?;
+            for (index, path) in template.children() {
+                let child = parent.derive_path(path.as_str().into())?;
+                table.push(&export_report(
+                    template,
+                    index,
+                    &path,
+                    &child,
+                    address_type,
+                )?)?;
+            }
+        }
+    }
+    Ok(table.finish()?)
+}
+
+fn export_report(
+    template: &PathTemplate,
+    index: String,
+    path: &str,
+    key: &PubKey,
+    address_type: AddressType,
+) -> Result<Report> {
+    Ok(Report::default()
+        .field("index", index)
+        .field("path", template.full_path(path))
+        .field(
+            "public_key",
+            hex::encode(key.extended_key.public_key.serialize()),
+        )
+        .field("address", key.address(address_type)?.to_string()))
+}
+
+fn priv_key_report(report: Report, key: &PrivKey) -> Result<Report> {
+    use hdwallet::traits::Serialize;
+
+    let report = report.field("xprv", Serialize::<String>::serialize(key));
+    Ok(pub_key_report(report, &PubKey::from_private_key(key)?))
+}
+
+fn pub_key_report(report: Report, key: &PubKey) -> Report {
+    use hdwallet::traits::Serialize;
+
+    report
+        .field("xpub", Serialize::<String>::serialize(key))
+        .field("fingerprint", hex::encode(key.fingerprint()))
+        .field("parent_fingerprint", hex::encode(key.parent_fingerprint()))
+        .field("depth", key.derivation.depth)
+}
+
+fn inspect(s: &str) -> Result<Report> {
+    use base58::FromBase58;
+
+    let key = Key::parse(s)?;
+    // the key is valid, so the prefix is its version bytes
+    let version = s.from_base58().map_err(|_| "invalid base58")?[..4].to_vec();
+    let (key_type, pub_key) = match key {
+        // built by hand, keys with unknown versions have no public key version to convert to
+        Key::Private(key) => (
+            "private",
+            PubKey {
+                network: key.network,
+                script_type: key.script_type,
+                extended_key: ExtendedPubKey::from_private_key(&key.extended_key),
+                derivation: key.derivation,
+                parent_fingerprint: key.parent_fingerprint,
+            },
+        ),
+        Key::Public(key) => ("public", key),
+    };
+    let child_index = match pub_key.derivation.key_index {
+        Some(index @ KeyIndex::Hardened(_)) => Value::from(format!("{}H", index.normalize_index())),
+        Some(KeyIndex::Normal(i)) => Value::from(i.to_string()),
+        None => Value::Null,
+    };
+    let network = match pub_key.network {
+        Network::MainNet => "mainnet",
+        // regtest and signet keys use the testnet versions
+        Network::TestNet | Network::Regtest | Network::Signet => "testnet",
+        Network::Custom(_) | Network::Unknown(_) => "unknown",
+    };
+    let script_type = match pub_key.script_type {
+        ScriptType::Legacy => "legacy",
+        ScriptType::P2shP2wpkh => "p2sh-p2wpkh",
+        ScriptType::P2shP2wsh => "p2sh-p2wsh",
+        ScriptType::P2wpkh => "p2wpkh",
+        ScriptType::P2wsh => "p2wsh",
+    };
+    Ok(Report::default()
+        .field("version", hex::encode(version))
+        .field("type", key_type)
+        .field("network", network)
+        .field("script_type", script_type)
+        .field("depth", pub_key.derivation.depth)
+        .field("fingerprint", hex::encode(pub_key.fingerprint()))
+        .field(
+            "parent_fingerprint",
+            hex::encode(pub_key.parent_fingerprint()),
+        )
+        .field("child_index", child_index)
+        .field("chain_code", hex::encode(&pub_key.extended_key.chain_code))
+        .field(
+            "public_key",
+            hex::encode(pub_key.extended_key.public_key.serialize()),
+        ))
+}
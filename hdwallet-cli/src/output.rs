@@ -0,0 +1,165 @@
+use clap::ValueEnum;
+use serde_json::{Map, Value};
+use std::{
+    fmt,
+    io::{self, Write},
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+    /// Comma-separated values with a header line
+    Csv,
+    /// JSON Lines, one object per line
+    Jsonl,
+}
+
+/// Ordered fields of a command result, printed as `name: value` lines or a JSON object.
+#[derive(Default)]
+pub struct Report(Vec<(&'static str, Value)>);
+
+impl Report {
+    pub fn field(mut self, name: &'static str, value: impl Into<Value>) -> Self {
+        self.0.push((name, value.into()));
+        self
+    }
+
+    pub fn write(&self, w: &mut impl Write, format: Format) -> io::Result<()> {
+        match format {
+            Format::Text => write!(w, "{}", self),
+            Format::Json | Format::Jsonl => writeln!(w, "{}", self.to_json()),
+            Format::Csv => {
+                self.write_csv_header(w)?;
+                self.write_csv_row(w)
+            }
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        let object: Map<String, Value> = self
+            .0
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect();
+        Value::Object(object)
+    }
+
+    fn write_csv_header(&self, w: &mut impl Write) -> io::Result<()> {
+        let names: Vec<&str> = self.0.iter().map(|(name, _)| *name).collect();
+        writeln!(w, "{}", names.join(","))
+    }
+
+    fn write_csv_row(&self, w: &mut impl Write) -> io::Result<()> {
+        let values: Vec<String> = self
+            .0
+            .iter()
+            .map(|(_, value)| match value {
+                Value::Null => String::new(),
+                Value::String(s) if s.contains(&[',', '"', '\n'][..]) => {
+                    format!("\"{}\"", s.replace('"', "\"\""))
+                }
+                Value::String(s) => s.clone(),
+                value => value.to_string(),
+            })
+            .collect();
+        writeln!(w, "{}", values.join(","))
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, value) in &self.0 {
+            match value {
+                Value::String(s) => writeln!(f, "{}: {}", name, s)?,
+                Value::Null => writeln!(f, "{}: -", name)?,
+                value => writeln!(f, "{}: {}", name, value)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes reports with the same fields as they are produced, e.g. one per derived child.
+///
+/// Text records are separated by a blank line, JSON is an array and CSV has a single header.
+pub struct Table<W: Write> {
+    w: W,
+    format: Format,
+    rows: usize,
+}
+
+impl<W: Write> Table<W> {
+    pub fn new(w: W, format: Format) -> Self {
+        Table { w, format, rows: 0 }
+    }
+
+    pub fn push(&mut self, report: &Report) -> io::Result<()> {
+        let first = self.rows == 0;
+        self.rows += 1;
+        match self.format {
+            Format::Text => {
+                if !first {
+                    writeln!(self.w)?;
+                }
+                write!(self.w, "{}", report)
+            }
+            Format::Json => {
+                let separator = if first { "[\n" } else { ",\n" };
+                write!(self.w, "{}  {}", separator, report.to_json())
+            }
+            Format::Jsonl => writeln!(self.w, "{}", report.to_json()),
+            Format::Csv => {
+                if first {
+                    report.write_csv_header(&mut self.w)?;
+                }
+                report.write_csv_row(&mut self.w)
+            }
+        }
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        if self.format == Format::Json {
+            let end = if self.rows == 0 { "[]" } else { "\n]" };
+            writeln!(self.w, "{}", end)?;
+        }
+        self.w.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(format: Format) -> String {
+        let mut buf = Vec::new();
+        let mut table = Table::new(&mut buf, format);
+        for i in 0..2 {
+            let report = Report::default()
+                .field("index", i)
+                .field("label", format!("a,\"{}\"", i))
+                .field("parent", Value::Null);
+            table.push(&report).expect("push");
+        }
+        table.finish().expect("finish");
+        String::from_utf8(buf).expect("utf8")
+    }
+
+    #[test]
+    fn write_table() {
+        assert_eq!(
+            table(Format::Csv),
+            "index,label,parent\n0,\"a,\"\"0\"\"\",\n1,\"a,\"\"1\"\"\",\n"
+        );
+        assert_eq!(
+            table(Format::Jsonl),
+            "{\"index\":0,\"label\":\"a,\\\"0\\\"\",\"parent\":null}\n{\"index\":1,\"label\":\"a,\\\"1\\\"\",\"parent\":null}\n"
+        );
+        let json: Value = serde_json::from_str(&table(Format::Json)).expect("json");
+        assert_eq!(json.as_array().expect("array").len(), 2);
+        assert_eq!(
+            table(Format::Text),
+            "index: 0\nlabel: a,\"0\"\nparent: -\n\nindex: 1\nlabel: a,\"1\"\nparent: -\n"
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use std::{ops::RangeInclusive, str::FromStr};
+
+/// Chain path with one inclusive range component, e.g. `m/0/0-19` or `m/44H/0H/0-4H/0/0`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PathTemplate {
+    /// Path up to the range component, starting with `m`.
+    pub prefix: String,
+    pub range: RangeInclusive<u32>,
+    pub hardened: bool,
+    /// Path components after the range component, each starting with `/`.
+    pub suffix: String,
+}
+
+impl PathTemplate {
+    /// Expand the template to `(child index, path)`, the path starting with `m` is relative to
+    /// the prefix key.
+    pub fn children(&self) -> impl Iterator<Item = (String, String)> + '_ {
+        self.range.clone().map(move |i| {
+            let index = if self.hardened {
+                format!("{}H", i)
+            } else {
+                i.to_string()
+            };
+            let path = format!("m/{}{}", index, self.suffix);
+            (index, path)
+        })
+    }
+
+    /// Full path of a child returned by [`children`](Self::children).
+    pub fn full_path(&self, child_path: &str) -> String {
+        format!("{}{}", self.prefix, &child_path[1..])
+    }
+}
+
+impl FromStr for PathTemplate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let components: Vec<&str> = s.split('/').collect();
+        if components[0] != "m" {
+            return Err("path template must start with m".into());
+        }
+        let mut ranges = components
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.contains('-'));
+        let (pos, range) = match (ranges.next(), ranges.next()) {
+            (Some(range), None) => range,
+            _ => return Err("path template needs exactly one range, e.g. m/0/0-19".into()),
+        };
+        let (range, hardened) = match range.strip_suffix(|c| c == 'H' || c == '\'') {
+            Some(range) => (range, true),
+            None => (*range, false),
+        };
+        let (start, end) = range.split_once('-').expect("range");
+        let parse = |i: &str| {
+            i.parse::<u32>()
+                .ok()
+                .filter(|i| *i < 1 << 31)
+                .ok_or_else(|| format!("invalid range {}", range))
+        };
+        let range = parse(start)?..=parse(end)?;
+        if range.is_empty() {
+            return Err("range start must not exceed its end".into());
+        }
+        Ok(PathTemplate {
+            prefix: components[..pos].join("/"),
+            range,
+            hardened,
+            suffix: components[pos + 1..]
+                .iter()
+                .map(|c| format!("/{}", c))
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_template() {
+        let template: PathTemplate = "m/44H/0H/0-1H/0/7".parse().expect("parse");
+        assert_eq!(template.prefix, "m/44H/0H");
+        assert_eq!(template.range, 0..=1);
+        assert!(template.hardened);
+        assert_eq!(template.suffix, "/0/7");
+        let children: Vec<_> = template.children().collect();
+        assert_eq!(
+            children,
+            vec![
+                ("0H".to_string(), "m/0H/0/7".to_string()),
+                ("1H".to_string(), "m/1H/0/7".to_string())
+            ]
+        );
+        assert_eq!(template.full_path(&children[1].1), "m/44H/0H/1H/0/7");
+
+        let template: PathTemplate = "m/3-5".parse().expect("parse");
+        assert_eq!(template.prefix, "m");
+        assert_eq!(template.full_path("m/3"), "m/3");
+
+        for invalid in &[
+            "0/0-1",
+            "m/0",
+            "m/0-1/0-1",
+            "m/2-1",
+            "m/0-2147483648",
+            "m/a-1",
+        ] {
+            assert!(invalid.parse::<PathTemplate>().is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+// BIP-32 test vector 1
+const SEED: &str = "000102030405060708090a0b0c0d0e0f";
+const XPRV_0H_1: &str = "xprv9wTYmMFdV23N2TdNG573QoEsfRrWKQgWeibmLntzniatZvR9BmLnvSxqu53Kw1UmYPxLgboyZQaXwTCg8MSY3H2EU4pWcQDnRnrVA1xe8fs";
+const XPUB_0H_1: &str = "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ";
+
+fn hdwallet(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_hdwallet"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn hdwallet");
+    // the process may exit on invalid arguments without reading stdin
+    let _ = child.stdin.take().unwrap().write_all(stdin.as_bytes());
+    child.wait_with_output().expect("hdwallet output")
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout.clone()).expect("utf8")
+}
+
+fn json(output: &Output) -> serde_json::Value {
+    serde_json::from_str(&stdout(output)).expect("json")
+}
+
+#[test]
+fn derive_from_seed() {
+    let output = stdout(&hdwallet(&["derive", "--from", "seed", "m/0H/1/2H"], SEED));
+    assert!(output.contains("xprv: xprv9z4pot5VBttmtdRTWfWQmoH1taj2axGVzFqSb8C9xaxKymcFzXBDptWmT7FwuEzG3ryjH4ktypQSAewRiNMjANTtpgP4mLTj34bhnZX7UiM\n"));
+    assert!(output.contains("xpub: xpub6D4BDPcP2GT577Vvch3R8wDkScZWzQzMMUm3PWbmWvVJrZwQY4VUNgqFJPMM3No2dFDFGTsxxpG5uJh7n7epu4trkrX7x7DogT5Uv6fcLW5\n"));
+    assert!(output.contains("parent_fingerprint: bef5a2f9\n"));
+
+    let output = json(&hdwallet(
+        &[
+            "derive", "--from", "seed", "--public", "-f", "json", "m/0H/1",
+        ],
+        SEED,
+    ));
+    assert_eq!(output["xpub"], XPUB_0H_1);
+    assert!(output.get("xprv").is_none());
+}
+
+#[test]
+fn derive_from_mnemonic() {
+    // BIP-39 test vector with passphrase TREZOR
+    let output = json(&hdwallet(
+        &["derive", "--from", "mnemonic", "-f", "json", "m"],
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about\nTREZOR\n",
+    ));
+    assert_eq!(output["xprv"], "xprv9s21ZrQH143K3h3fDYiay8mocZ3afhfULfb5GX8kCBdno77K4HiA15Tg23wpbeF1pLfs1c5SPmYHrEpTuuRhxMwvKDwqdKiGJS9XFKzUsAF");
+}
+
+#[test]
+fn derive_from_extended_key() {
+    let output = json(&hdwallet(&["derive", "-f", "json", "m/2H/2"], XPRV_0H_1));
+    assert_eq!(output["xprv"], "xprvA2JDeKCSNNZky6uBCviVfJSKyQ1mDYahRjijr5idH2WwLsEd4Hsb2Tyh8RfQMuPh7f7RtyzTtdrbdqqsunu5Mm3wDvUAKRHSC34sJ7in334");
+    assert_eq!(output["depth"], 4);
+
+    let output = hdwallet(&["derive", "m/0H"], XPUB_0H_1);
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "error: Key index out of range\n"
+    );
+}
+
+#[test]
+fn public_and_inspect() {
+    let output = stdout(&hdwallet(&["public"], XPRV_0H_1));
+    assert!(output.starts_with(&format!("xpub: {}\n", XPUB_0H_1)));
+    assert!(!hdwallet(&["public"], XPUB_0H_1).status.success());
+
+    let output = json(&hdwallet(&["inspect", "--format", "json"], XPRV_0H_1));
+    assert_eq!(output["version"], "0488ade4");
+    assert_eq!(output["type"], "private");
+    assert_eq!(output["network"], "mainnet");
+    assert_eq!(output["script_type"], "legacy");
+    assert_eq!(output["depth"], 2);
+    assert_eq!(output["fingerprint"], "bef5a2f9");
+    assert_eq!(output["parent_fingerprint"], "5c1bd648");
+    assert_eq!(output["child_index"], "1");
+    assert_eq!(
+        output["chain_code"],
+        "2a7857631386ba23dacac34180dd1983734e444fdbf774041578e9b6adb37c19"
+    );
+
+    let output = json(&hdwallet(&["inspect", "-f", "json"], XPUB_0H_1));
+    assert_eq!(output["version"], "0488b21e");
+    assert_eq!(output["type"], "public");
+
+    // SLIP-132 zpub of BIP-84 account m/84H/0H/0H
+    let output = json(&hdwallet(
+        &["inspect", "-f", "json"],
+        "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs",
+    ));
+    assert_eq!(output["version"], "04b24746");
+    assert_eq!(output["script_type"], "p2wpkh");
+}
+
+#[test]
+fn generate() {
+    let output = json(&hdwallet(
+        &["generate", "--mnemonic", "--words", "12", "-f", "json"],
+        "",
+    ));
+    let mnemonic = output["mnemonic"].as_str().expect("mnemonic");
+    assert_eq!(mnemonic.split(' ').count(), 12);
+    // the printed master key is derived from the printed mnemonic
+    let derived = json(&hdwallet(
+        &["derive", "--from", "mnemonic", "-f", "json", "m"],
+        mnemonic,
+    ));
+    assert_eq!(derived["xprv"], output["xprv"]);
+
+    let output = json(&hdwallet(
+        &["generate", "--network", "testnet", "-f", "json"],
+        "",
+    ));
+    assert!(output["xprv"].as_str().unwrap().starts_with("tprv"));
+}
+
+#[test]
+fn export() {
+    let output = stdout(&hdwallet(
+        &["export", "-f", "csv", "m/2H/2/1000000000-1000000001"],
+        XPRV_0H_1,
+    ));
+    assert_eq!(
+        output,
+        "index,path,public_key,address\n\
+         1000000000,m/2H/2/1000000000,022a471424da5e657499d1ff51cb43c47481a03b1e77f951fe64cec9f5a48f7011,1LZiqrop2HGR4qrH1ULZPyBpU6AUP49Uam\n\
+         1000000001,m/2H/2/1000000001,02e3bac8d993f5e35ea0ab6e2d73c310acc821fdecd3ede7d85231c1d50016aba7,1HAKXhsWN8WHRQPcumWv2JQVLcW5KY2HoN\n"
+    );
+
+    // the same children derived from the xpub of m/0H/1/2H
+    let output = stdout(&hdwallet(
+        &["export", "-f", "jsonl", "m/2/1000000000-1000000001"],
+        "xpub6D4BDPcP2GT577Vvch3R8wDkScZWzQzMMUm3PWbmWvVJrZwQY4VUNgqFJPMM3No2dFDFGTsxxpG5uJh7n7epu4trkrX7x7DogT5Uv6fcLW5",
+    ));
+    let rows: Vec<serde_json::Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("json"))
+        .collect();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0]["index"], "1000000000");
+    assert_eq!(rows[0]["path"], "m/2/1000000000");
+    assert_eq!(rows[0]["address"], "1LZiqrop2HGR4qrH1ULZPyBpU6AUP49Uam");
+    assert_eq!(rows[1]["address"], "1HAKXhsWN8WHRQPcumWv2JQVLcW5KY2HoN");
+
+    let rows = json(&hdwallet(&["export", "-f", "json", "m/0-9/0"], XPUB_0H_1));
+    assert_eq!(rows.as_array().expect("array").len(), 10);
+    assert_eq!(rows[9]["path"], "m/9/0");
+
+    assert!(!hdwallet(&["export", "m/0-1H"], XPUB_0H_1).status.success());
+    assert!(!hdwallet(&["export", "m/0/1"], XPUB_0H_1).status.success());
+}
+
+#[test]
+fn export_address_types() {
+    // BIP-84 and BIP-86 account keys of mnemonic `abandon abandon ... about`
+    let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about\n";
+    for (account, address_type, address) in &[
+        (
+            "m/84H/0H/0H",
+            "p2wpkh",
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu",
+        ),
+        (
+            "m/86H/0H/0H",
+            "p2tr",
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+        ),
+    ] {
+        let xpub = json(&hdwallet(
+            &[
+                "derive", "--from", "mnemonic", "--public", "-f", "json", account,
+            ],
+            mnemonic,
+        ))["xpub"]
+            .as_str()
+            .expect("xpub")
+            .to_string();
+        let rows = json(&hdwallet(
+            &[
+                "export",
+                "-f",
+                "json",
+                "--address-type",
+                address_type,
+                "m/0/0-1",
+            ],
+            &xpub,
+        ));
+        assert_eq!(rows[0]["address"], *address);
+    }
+
+    // the zpub version implies P2WPKH
+    let rows = json(&hdwallet(
+        &["export", "-f", "json", "m/0/0-1"],
+        "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs",
+    ));
+    assert_eq!(
+        rows[1]["address"],
+        "bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g"
+    );
+}
+
+#[test]
+fn networks() {
+    let output = json(&hdwallet(
+        &[
+            "derive",
+            "--from",
+            "seed",
+            "--network",
+            "regtest",
+            "-f",
+            "json",
+            "m/0H",
+        ],
+        SEED,
+    ));
+    assert!(output["xprv"].as_str().unwrap().starts_with("tprv"));
+
+    // unregistered version bytes are reported rather than rejected
+    let output = json(&hdwallet(&["inspect", "-f", "json"], "Ltub2SSUS19CirucWFod2ZsYA2J4v4U76YiCXHdcQttnoiy5aGanFHCPDBX7utfG6f95u1cUbZJNafmvzNCzZZJTw1EmyFoL8u1gJbGM8ipu491"));
+    assert_eq!(output["version"], "019da462");
+    assert_eq!(output["network"], "unknown");
+    assert_eq!(output["type"], "public");
+}
@@ -0,0 +1,219 @@
+#ifndef HDWALLET_H
+#define HDWALLET_H
+
+/* Generated with cbindgen:0.26.0 */
+
+/* Generated by cbindgen, do not edit. Run `make ffi-header` to regenerate. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+// Length of a key fingerprint.
+#define HDWALLET_FINGERPRINT_LEN 4
+
+// Length of a serialized secp256k1 secret key.
+#define HDWALLET_SECRET_KEY_LEN 32
+
+// Length of a compressed SEC1 public key.
+#define HDWALLET_PUBLIC_KEY_LEN 33
+
+typedef enum HdwalletNetwork {
+  HDWALLET_NETWORK_MAIN_NET,
+  HDWALLET_NETWORK_TEST_NET,
+  HDWALLET_NETWORK_REGTEST,
+  HDWALLET_NETWORK_SIGNET,
+} HdwalletNetwork;
+
+// Extended private key handle, serialized as xprv/tprv.
+typedef struct HdwalletPrivKey HdwalletPrivKey;
+
+// Extended public key handle, serialized as xpub/tpub.
+typedef struct HdwalletPubKey HdwalletPubKey;
+
+// Status code returned by fallible functions, [`HDWALLET_OK`] on success.
+typedef int32_t HdwalletStatus;
+
+#define HDWALLET_OK 0
+
+// A required pointer argument is null.
+#define HDWALLET_ERR_NULL_POINTER 1
+
+// A string argument is not valid UTF-8.
+#define HDWALLET_ERR_INVALID_UTF8 2
+
+// Extended key checksum mismatched.
+#define HDWALLET_ERR_CHECKSUM 3
+
+// Extended key version bytes are unknown, or a private key is given where a public key is expected.
+#define HDWALLET_ERR_UNKNOWN_VERSION 4
+
+// Invalid secp256k1 key, seed or tweak.
+#define HDWALLET_ERR_SECP 5
+
+#define HDWALLET_ERR_INVALID_BASE58 6
+
+#define HDWALLET_ERR_INVALID_LENGTH 7
+
+// Key index out of range, or hardened derivation from a public key.
+#define HDWALLET_ERR_KEY_INDEX_OUT_OF_RANGE 8
+
+#define HDWALLET_ERR_INVALID_CHAIN_PATH 9
+
+// Derivation depth exceeds 255.
+#define HDWALLET_ERR_MAX_DEPTH_EXCEEDED 10
+
+#define HDWALLET_ERR_RNG 11
+
+// Unexpected panic inside the library, please report a bug.
+#define HDWALLET_ERR_PANIC 12
+
+#define HDWALLET_ERR_INVALID_ADDRESS 13
+
+#define HDWALLET_ERR_INVALID_DESCRIPTOR 14
+
+#define HDWALLET_ERR_KEY_ORIGIN_MISMATCH 15
+
+#define HDWALLET_ERR_INVALID_TRANSACTION 16
+
+#define HDWALLET_ERR_INVALID_PSBT 17
+
+#define HDWALLET_ERR_INVALID_MULTISIG 18
+
+#define HDWALLET_ERR_INVALID_SIGNATURE 19
+
+#define HDWALLET_ERR_INVALID_PAYMENT_CODE 20
+
+#define HDWALLET_ERR_INVALID_MUSIG 21
+
+#define HDWALLET_ERR_INVALID_SEED 22
+
+#define HDWALLET_ERR_UNSUPPORTED_DESCRIPTOR 23
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Create a master key from `seed_len` bytes of seed.
+//
+// # Safety
+//
+// `seed` must point to `seed_len` readable bytes, `out` must be a valid pointer.
+HdwalletStatus hdwallet_priv_key_from_seed(const uint8_t *seed,
+                                           size_t seed_len,
+                                           enum HdwalletNetwork network,
+                                           struct HdwalletPrivKey **out);
+
+// Decode a base58 xprv/tprv string.
+//
+// # Safety
+//
+// `xprv` must be a NUL terminated string, `out` must be a valid pointer.
+HdwalletStatus hdwallet_priv_key_from_xprv(const char *xprv, struct HdwalletPrivKey **out);
+
+// Encode the key as a base58 xprv/tprv string, free it with [`hdwallet_string_free`].
+//
+// # Safety
+//
+// `key` must be a live handle, `out` must be a valid pointer.
+HdwalletStatus hdwallet_priv_key_to_xprv(const struct HdwalletPrivKey *key, char **out);
+
+// Derive a descendant key, `m` in `path` denotes this key, e.g. `m/0H/1`.
+//
+// # Safety
+//
+// `key` must be a live handle, `path` must be a NUL terminated string, `out` must be a valid pointer.
+HdwalletStatus hdwallet_priv_key_derive(const struct HdwalletPrivKey *key,
+                                        const char *path,
+                                        struct HdwalletPrivKey **out);
+
+// Extended public key of this key.
+//
+// # Safety
+//
+// `key` must be a live handle, `out` must be a valid pointer.
+HdwalletStatus hdwallet_priv_key_to_pub_key(const struct HdwalletPrivKey *key,
+                                            struct HdwalletPubKey **out);
+
+// Write the secp256k1 secret key to `out`.
+//
+// # Safety
+//
+// `key` must be a live handle, `out` must point to [`HDWALLET_SECRET_KEY_LEN`] writable bytes.
+HdwalletStatus hdwallet_priv_key_secret_key(const struct HdwalletPrivKey *key, uint8_t *out);
+
+// Write the key fingerprint to `out`.
+//
+// # Safety
+//
+// `key` must be a live handle, `out` must point to [`HDWALLET_FINGERPRINT_LEN`] writable bytes.
+HdwalletStatus hdwallet_priv_key_fingerprint(const struct HdwalletPrivKey *key, uint8_t *out);
+
+// Erase and release the key, NULL is ignored.
+//
+// # Safety
+//
+// `key` must be NULL or a handle not yet freed.
+void hdwallet_priv_key_free(struct HdwalletPrivKey *key);
+
+// Decode a base58 xpub/tpub string.
+//
+// # Safety
+//
+// `xpub` must be a NUL terminated string, `out` must be a valid pointer.
+HdwalletStatus hdwallet_pub_key_from_xpub(const char *xpub, struct HdwalletPubKey **out);
+
+// Encode the key as a base58 xpub/tpub string, free it with [`hdwallet_string_free`].
+//
+// # Safety
+//
+// `key` must be a live handle, `out` must be a valid pointer.
+HdwalletStatus hdwallet_pub_key_to_xpub(const struct HdwalletPubKey *key, char **out);
+
+// Derive a descendant key, `m` in `path` denotes this key, hardened indexes are rejected.
+//
+// # Safety
+//
+// `key` must be a live handle, `path` must be a NUL terminated string, `out` must be a valid pointer.
+HdwalletStatus hdwallet_pub_key_derive(const struct HdwalletPubKey *key,
+                                       const char *path,
+                                       struct HdwalletPubKey **out);
+
+// Write the compressed SEC1 public key to `out`.
+//
+// # Safety
+//
+// `key` must be a live handle, `out` must point to [`HDWALLET_PUBLIC_KEY_LEN`] writable bytes.
+HdwalletStatus hdwallet_pub_key_public_key(const struct HdwalletPubKey *key, uint8_t *out);
+
+// Write the key fingerprint to `out`.
+//
+// # Safety
+//
+// `key` must be a live handle, `out` must point to [`HDWALLET_FINGERPRINT_LEN`] writable bytes.
+HdwalletStatus hdwallet_pub_key_fingerprint(const struct HdwalletPubKey *key, uint8_t *out);
+
+// Release the key, NULL is ignored.
+//
+// # Safety
+//
+// `key` must be NULL or a handle not yet freed.
+void hdwallet_pub_key_free(struct HdwalletPubKey *key);
+
+// Erase and release a string returned by this library, NULL is ignored.
+//
+// # Safety
+//
+// `s` must be NULL or a string returned by this library and not yet freed.
+void hdwallet_string_free(char *s);
+
+// Message of the last failed call on the current thread, NULL if no call has failed.
+//
+// The string is owned by the library and stays valid until the next failed call on the same
+// thread, do not free it.
+const char *hdwallet_last_error_message(void);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* HDWALLET_H */
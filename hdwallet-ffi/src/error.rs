@@ -0,0 +1,133 @@
+use hdwallet_bitcoin::Error as BitcoinError;
+use std::{cell::RefCell, ffi::CString, fmt, os::raw::c_char, ptr, str::Utf8Error};
+
+/// Status code returned by fallible functions, [`HDWALLET_OK`] on success.
+pub type HdwalletStatus = i32;
+
+pub const HDWALLET_OK: HdwalletStatus = 0;
+/// A required pointer argument is null.
+pub const HDWALLET_ERR_NULL_POINTER: HdwalletStatus = 1;
+/// A string argument is not valid UTF-8.
+pub const HDWALLET_ERR_INVALID_UTF8: HdwalletStatus = 2;
+/// Extended key checksum mismatched.
+pub const HDWALLET_ERR_CHECKSUM: HdwalletStatus = 3;
+/// Extended key version bytes are unknown, or a private key is given where a public key is expected.
+pub const HDWALLET_ERR_UNKNOWN_VERSION: HdwalletStatus = 4;
+/// Invalid secp256k1 key, seed or tweak.
+pub const HDWALLET_ERR_SECP: HdwalletStatus = 5;
+pub const HDWALLET_ERR_INVALID_BASE58: HdwalletStatus = 6;
+pub const HDWALLET_ERR_INVALID_LENGTH: HdwalletStatus = 7;
+/// Key index out of range, or hardened derivation from a public key.
+pub const HDWALLET_ERR_KEY_INDEX_OUT_OF_RANGE: HdwalletStatus = 8;
+pub const HDWALLET_ERR_INVALID_CHAIN_PATH: HdwalletStatus = 9;
+/// Derivation depth exceeds 255.
+pub const HDWALLET_ERR_MAX_DEPTH_EXCEEDED: HdwalletStatus = 10;
+pub const HDWALLET_ERR_RNG: HdwalletStatus = 11;
+/// Unexpected panic inside the library, please report a bug.
+pub const HDWALLET_ERR_PANIC: HdwalletStatus = 12;
+pub const HDWALLET_ERR_INVALID_ADDRESS: HdwalletStatus = 13;
+pub const HDWALLET_ERR_INVALID_DESCRIPTOR: HdwalletStatus = 14;
+pub const HDWALLET_ERR_KEY_ORIGIN_MISMATCH: HdwalletStatus = 15;
+pub const HDWALLET_ERR_INVALID_TRANSACTION: HdwalletStatus = 16;
+pub const HDWALLET_ERR_INVALID_PSBT: HdwalletStatus = 17;
+pub const HDWALLET_ERR_INVALID_MULTISIG: HdwalletStatus = 18;
+pub const HDWALLET_ERR_INVALID_SIGNATURE: HdwalletStatus = 19;
+pub const HDWALLET_ERR_INVALID_PAYMENT_CODE: HdwalletStatus = 20;
+pub const HDWALLET_ERR_INVALID_MUSIG: HdwalletStatus = 21;
+pub const HDWALLET_ERR_INVALID_SEED: HdwalletStatus = 22;
+pub const HDWALLET_ERR_UNSUPPORTED_DESCRIPTOR: HdwalletStatus = 23;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Error {
+    NullPointer,
+    InvalidUtf8,
+    Bitcoin(BitcoinError),
+    Panic,
+}
+
+impl Error {
+    pub(crate) fn status(&self) -> HdwalletStatus {
+        match self {
+            Error::NullPointer => HDWALLET_ERR_NULL_POINTER,
+            Error::InvalidUtf8 => HDWALLET_ERR_INVALID_UTF8,
+            Error::Panic => HDWALLET_ERR_PANIC,
+            Error::Bitcoin(err) => match err {
+                BitcoinError::MisChecksum => HDWALLET_ERR_CHECKSUM,
+                BitcoinError::UnknownVersion | BitcoinError::UnknownNetworkByte(_) => {
+                    HDWALLET_ERR_UNKNOWN_VERSION
+                }
+                BitcoinError::Secp(_) => HDWALLET_ERR_SECP,
+                BitcoinError::InvalidBase58 => HDWALLET_ERR_INVALID_BASE58,
+                BitcoinError::InvalidLength => HDWALLET_ERR_INVALID_LENGTH,
+                BitcoinError::KeyIndexOutOfRange => HDWALLET_ERR_KEY_INDEX_OUT_OF_RANGE,
+                BitcoinError::ChainPath(_) => HDWALLET_ERR_INVALID_CHAIN_PATH,
+                BitcoinError::MaxDepthExceeded => HDWALLET_ERR_MAX_DEPTH_EXCEEDED,
+                BitcoinError::Rng => HDWALLET_ERR_RNG,
+                BitcoinError::InvalidAddress => HDWALLET_ERR_INVALID_ADDRESS,
+                BitcoinError::InvalidDescriptor => HDWALLET_ERR_INVALID_DESCRIPTOR,
+                BitcoinError::KeyOriginMismatch => HDWALLET_ERR_KEY_ORIGIN_MISMATCH,
+                BitcoinError::InvalidTransaction => HDWALLET_ERR_INVALID_TRANSACTION,
+                BitcoinError::InvalidPsbt => HDWALLET_ERR_INVALID_PSBT,
+                BitcoinError::InvalidMultisig => HDWALLET_ERR_INVALID_MULTISIG,
+                BitcoinError::InvalidSignature => HDWALLET_ERR_INVALID_SIGNATURE,
+                BitcoinError::InvalidPaymentCode => HDWALLET_ERR_INVALID_PAYMENT_CODE,
+                BitcoinError::InvalidMusig => HDWALLET_ERR_INVALID_MUSIG,
+                BitcoinError::InvalidSeed => HDWALLET_ERR_INVALID_SEED,
+                BitcoinError::UnsupportedDescriptor => HDWALLET_ERR_UNSUPPORTED_DESCRIPTOR,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NullPointer => write!(f, "Null pointer argument"),
+            Error::InvalidUtf8 => write!(f, "Invalid UTF-8 string"),
+            Error::Bitcoin(err) => write!(f, "{}", err),
+            Error::Panic => write!(f, "Unexpected panic"),
+        }
+    }
+}
+
+impl From<BitcoinError> for Error {
+    fn from(err: BitcoinError) -> Self {
+        Error::Bitcoin(err)
+    }
+}
+
+impl From<hdwallet::error::Error> for Error {
+    fn from(err: hdwallet::error::Error) -> Self {
+        Error::Bitcoin(err.into())
+    }
+}
+
+impl From<Utf8Error> for Error {
+    fn from(_err: Utf8Error) -> Self {
+        Error::InvalidUtf8
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn set_last_error(err: &Error) {
+    // messages never contain NUL
+    let message = CString::new(err.to_string()).expect("error message");
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(message));
+}
+
+/// Message of the last failed call on the current thread, NULL if no call has failed.
+///
+/// The string is owned by the library and stays valid until the next failed call on the same
+/// thread, do not free it.
+#[no_mangle]
+pub extern "C" fn hdwallet_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last_error| {
+        last_error
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
@@ -0,0 +1,461 @@
+//! C bindings of `hdwallet` and `hdwallet-bitcoin`.
+//!
+//! The header `include/hdwallet.h` is generated by [cbindgen](https://github.com/mozilla/cbindgen),
+//! run `make ffi-header` after changing the exported API.
+//!
+//! Keys are passed around as opaque handles, every handle returned through an out pointer must be
+//! released with the matching free function, strings with [`hdwallet_string_free`]. Fallible
+//! functions return a [`HdwalletStatus`] and leave the out pointer untouched on error, call
+//! [`hdwallet_last_error_message`] for a description.
+//!
+//! ``` c
+//! HdwalletPrivKey *master = NULL, *account = NULL;
+//! char *xprv = NULL;
+//! if (hdwallet_priv_key_from_seed(seed, seed_len, HDWALLET_NETWORK_MAIN_NET, &master) != HDWALLET_OK ||
+//!     hdwallet_priv_key_derive(master, "m/44H/0H/0H", &account) != HDWALLET_OK ||
+//!     hdwallet_priv_key_to_xprv(account, &xprv) != HDWALLET_OK) {
+//!     fprintf(stderr, "%s\n", hdwallet_last_error_message());
+//! }
+//! hdwallet_string_free(xprv);
+//! hdwallet_priv_key_free(account);
+//! hdwallet_priv_key_free(master);
+//! ```
+
+mod error;
+
+pub use error::*;
+
+use error::{set_last_error, Error};
+use hdwallet::{
+    traits::{Deserialize, Serialize},
+    zeroize::Zeroize,
+    ExtendedPrivKey,
+};
+use hdwallet_bitcoin::{Network, PrivKey, PubKey};
+use std::{
+    ffi::{CStr, CString},
+    os::raw::c_char,
+    panic::{self, UnwindSafe},
+    ptr, slice,
+};
+
+/// Length of a key fingerprint.
+pub const HDWALLET_FINGERPRINT_LEN: usize = 4;
+/// Length of a serialized secp256k1 secret key.
+pub const HDWALLET_SECRET_KEY_LEN: usize = 32;
+/// Length of a compressed SEC1 public key.
+pub const HDWALLET_PUBLIC_KEY_LEN: usize = 33;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HdwalletNetwork {
+    MainNet,
+    TestNet,
+    Regtest,
+    Signet,
+}
+
+impl From<HdwalletNetwork> for Network {
+    fn from(network: HdwalletNetwork) -> Self {
+        match network {
+            HdwalletNetwork::MainNet => Network::MainNet,
+            HdwalletNetwork::TestNet => Network::TestNet,
+            HdwalletNetwork::Regtest => Network::Regtest,
+            HdwalletNetwork::Signet => Network::Signet,
+        }
+    }
+}
+
+/// Extended private key handle, serialized as xprv/tprv.
+pub struct HdwalletPrivKey(PrivKey);
+
+/// Extended public key handle, serialized as xpub/tpub.
+pub struct HdwalletPubKey(PubKey);
+
+/// Run `f`, turning errors and panics into a status code.
+fn ffi_call(f: impl FnOnce() -> Result<(), Error> + UnwindSafe) -> HdwalletStatus {
+    let result = panic::catch_unwind(f).unwrap_or(Err(Error::Panic));
+    match result {
+        Ok(()) => HDWALLET_OK,
+        Err(err) => {
+            set_last_error(&err);
+            err.status()
+        }
+    }
+}
+
+unsafe fn arg_ref<'a, T>(ptr: *const T) -> Result<&'a T, Error> {
+    ptr.as_ref().ok_or(Error::NullPointer)
+}
+
+unsafe fn arg_str<'a>(ptr: *const c_char) -> Result<&'a str, Error> {
+    if ptr.is_null() {
+        return Err(Error::NullPointer);
+    }
+    Ok(CStr::from_ptr(ptr).to_str()?)
+}
+
+unsafe fn arg_bytes<'a>(ptr: *const u8, len: usize) -> Result<&'a [u8], Error> {
+    if ptr.is_null() {
+        return Err(Error::NullPointer);
+    }
+    Ok(slice::from_raw_parts(ptr, len))
+}
+
+/// Check the out pointer first so nothing is computed for a call that can't return it.
+fn out_ptr<T>(out: *mut T) -> Result<*mut T, Error> {
+    if out.is_null() {
+        return Err(Error::NullPointer);
+    }
+    Ok(out)
+}
+
+unsafe fn write_bytes(out: *mut u8, bytes: &[u8]) -> Result<(), Error> {
+    ptr::copy_nonoverlapping(bytes.as_ptr(), out_ptr(out)?, bytes.len());
+    Ok(())
+}
+
+fn into_c_string(s: String) -> *mut c_char {
+    // base58 strings never contain NUL
+    CString::new(s).expect("base58 string").into_raw()
+}
+
+/// Create a master key from `seed_len` bytes of seed.
+///
+/// # Safety
+///
+/// `seed` must point to `seed_len` readable bytes, `out` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn hdwallet_priv_key_from_seed(
+    seed: *const u8,
+    seed_len: usize,
+    network: HdwalletNetwork,
+    out: *mut *mut HdwalletPrivKey,
+) -> HdwalletStatus {
+    ffi_call(|| {
+        let out = out_ptr(out)?;
+        let extended_key = ExtendedPrivKey::with_seed(arg_bytes(seed, seed_len)?)?;
+        let key = PrivKey::from_master_key(extended_key, network.into());
+        *out = Box::into_raw(Box::new(HdwalletPrivKey(key)));
+        Ok(())
+    })
+}
+
+/// Decode a base58 xprv/tprv string.
+///
+/// # Safety
+///
+/// `xprv` must be a NUL terminated string, `out` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn hdwallet_priv_key_from_xprv(
+    xprv: *const c_char,
+    out: *mut *mut HdwalletPrivKey,
+) -> HdwalletStatus {
+    ffi_call(|| {
+        let out = out_ptr(out)?;
+        let key = PrivKey::deserialize(arg_str(xprv)?.to_string())?;
+        *out = Box::into_raw(Box::new(HdwalletPrivKey(key)));
+        Ok(())
+    })
+}
+
+/// Encode the key as a base58 xprv/tprv string, free it with [`hdwallet_string_free`].
+///
+/// # Safety
+///
+/// `key` must be a live handle, `out` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn hdwallet_priv_key_to_xprv(
+    key: *const HdwalletPrivKey,
+    out: *mut *mut c_char,
+) -> HdwalletStatus {
+    ffi_call(|| {
+        let out = out_ptr(out)?;
+        *out = into_c_string(arg_ref(key)?.0.serialize());
+        Ok(())
+    })
+}
+
+/// Derive a descendant key, `m` in `path` denotes this key, e.g. `m/0H/1`.
+///
+/// # Safety
+///
+/// `key` must be a live handle, `path` must be a NUL terminated string, `out` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn hdwallet_priv_key_derive(
+    key: *const HdwalletPrivKey,
+    path: *const c_char,
+    out: *mut *mut HdwalletPrivKey,
+) -> HdwalletStatus {
+    ffi_call(|| {
+        let out = out_ptr(out)?;
+        let child = arg_ref(key)?.0.derive_path(arg_str(path)?.into())?;
+        *out = Box::into_raw(Box::new(HdwalletPrivKey(child)));
+        Ok(())
+    })
+}
+
+/// Extended public key of this key.
+///
+/// # Safety
+///
+/// `key` must be a live handle, `out` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn hdwallet_priv_key_to_pub_key(
+    key: *const HdwalletPrivKey,
+    out: *mut *mut HdwalletPubKey,
+) -> HdwalletStatus {
+    ffi_call(|| {
+        let out = out_ptr(out)?;
+        let pub_key = PubKey::from_private_key(&arg_ref(key)?.0)?;
+        *out = Box::into_raw(Box::new(HdwalletPubKey(pub_key)));
+        Ok(())
+    })
+}
+
+/// Write the secp256k1 secret key to `out`.
+///
+/// # Safety
+///
+/// `key` must be a live handle, `out` must point to [`HDWALLET_SECRET_KEY_LEN`] writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn hdwallet_priv_key_secret_key(
+    key: *const HdwalletPrivKey,
+    out: *mut u8,
+) -> HdwalletStatus {
+    ffi_call(|| write_bytes(out, &arg_ref(key)?.0.extended_key.private_key()[..]))
+}
+
+/// Write the key fingerprint to `out`.
+///
+/// # Safety
+///
+/// `key` must be a live handle, `out` must point to [`HDWALLET_FINGERPRINT_LEN`] writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn hdwallet_priv_key_fingerprint(
+    key: *const HdwalletPrivKey,
+    out: *mut u8,
+) -> HdwalletStatus {
+    ffi_call(|| write_bytes(out, &arg_ref(key)?.0.fingerprint()))
+}
+
+/// Erase and release the key, NULL is ignored.
+///
+/// # Safety
+///
+/// `key` must be NULL or a handle not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn hdwallet_priv_key_free(key: *mut HdwalletPrivKey) {
+    if !key.is_null() {
+        let mut key = Box::from_raw(key);
+        key.0.zeroize();
+    }
+}
+
+/// Decode a base58 xpub/tpub string.
+///
+/// # Safety
+///
+/// `xpub` must be a NUL terminated string, `out` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn hdwallet_pub_key_from_xpub(
+    xpub: *const c_char,
+    out: *mut *mut HdwalletPubKey,
+) -> HdwalletStatus {
+    ffi_call(|| {
+        let out = out_ptr(out)?;
+        let key = PubKey::deserialize(arg_str(xpub)?.to_string())?;
+        *out = Box::into_raw(Box::new(HdwalletPubKey(key)));
+        Ok(())
+    })
+}
+
+/// Encode the key as a base58 xpub/tpub string, free it with [`hdwallet_string_free`].
+///
+/// # Safety
+///
+/// `key` must be a live handle, `out` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn hdwallet_pub_key_to_xpub(
+    key: *const HdwalletPubKey,
+    out: *mut *mut c_char,
+) -> HdwalletStatus {
+    ffi_call(|| {
+        let out = out_ptr(out)?;
+        *out = into_c_string(arg_ref(key)?.0.serialize());
+        Ok(())
+    })
+}
+
+/// Derive a descendant key, `m` in `path` denotes this key, hardened indexes are rejected.
+///
+/// # Safety
+///
+/// `key` must be a live handle, `path` must be a NUL terminated string, `out` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn hdwallet_pub_key_derive(
+    key: *const HdwalletPubKey,
+    path: *const c_char,
+    out: *mut *mut HdwalletPubKey,
+) -> HdwalletStatus {
+    ffi_call(|| {
+        let out = out_ptr(out)?;
+        let child = arg_ref(key)?.0.derive_path(arg_str(path)?.into())?;
+        *out = Box::into_raw(Box::new(HdwalletPubKey(child)));
+        Ok(())
+    })
+}
+
+/// Write the compressed SEC1 public key to `out`.
+///
+/// # Safety
+///
+/// `key` must be a live handle, `out` must point to [`HDWALLET_PUBLIC_KEY_LEN`] writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn hdwallet_pub_key_public_key(
+    key: *const HdwalletPubKey,
+    out: *mut u8,
+) -> HdwalletStatus {
+    ffi_call(|| write_bytes(out, &arg_ref(key)?.0.extended_key.public_key.serialize()))
+}
+
+/// Write the key fingerprint to `out`.
+///
+/// # Safety
+///
+/// `key` must be a live handle, `out` must point to [`HDWALLET_FINGERPRINT_LEN`] writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn hdwallet_pub_key_fingerprint(
+    key: *const HdwalletPubKey,
+    out: *mut u8,
+) -> HdwalletStatus {
+    ffi_call(|| write_bytes(out, &arg_ref(key)?.0.fingerprint()))
+}
+
+/// Release the key, NULL is ignored.
+///
+/// # Safety
+///
+/// `key` must be NULL or a handle not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn hdwallet_pub_key_free(key: *mut HdwalletPubKey) {
+    if !key.is_null() {
+        drop(Box::from_raw(key));
+    }
+}
+
+/// Erase and release a string returned by this library, NULL is ignored.
+///
+/// # Safety
+///
+/// `s` must be NULL or a string returned by this library and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn hdwallet_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        CString::from_raw(s).into_bytes_with_nul().zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // BIP-32 test vector 1
+    const SEED: &str = "000102030405060708090a0b0c0d0e0f";
+
+    unsafe fn take_string(s: *mut c_char) -> String {
+        let string = CStr::from_ptr(s).to_str().expect("utf8").to_string();
+        hdwallet_string_free(s);
+        string
+    }
+
+    #[test]
+    fn derive_and_serialize() {
+        let seed = hex::decode(SEED).expect("decode");
+        unsafe {
+            let mut master = ptr::null_mut();
+            assert_eq!(
+                hdwallet_priv_key_from_seed(
+                    seed.as_ptr(),
+                    seed.len(),
+                    HdwalletNetwork::MainNet,
+                    &mut master
+                ),
+                HDWALLET_OK
+            );
+            let mut key = ptr::null_mut();
+            let path = CString::new("m/0H/1/2H").unwrap();
+            assert_eq!(
+                hdwallet_priv_key_derive(master, path.as_ptr(), &mut key),
+                HDWALLET_OK
+            );
+            let mut xprv = ptr::null_mut();
+            assert_eq!(hdwallet_priv_key_to_xprv(key, &mut xprv), HDWALLET_OK);
+            assert_eq!(take_string(xprv), "xprv9z4pot5VBttmtdRTWfWQmoH1taj2axGVzFqSb8C9xaxKymcFzXBDptWmT7FwuEzG3ryjH4ktypQSAewRiNMjANTtpgP4mLTj34bhnZX7UiM");
+
+            let mut pub_key = ptr::null_mut();
+            assert_eq!(hdwallet_priv_key_to_pub_key(key, &mut pub_key), HDWALLET_OK);
+            let mut child = ptr::null_mut();
+            let path = CString::new("m/2/1000000000").unwrap();
+            assert_eq!(
+                hdwallet_pub_key_derive(pub_key, path.as_ptr(), &mut child),
+                HDWALLET_OK
+            );
+            let mut xpub = ptr::null_mut();
+            assert_eq!(hdwallet_pub_key_to_xpub(child, &mut xpub), HDWALLET_OK);
+            assert_eq!(take_string(xpub), "xpub6H1LXWLaKsWFhvm6RVpEL9P4KfRZSW7abD2ttkWP3SSQvnyA8FSVqNTEcYFgJS2UaFcxupHiYkro49S8yGasTvXEYBVPamhGW6cFJodrTHy");
+
+            let mut fingerprint = [0u8; HDWALLET_FINGERPRINT_LEN];
+            assert_eq!(
+                hdwallet_priv_key_fingerprint(master, fingerprint.as_mut_ptr()),
+                HDWALLET_OK
+            );
+            assert_eq!(hex::encode(fingerprint), "3442193e");
+
+            hdwallet_pub_key_free(child);
+            hdwallet_pub_key_free(pub_key);
+            hdwallet_priv_key_free(key);
+            hdwallet_priv_key_free(master);
+        }
+    }
+
+    #[test]
+    fn error_codes() {
+        unsafe {
+            let mut key = ptr::null_mut();
+            let xpub = CString::new("xpub6ASuArnXKPbf").unwrap();
+            assert_eq!(
+                hdwallet_pub_key_from_xpub(xpub.as_ptr(), &mut key),
+                HDWALLET_ERR_INVALID_LENGTH
+            );
+            assert!(key.is_null());
+            assert_eq!(
+                CStr::from_ptr(hdwallet_last_error_message()).to_str(),
+                Ok("Invalid length")
+            );
+            assert_eq!(
+                hdwallet_pub_key_from_xpub(ptr::null(), &mut key),
+                HDWALLET_ERR_NULL_POINTER
+            );
+
+            let xpub = CString::new("xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ").unwrap();
+            assert_eq!(
+                hdwallet_pub_key_from_xpub(xpub.as_ptr(), &mut key),
+                HDWALLET_OK
+            );
+            let mut child = ptr::null_mut();
+            let path = CString::new("m/0H").unwrap();
+            assert_eq!(
+                hdwallet_pub_key_derive(key, path.as_ptr(), &mut child),
+                HDWALLET_ERR_KEY_INDEX_OUT_OF_RANGE
+            );
+            let path = CString::new("0/1").unwrap();
+            assert_eq!(
+                hdwallet_pub_key_derive(key, path.as_ptr(), &mut child),
+                HDWALLET_ERR_INVALID_CHAIN_PATH
+            );
+            assert!(child.is_null());
+            hdwallet_pub_key_free(key);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+/* C harness of the hdwallet-ffi ABI, run with `make ffi-test`. */
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "hdwallet.h"
+
+static int failures = 0;
+
+#define CHECK(cond)                                                       \
+    do {                                                                  \
+        if (!(cond)) {                                                    \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,        \
+                    __LINE__, #cond);                                     \
+            failures++;                                                   \
+        }                                                                 \
+    } while (0)
+
+#define CHECK_STATUS(call, expected)                                      \
+    do {                                                                  \
+        HdwalletStatus status = (call);                                   \
+        if (status != (expected)) {                                       \
+            const char *message = hdwallet_last_error_message();          \
+            fprintf(stderr, "%s:%d: %s returned %d (%s), expected %d\n",  \
+                    __FILE__, __LINE__, #call, status,                    \
+                    message ? message : "no message", (expected));        \
+            failures++;                                                   \
+        }                                                                 \
+    } while (0)
+
+/* BIP-32 test vector 1 */
+static const uint8_t SEED[] = {0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07,
+                               0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f};
+
+static void check_string(char *s, const char *expected) {
+    CHECK(s != NULL && strcmp(s, expected) == 0);
+    hdwallet_string_free(s);
+}
+
+static void test_derive_and_serialize(void) {
+    HdwalletPrivKey *master = NULL, *key = NULL;
+    HdwalletPubKey *pub_key = NULL, *child = NULL;
+    char *s = NULL;
+    uint8_t fingerprint[HDWALLET_FINGERPRINT_LEN];
+    const uint8_t master_fingerprint[] = {0x34, 0x42, 0x19, 0x3e};
+
+    CHECK_STATUS(hdwallet_priv_key_from_seed(SEED, sizeof(SEED), HDWALLET_NETWORK_MAIN_NET, &master), HDWALLET_OK);
+    CHECK_STATUS(hdwallet_priv_key_to_xprv(master, &s), HDWALLET_OK);
+    check_string(s, "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi");
+    CHECK_STATUS(hdwallet_priv_key_fingerprint(master, fingerprint), HDWALLET_OK);
+    CHECK(memcmp(fingerprint, master_fingerprint, sizeof(fingerprint)) == 0);
+
+    CHECK_STATUS(hdwallet_priv_key_derive(master, "m/0H/1/2H", &key), HDWALLET_OK);
+    CHECK_STATUS(hdwallet_priv_key_to_xprv(key, &s), HDWALLET_OK);
+    check_string(s, "xprv9z4pot5VBttmtdRTWfWQmoH1taj2axGVzFqSb8C9xaxKymcFzXBDptWmT7FwuEzG3ryjH4ktypQSAewRiNMjANTtpgP4mLTj34bhnZX7UiM");
+
+    CHECK_STATUS(hdwallet_priv_key_to_pub_key(key, &pub_key), HDWALLET_OK);
+    CHECK_STATUS(hdwallet_pub_key_to_xpub(pub_key, &s), HDWALLET_OK);
+    check_string(s, "xpub6D4BDPcP2GT577Vvch3R8wDkScZWzQzMMUm3PWbmWvVJrZwQY4VUNgqFJPMM3No2dFDFGTsxxpG5uJh7n7epu4trkrX7x7DogT5Uv6fcLW5");
+
+    CHECK_STATUS(hdwallet_pub_key_derive(pub_key, "m/2/1000000000", &child), HDWALLET_OK);
+    CHECK_STATUS(hdwallet_pub_key_to_xpub(child, &s), HDWALLET_OK);
+    check_string(s, "xpub6H1LXWLaKsWFhvm6RVpEL9P4KfRZSW7abD2ttkWP3SSQvnyA8FSVqNTEcYFgJS2UaFcxupHiYkro49S8yGasTvXEYBVPamhGW6cFJodrTHy");
+
+    hdwallet_pub_key_free(child);
+    hdwallet_pub_key_free(pub_key);
+    hdwallet_priv_key_free(key);
+    hdwallet_priv_key_free(master);
+}
+
+static void test_decode_keys(void) {
+    const char *xprv = "xprv9wTYmMFdV23N2TdNG573QoEsfRrWKQgWeibmLntzniatZvR9BmLnvSxqu53Kw1UmYPxLgboyZQaXwTCg8MSY3H2EU4pWcQDnRnrVA1xe8fs";
+    const char *xpub = "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ";
+    const uint8_t public_key[] = {0x03, 0x50, 0x1e, 0x45, 0x4b, 0xf0, 0x07, 0x51, 0xf2, 0x4b, 0x1b,
+                                  0x48, 0x9a, 0xa9, 0x25, 0x21, 0x5d, 0x66, 0xaf, 0x22, 0x34, 0xe3,
+                                  0x89, 0x1c, 0x3b, 0x21, 0xa5, 0x2b, 0xed, 0xb3, 0xcd, 0x71, 0x1c};
+    HdwalletPrivKey *priv_key = NULL;
+    HdwalletPubKey *pub_key = NULL;
+    char *s = NULL;
+    uint8_t buf[HDWALLET_PUBLIC_KEY_LEN];
+
+    CHECK_STATUS(hdwallet_priv_key_from_xprv(xprv, &priv_key), HDWALLET_OK);
+    CHECK_STATUS(hdwallet_priv_key_to_xprv(priv_key, &s), HDWALLET_OK);
+    check_string(s, xprv);
+    CHECK_STATUS(hdwallet_priv_key_secret_key(priv_key, buf), HDWALLET_OK);
+
+    CHECK_STATUS(hdwallet_pub_key_from_xpub(xpub, &pub_key), HDWALLET_OK);
+    CHECK_STATUS(hdwallet_pub_key_to_xpub(pub_key, &s), HDWALLET_OK);
+    check_string(s, xpub);
+    CHECK_STATUS(hdwallet_pub_key_public_key(pub_key, buf), HDWALLET_OK);
+    CHECK(memcmp(buf, public_key, sizeof(public_key)) == 0);
+
+    hdwallet_pub_key_free(pub_key);
+    hdwallet_priv_key_free(priv_key);
+}
+
+static void test_errors(void) {
+    HdwalletPrivKey *priv_key = NULL;
+    HdwalletPubKey *pub_key = NULL, *child = NULL;
+    const char *xpub = "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ";
+
+    CHECK_STATUS(hdwallet_pub_key_from_xpub("xpub6ASuArnXKPbf", &pub_key), HDWALLET_ERR_INVALID_LENGTH);
+    CHECK(pub_key == NULL);
+    CHECK(strcmp(hdwallet_last_error_message(), "Invalid length") == 0);
+    CHECK_STATUS(hdwallet_priv_key_from_xprv(xpub, &priv_key), HDWALLET_ERR_UNKNOWN_VERSION);
+    CHECK_STATUS(hdwallet_priv_key_from_xprv(NULL, &priv_key), HDWALLET_ERR_NULL_POINTER);
+    CHECK_STATUS(hdwallet_priv_key_from_seed(SEED, sizeof(SEED), HDWALLET_NETWORK_MAIN_NET, NULL), HDWALLET_ERR_NULL_POINTER);
+    CHECK(priv_key == NULL);
+
+    CHECK_STATUS(hdwallet_pub_key_from_xpub(xpub, &pub_key), HDWALLET_OK);
+    CHECK_STATUS(hdwallet_pub_key_derive(pub_key, "m/0H", &child), HDWALLET_ERR_KEY_INDEX_OUT_OF_RANGE);
+    CHECK_STATUS(hdwallet_pub_key_derive(pub_key, "m/x", &child), HDWALLET_ERR_INVALID_CHAIN_PATH);
+    CHECK(child == NULL);
+    hdwallet_pub_key_free(pub_key);
+
+    /* freeing NULL is a no-op */
+    hdwallet_priv_key_free(NULL);
+    hdwallet_pub_key_free(NULL);
+    hdwallet_string_free(NULL);
+}
+
+int main(void) {
+    test_derive_and_serialize();
+    test_decode_keys();
+    test_errors();
+    if (failures) {
+        fprintf(stderr, "%d check(s) failed\n", failures);
+        return EXIT_FAILURE;
+    }
+    printf("all checks passed\n");
+    return EXIT_SUCCESS;
+}
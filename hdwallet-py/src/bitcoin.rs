@@ -0,0 +1,248 @@
+use crate::{
+    error::Error,
+    extended_key::{ExtendedPrivKey, ExtendedPubKey},
+    key_chain::{Derivation, PathArg},
+};
+use hdwallet::traits::{Deserialize, Serialize};
+use hdwallet_bitcoin::{Error as BitcoinError, Network as BitcoinNetwork, PrivKey, PubKey};
+use pyo3::{prelude::*, types::PyBytes};
+
+#[pyclass(module = "hdwallet_py", eq, eq_int)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Network {
+    MainNet,
+    TestNet,
+    Regtest,
+    Signet,
+}
+
+impl From<Network> for BitcoinNetwork {
+    fn from(network: Network) -> Self {
+        match network {
+            Network::MainNet => BitcoinNetwork::MainNet,
+            Network::TestNet => BitcoinNetwork::TestNet,
+            Network::Regtest => BitcoinNetwork::Regtest,
+            Network::Signet => BitcoinNetwork::Signet,
+        }
+    }
+}
+
+/// Only Bitcoin networks are exposed, keys with unknown versions are rejected.
+impl TryFrom<BitcoinNetwork> for Network {
+    type Error = BitcoinError;
+
+    fn try_from(network: BitcoinNetwork) -> Result<Self, Self::Error> {
+        match network {
+            BitcoinNetwork::MainNet => Ok(Network::MainNet),
+            BitcoinNetwork::TestNet => Ok(Network::TestNet),
+            BitcoinNetwork::Regtest => Ok(Network::Regtest),
+            BitcoinNetwork::Signet => Ok(Network::Signet),
+            BitcoinNetwork::Custom(_) | BitcoinNetwork::Unknown(_) => {
+                Err(BitcoinError::UnknownVersion)
+            }
+        }
+    }
+}
+
+/// Bitcoin extended private key, serialized as xprv/tprv.
+#[pyclass(module = "hdwallet_py")]
+#[derive(Clone)]
+pub(crate) struct BitcoinPrivKey(PrivKey);
+
+#[pymethods]
+impl BitcoinPrivKey {
+    /// `parent_fingerprint` is used when `derivation` has no parent key.
+    #[new]
+    #[pyo3(signature = (extended_key, derivation, network, parent_fingerprint = None))]
+    fn new(
+        extended_key: ExtendedPrivKey,
+        derivation: Derivation,
+        network: Network,
+        parent_fingerprint: Option<[u8; 4]>,
+    ) -> Self {
+        BitcoinPrivKey(PrivKey {
+            network: network.into(),
+            script_type: Default::default(),
+            derivation: derivation.0,
+            parent_fingerprint,
+            extended_key: extended_key.0,
+        })
+    }
+
+    #[staticmethod]
+    fn from_master_key(extended_key: ExtendedPrivKey, network: Network) -> Self {
+        BitcoinPrivKey(PrivKey::from_master_key(extended_key.0, network.into()))
+    }
+
+    /// Decode a base58 xprv/tprv string.
+    #[staticmethod]
+    fn deserialize(key: String) -> PyResult<Self> {
+        let key = PrivKey::deserialize(key).map_err(Error::from)?;
+        Network::try_from(key.network).map_err(Error::from)?;
+        Ok(BitcoinPrivKey(key))
+    }
+
+    /// Encode as a base58 xprv/tprv string.
+    fn serialize(&self) -> String {
+        self.0.serialize()
+    }
+
+    #[getter]
+    fn network(&self) -> PyResult<Network> {
+        Ok(Network::try_from(self.0.network).map_err(Error::from)?)
+    }
+
+    #[getter]
+    fn derivation(&self) -> Derivation {
+        Derivation(self.0.derivation.clone())
+    }
+
+    #[getter]
+    fn extended_key(&self) -> ExtendedPrivKey {
+        ExtendedPrivKey(self.0.extended_key.clone())
+    }
+
+    #[getter]
+    fn depth(&self) -> u8 {
+        self.0.derivation.depth
+    }
+
+    #[getter]
+    fn fingerprint<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.0.fingerprint())
+    }
+
+    #[getter]
+    fn parent_fingerprint<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.0.parent_fingerprint())
+    }
+
+    /// Derive a child key, indexes from `2 ** 31` are hardened.
+    fn derive_private_key(&self, index: u32) -> PyResult<Self> {
+        Ok(BitcoinPrivKey(
+            self.0
+                .derive_private_key(index.into())
+                .map_err(Error::from)?,
+        ))
+    }
+
+    /// Derive a descendant key, `m` in path denotes this key.
+    fn derive_path(&self, path: PathArg) -> PyResult<Self> {
+        Ok(BitcoinPrivKey(
+            self.0.derive_path(path.into_core()).map_err(Error::from)?,
+        ))
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "BitcoinPrivKey(network={:?}, depth={}, fingerprint={})",
+            self.0.network,
+            self.0.derivation.depth,
+            hex::encode(self.0.fingerprint())
+        )
+    }
+}
+
+/// Bitcoin extended public key, serialized as xpub/tpub.
+#[pyclass(module = "hdwallet_py")]
+#[derive(Clone)]
+pub(crate) struct BitcoinPubKey(PubKey);
+
+#[pymethods]
+impl BitcoinPubKey {
+    /// `parent_fingerprint` is used when `derivation` has no parent key.
+    #[new]
+    #[pyo3(signature = (extended_key, derivation, network, parent_fingerprint = None))]
+    fn new(
+        extended_key: ExtendedPubKey,
+        derivation: Derivation,
+        network: Network,
+        parent_fingerprint: Option<[u8; 4]>,
+    ) -> Self {
+        BitcoinPubKey(PubKey {
+            network: network.into(),
+            script_type: Default::default(),
+            derivation: derivation.0,
+            parent_fingerprint,
+            extended_key: extended_key.0,
+        })
+    }
+
+    #[staticmethod]
+    fn from_private_key(key: &BitcoinPrivKey) -> PyResult<Self> {
+        Ok(BitcoinPubKey(
+            PubKey::from_private_key(&key.0).map_err(Error::from)?,
+        ))
+    }
+
+    /// Decode a base58 xpub/tpub string.
+    #[staticmethod]
+    fn deserialize(key: String) -> PyResult<Self> {
+        let key = PubKey::deserialize(key).map_err(Error::from)?;
+        Network::try_from(key.network).map_err(Error::from)?;
+        Ok(BitcoinPubKey(key))
+    }
+
+    /// Encode as a base58 xpub/tpub string.
+    fn serialize(&self) -> String {
+        self.0.serialize()
+    }
+
+    #[getter]
+    fn network(&self) -> PyResult<Network> {
+        Ok(Network::try_from(self.0.network).map_err(Error::from)?)
+    }
+
+    #[getter]
+    fn derivation(&self) -> Derivation {
+        Derivation(self.0.derivation.clone())
+    }
+
+    #[getter]
+    fn extended_key(&self) -> ExtendedPubKey {
+        ExtendedPubKey(self.0.extended_key.clone())
+    }
+
+    #[getter]
+    fn depth(&self) -> u8 {
+        self.0.derivation.depth
+    }
+
+    #[getter]
+    fn fingerprint<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.0.fingerprint())
+    }
+
+    #[getter]
+    fn parent_fingerprint<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.0.parent_fingerprint())
+    }
+
+    /// Derive a normal child key, hardened indexes raise `KeyIndexOutOfRangeError`.
+    fn derive_public_key(&self, index: u32) -> PyResult<Self> {
+        Ok(BitcoinPubKey(
+            self.0
+                .derive_public_key(index.into())
+                .map_err(Error::from)?,
+        ))
+    }
+
+    /// Derive a descendant key, `m` in path denotes this key.
+    fn derive_path(&self, path: PathArg) -> PyResult<Self> {
+        Ok(BitcoinPubKey(
+            self.0.derive_path(path.into_core()).map_err(Error::from)?,
+        ))
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+
+    fn __repr__(&self) -> String {
+        format!("BitcoinPubKey({})", self.serialize())
+    }
+}
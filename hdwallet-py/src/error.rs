@@ -0,0 +1,240 @@
+use hdwallet::{secp256k1, ChainPathError as CoreChainPathError};
+use hdwallet_bitcoin::Error as BitcoinError;
+use pyo3::{create_exception, exceptions::PyValueError, prelude::*};
+
+create_exception!(
+    hdwallet_py,
+    HDWalletError,
+    PyValueError,
+    "Base class of all hdwallet errors."
+);
+create_exception!(
+    hdwallet_py,
+    KeyIndexOutOfRangeError,
+    HDWalletError,
+    "Key index out of range, or hardened derivation from a public key."
+);
+create_exception!(
+    hdwallet_py,
+    ChainPathError,
+    HDWalletError,
+    "Malformed chain path."
+);
+create_exception!(
+    hdwallet_py,
+    Secp256k1Error,
+    HDWalletError,
+    "Invalid secp256k1 key or tweak."
+);
+create_exception!(
+    hdwallet_py,
+    RngError,
+    HDWalletError,
+    "Random number generator failure."
+);
+create_exception!(
+    hdwallet_py,
+    ChecksumError,
+    HDWalletError,
+    "Mismatched extended key checksum."
+);
+create_exception!(
+    hdwallet_py,
+    UnknownVersionError,
+    HDWalletError,
+    "Unknown extended key version bytes."
+);
+create_exception!(
+    hdwallet_py,
+    InvalidBase58Error,
+    HDWalletError,
+    "Invalid base58 string."
+);
+create_exception!(
+    hdwallet_py,
+    InvalidLengthError,
+    HDWalletError,
+    "Invalid extended key length."
+);
+create_exception!(
+    hdwallet_py,
+    MaxDepthExceededError,
+    HDWalletError,
+    "Derivation depth exceeds 255."
+);
+create_exception!(
+    hdwallet_py,
+    InvalidAddressError,
+    HDWalletError,
+    "Invalid Bitcoin address."
+);
+create_exception!(
+    hdwallet_py,
+    InvalidDescriptorError,
+    HDWalletError,
+    "Invalid output descriptor."
+);
+create_exception!(
+    hdwallet_py,
+    KeyOriginMismatchError,
+    HDWalletError,
+    "Key origin disagrees with the extended key."
+);
+create_exception!(
+    hdwallet_py,
+    InvalidTransactionError,
+    HDWalletError,
+    "Invalid Bitcoin transaction."
+);
+create_exception!(
+    hdwallet_py,
+    InvalidPsbtError,
+    HDWalletError,
+    "Invalid partially signed Bitcoin transaction."
+);
+create_exception!(
+    hdwallet_py,
+    InvalidMultisigError,
+    HDWalletError,
+    "Multisig threshold, key count or script type out of range."
+);
+create_exception!(
+    hdwallet_py,
+    InvalidSignatureError,
+    HDWalletError,
+    "Invalid Bitcoin message signature."
+);
+create_exception!(
+    hdwallet_py,
+    InvalidPaymentCodeError,
+    HDWalletError,
+    "Invalid BIP-47 payment code."
+);
+create_exception!(
+    hdwallet_py,
+    InvalidMusigError,
+    HDWalletError,
+    "Invalid MuSig2 nonce, partial signature or signer."
+);
+create_exception!(
+    hdwallet_py,
+    InvalidSeedError,
+    HDWalletError,
+    "Invalid Electrum seed."
+);
+create_exception!(
+    hdwallet_py,
+    UnsupportedDescriptorError,
+    HDWalletError,
+    "Output descriptor not supported, e.g. a taproot script tree."
+);
+
+/// `hdwallet::Error` is a subset of `hdwallet_bitcoin::Error`, both are raised through this type.
+pub(crate) struct Error(BitcoinError);
+
+impl From<BitcoinError> for Error {
+    fn from(err: BitcoinError) -> Self {
+        Error(err)
+    }
+}
+
+impl From<hdwallet::error::Error> for Error {
+    fn from(err: hdwallet::error::Error) -> Self {
+        Error(err.into())
+    }
+}
+
+impl From<secp256k1::Error> for Error {
+    fn from(err: secp256k1::Error) -> Self {
+        Error(err.into())
+    }
+}
+
+impl From<CoreChainPathError> for Error {
+    fn from(err: CoreChainPathError) -> Self {
+        Error(err.into())
+    }
+}
+
+impl From<Error> for PyErr {
+    fn from(Error(err): Error) -> Self {
+        let message = err.to_string();
+        match err {
+            BitcoinError::MisChecksum => ChecksumError::new_err(message),
+            BitcoinError::UnknownVersion | BitcoinError::UnknownNetworkByte(_) => {
+                UnknownVersionError::new_err(message)
+            }
+            BitcoinError::Secp(_) => Secp256k1Error::new_err(message),
+            BitcoinError::InvalidBase58 => InvalidBase58Error::new_err(message),
+            BitcoinError::InvalidLength => InvalidLengthError::new_err(message),
+            BitcoinError::KeyIndexOutOfRange => KeyIndexOutOfRangeError::new_err(message),
+            BitcoinError::ChainPath(_) => ChainPathError::new_err(message),
+            BitcoinError::MaxDepthExceeded => MaxDepthExceededError::new_err(message),
+            BitcoinError::Rng => RngError::new_err(message),
+            BitcoinError::InvalidAddress => InvalidAddressError::new_err(message),
+            BitcoinError::InvalidDescriptor => InvalidDescriptorError::new_err(message),
+            BitcoinError::KeyOriginMismatch => KeyOriginMismatchError::new_err(message),
+            BitcoinError::InvalidTransaction => InvalidTransactionError::new_err(message),
+            BitcoinError::InvalidPsbt => InvalidPsbtError::new_err(message),
+            BitcoinError::InvalidMultisig => InvalidMultisigError::new_err(message),
+            BitcoinError::InvalidSignature => InvalidSignatureError::new_err(message),
+            BitcoinError::InvalidPaymentCode => InvalidPaymentCodeError::new_err(message),
+            BitcoinError::InvalidMusig => InvalidMusigError::new_err(message),
+            BitcoinError::InvalidSeed => InvalidSeedError::new_err(message),
+            BitcoinError::UnsupportedDescriptor => UnsupportedDescriptorError::new_err(message),
+        }
+    }
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("HDWalletError", py.get_type::<HDWalletError>())?;
+    m.add(
+        "KeyIndexOutOfRangeError",
+        py.get_type::<KeyIndexOutOfRangeError>(),
+    )?;
+    m.add("ChainPathError", py.get_type::<ChainPathError>())?;
+    m.add("Secp256k1Error", py.get_type::<Secp256k1Error>())?;
+    m.add("RngError", py.get_type::<RngError>())?;
+    m.add("ChecksumError", py.get_type::<ChecksumError>())?;
+    m.add("UnknownVersionError", py.get_type::<UnknownVersionError>())?;
+    m.add("InvalidBase58Error", py.get_type::<InvalidBase58Error>())?;
+    m.add("InvalidLengthError", py.get_type::<InvalidLengthError>())?;
+    m.add(
+        "MaxDepthExceededError",
+        py.get_type::<MaxDepthExceededError>(),
+    )?;
+    m.add("InvalidAddressError", py.get_type::<InvalidAddressError>())?;
+    m.add(
+        "InvalidDescriptorError",
+        py.get_type::<InvalidDescriptorError>(),
+    )?;
+    m.add(
+        "KeyOriginMismatchError",
+        py.get_type::<KeyOriginMismatchError>(),
+    )?;
+    m.add(
+        "InvalidTransactionError",
+        py.get_type::<InvalidTransactionError>(),
+    )?;
+    m.add("InvalidPsbtError", py.get_type::<InvalidPsbtError>())?;
+    m.add(
+        "InvalidMultisigError",
+        py.get_type::<InvalidMultisigError>(),
+    )?;
+    m.add(
+        "InvalidSignatureError",
+        py.get_type::<InvalidSignatureError>(),
+    )?;
+    m.add(
+        "InvalidPaymentCodeError",
+        py.get_type::<InvalidPaymentCodeError>(),
+    )?;
+    m.add("InvalidMusigError", py.get_type::<InvalidMusigError>())?;
+    m.add("InvalidSeedError", py.get_type::<InvalidSeedError>())?;
+    m.add(
+        "UnsupportedDescriptorError",
+        py.get_type::<UnsupportedDescriptorError>(),
+    )?;
+    Ok(())
+}
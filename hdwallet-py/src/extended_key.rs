@@ -0,0 +1,137 @@
+use crate::error::Error;
+use hdwallet::{
+    secp256k1::{PublicKey, SecretKey},
+    KeySeed,
+};
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyBytes};
+
+/// BIP-32 extended private key, `ExtendedPrivKey(private_key, chain_code)`.
+#[pyclass(module = "hdwallet_py")]
+#[derive(Clone)]
+pub(crate) struct ExtendedPrivKey(pub(crate) hdwallet::ExtendedPrivKey);
+
+#[pymethods]
+impl ExtendedPrivKey {
+    #[new]
+    fn new(private_key: &[u8], chain_code: &[u8]) -> PyResult<Self> {
+        let private_key = SecretKey::from_slice(private_key).map_err(Error::from)?;
+        Ok(ExtendedPrivKey(hdwallet::ExtendedPrivKey::new(
+            private_key,
+            chain_code.to_vec(),
+        )))
+    }
+
+    /// Master key generated from `seed`.
+    #[staticmethod]
+    fn with_seed(seed: &[u8]) -> PyResult<Self> {
+        Ok(ExtendedPrivKey(
+            hdwallet::ExtendedPrivKey::with_seed(seed).map_err(Error::from)?,
+        ))
+    }
+
+    /// Master key generated from a random seed of 128, 256 or 512 bits.
+    #[staticmethod]
+    #[pyo3(signature = (seed_bits = 256))]
+    fn random(seed_bits: u32) -> PyResult<Self> {
+        let seed_size = match seed_bits {
+            128 => KeySeed::S128,
+            256 => KeySeed::S256,
+            512 => KeySeed::S512,
+            _ => return Err(PyValueError::new_err("seed_bits must be 128, 256 or 512")),
+        };
+        let key =
+            hdwallet::ExtendedPrivKey::random_with_seed_size(&mut rand::thread_rng(), seed_size)
+                .map_err(Error::from)?;
+        Ok(ExtendedPrivKey(key))
+    }
+
+    #[getter]
+    fn private_key<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.0.private_key()[..])
+    }
+
+    #[getter]
+    fn chain_code<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.0.chain_code())
+    }
+
+    /// Compressed SEC1 public key.
+    #[getter]
+    fn public_key<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.0.public_key().serialize())
+    }
+
+    /// Derive a child key, indexes from `2 ** 31` are hardened.
+    fn derive_private_key(&self, index: u32) -> PyResult<Self> {
+        Ok(ExtendedPrivKey(
+            self.0
+                .derive_private_key(index.into())
+                .map_err(Error::from)?,
+        ))
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+
+    fn __repr__(&self) -> String {
+        // never print the private key
+        format!(
+            "ExtendedPrivKey(public_key={})",
+            hex::encode(self.0.public_key().serialize())
+        )
+    }
+}
+
+/// BIP-32 extended public key, `ExtendedPubKey(public_key, chain_code)`.
+#[pyclass(module = "hdwallet_py")]
+#[derive(Clone)]
+pub(crate) struct ExtendedPubKey(pub(crate) hdwallet::ExtendedPubKey);
+
+#[pymethods]
+impl ExtendedPubKey {
+    #[new]
+    fn new(public_key: &[u8], chain_code: &[u8]) -> PyResult<Self> {
+        let public_key = PublicKey::from_slice(public_key).map_err(Error::from)?;
+        Ok(ExtendedPubKey(hdwallet::ExtendedPubKey {
+            public_key,
+            chain_code: chain_code.to_vec(),
+        }))
+    }
+
+    #[staticmethod]
+    fn from_private_key(key: &ExtendedPrivKey) -> Self {
+        ExtendedPubKey(hdwallet::ExtendedPubKey::from_private_key(&key.0))
+    }
+
+    /// Compressed SEC1 public key.
+    #[getter]
+    fn public_key<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.0.public_key.serialize())
+    }
+
+    #[getter]
+    fn chain_code<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.0.chain_code)
+    }
+
+    /// Derive a normal child key, hardened indexes raise `KeyIndexOutOfRangeError`.
+    fn derive_public_key(&self, index: u32) -> PyResult<Self> {
+        Ok(ExtendedPubKey(
+            self.0
+                .derive_public_key(index.into())
+                .map_err(Error::from)?,
+        ))
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ExtendedPubKey(public_key={})",
+            hex::encode(self.0.public_key.serialize())
+        )
+    }
+}
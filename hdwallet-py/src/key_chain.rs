@@ -0,0 +1,140 @@
+use crate::{error::Error, extended_key::ExtendedPrivKey};
+use hdwallet::{KeyChain, KeyIndex, SubPath};
+use pyo3::prelude::*;
+
+/// Derivation path such as `m/0H/1`, hardened indexes are marked with `H` or `'`.
+#[pyclass(module = "hdwallet_py")]
+#[derive(Clone)]
+pub(crate) struct ChainPath {
+    path: String,
+}
+
+impl ChainPath {
+    fn as_core(&self) -> hdwallet::ChainPath<'_> {
+        hdwallet::ChainPath::from(self.path.as_str())
+    }
+}
+
+#[pymethods]
+impl ChainPath {
+    #[new]
+    fn new(path: String) -> Self {
+        ChainPath { path }
+    }
+
+    /// Raw child indexes after `m`, hardened indexes are from `2 ** 31`.
+    fn indexes(&self) -> PyResult<Vec<u32>> {
+        let mut indexes = Vec::new();
+        for sub_path in self.as_core().iter() {
+            match sub_path.map_err(Error::from)? {
+                SubPath::Root => continue,
+                SubPath::Child(key_index) => indexes.push(key_index.raw_index()),
+            }
+        }
+        Ok(indexes)
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+
+    fn __str__(&self) -> &str {
+        &self.path
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ChainPath({:?})", self.path)
+    }
+}
+
+/// Either a `ChainPath` or a path string.
+#[derive(FromPyObject)]
+pub(crate) enum PathArg {
+    ChainPath(ChainPath),
+    Str(String),
+}
+
+impl PathArg {
+    pub(crate) fn into_core(self) -> hdwallet::ChainPath<'static> {
+        match self {
+            PathArg::ChainPath(path) => path.path.into(),
+            PathArg::Str(path) => path.into(),
+        }
+    }
+}
+
+/// Key derivation info, see `DefaultKeyChain.derive_private_key`.
+#[pyclass(module = "hdwallet_py")]
+#[derive(Clone)]
+pub(crate) struct Derivation(pub(crate) hdwallet::Derivation);
+
+#[pymethods]
+impl Derivation {
+    #[new]
+    #[pyo3(signature = (depth = 0, key_index = None, parent_key = None))]
+    fn new(depth: u8, key_index: Option<u32>, parent_key: Option<ExtendedPrivKey>) -> Self {
+        Derivation(hdwallet::Derivation {
+            depth,
+            parent_key: parent_key.map(|key| key.0),
+            key_index: key_index.map(KeyIndex::from),
+        })
+    }
+
+    /// Derivation of a master key.
+    #[staticmethod]
+    fn master() -> Self {
+        Derivation(hdwallet::Derivation::master())
+    }
+
+    #[getter]
+    fn depth(&self) -> u8 {
+        self.0.depth
+    }
+
+    /// Raw index used to derive this key, `None` for master key.
+    #[getter]
+    fn key_index(&self) -> Option<u32> {
+        self.0.key_index.map(KeyIndex::raw_index)
+    }
+
+    #[getter]
+    fn parent_key(&self) -> Option<ExtendedPrivKey> {
+        self.0.parent_key.clone().map(ExtendedPrivKey)
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+
+    fn __repr__(&self) -> String {
+        let key_index = match self.0.key_index {
+            Some(key_index) => key_index.raw_index().to_string(),
+            None => "None".to_string(),
+        };
+        format!(
+            "Derivation(depth={}, key_index={})",
+            self.0.depth, key_index
+        )
+    }
+}
+
+/// Derive keys of a master key by chain path.
+#[pyclass(module = "hdwallet_py")]
+pub(crate) struct DefaultKeyChain(hdwallet::DefaultKeyChain);
+
+#[pymethods]
+impl DefaultKeyChain {
+    #[new]
+    fn new(master_key: ExtendedPrivKey) -> Self {
+        DefaultKeyChain(hdwallet::DefaultKeyChain::new(master_key.0))
+    }
+
+    /// Return `(ExtendedPrivKey, Derivation)` of `path`, which is a `ChainPath` or a string.
+    fn derive_private_key(&self, path: PathArg) -> PyResult<(ExtendedPrivKey, Derivation)> {
+        let (key, derivation) = self
+            .0
+            .derive_private_key(path.into_core())
+            .map_err(Error::from)?;
+        Ok((ExtendedPrivKey(key), Derivation(derivation)))
+    }
+}
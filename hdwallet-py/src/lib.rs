@@ -0,0 +1,39 @@
+//! Python bindings of `hdwallet` and `hdwallet-bitcoin`.
+//!
+//! Build with [maturin](https://www.maturin.rs/):
+//!
+//! ``` bash
+//! cd hdwallet-py && maturin develop
+//! ```
+//!
+//! ``` python
+//! from hdwallet_py import BitcoinPrivKey, ExtendedPrivKey, Network
+//!
+//! master = BitcoinPrivKey.from_master_key(ExtendedPrivKey.with_seed(seed), Network.MainNet)
+//! account = master.derive_path("m/44H/0H/0H")
+//! print(account.serialize())
+//! ```
+//!
+//! Errors are raised as subclasses of `HDWalletError`, itself a `ValueError`.
+
+mod bitcoin;
+mod error;
+mod extended_key;
+mod key_chain;
+
+use pyo3::prelude::*;
+
+#[pymodule]
+fn hdwallet_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<extended_key::ExtendedPrivKey>()?;
+    m.add_class::<extended_key::ExtendedPubKey>()?;
+    m.add_class::<key_chain::ChainPath>()?;
+    m.add_class::<key_chain::Derivation>()?;
+    m.add_class::<key_chain::DefaultKeyChain>()?;
+    m.add_class::<bitcoin::Network>()?;
+    m.add_class::<bitcoin::BitcoinPrivKey>()?;
+    m.add_class::<bitcoin::BitcoinPubKey>()?;
+    m.add("HARDENED_KEY_START_INDEX", 1u32 << 31)?;
+    error::register(m)?;
+    Ok(())
+}
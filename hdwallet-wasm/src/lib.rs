@@ -0,0 +1,168 @@
+//! WebAssembly bindings of `hdwallet` and `hdwallet-bitcoin`.
+//!
+//! Build with [wasm-pack](https://rustwasm.github.io/wasm-pack/):
+//!
+//! ``` bash
+//! wasm-pack build --target web hdwallet-wasm
+//! ```
+//!
+//! ``` js
+//! import { Network, PrivateKey } from "hdwallet-wasm";
+//!
+//! const master = PrivateKey.fromSeed(seed, Network.MainNet);
+//! const account = master.derive("m/44H/0H/0H");
+//! console.log(account.toBase58(), account.publicKey().toBase58());
+//! ```
+
+use hdwallet::{
+    traits::{Deserialize, Serialize},
+    ExtendedPrivKey,
+};
+use hdwallet_bitcoin::{Error as BitcoinError, Network as BitcoinNetwork, PrivKey, PubKey};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Network {
+    MainNet,
+    TestNet,
+    Regtest,
+    Signet,
+}
+
+impl From<Network> for BitcoinNetwork {
+    fn from(network: Network) -> Self {
+        match network {
+            Network::MainNet => BitcoinNetwork::MainNet,
+            Network::TestNet => BitcoinNetwork::TestNet,
+            Network::Regtest => BitcoinNetwork::Regtest,
+            Network::Signet => BitcoinNetwork::Signet,
+        }
+    }
+}
+
+/// Only Bitcoin networks are exposed, keys with unknown versions are rejected.
+impl TryFrom<BitcoinNetwork> for Network {
+    type Error = BitcoinError;
+
+    fn try_from(network: BitcoinNetwork) -> Result<Self, Self::Error> {
+        match network {
+            BitcoinNetwork::MainNet => Ok(Network::MainNet),
+            BitcoinNetwork::TestNet => Ok(Network::TestNet),
+            BitcoinNetwork::Regtest => Ok(Network::Regtest),
+            BitcoinNetwork::Signet => Ok(Network::Signet),
+            BitcoinNetwork::Custom(_) | BitcoinNetwork::Unknown(_) => {
+                Err(BitcoinError::UnknownVersion)
+            }
+        }
+    }
+}
+
+/// Extended private key, serialized as xprv/tprv.
+#[wasm_bindgen]
+pub struct PrivateKey(PrivKey);
+
+#[wasm_bindgen]
+impl PrivateKey {
+    /// Create master key from seed bytes.
+    #[wasm_bindgen(js_name = fromSeed)]
+    pub fn from_seed(seed: &[u8], network: Network) -> Result<PrivateKey, JsError> {
+        let extended_key = ExtendedPrivKey::with_seed(seed)?;
+        Ok(PrivateKey(PrivKey::from_master_key(
+            extended_key,
+            network.into(),
+        )))
+    }
+
+    #[wasm_bindgen(js_name = fromBase58)]
+    pub fn from_base58(key: &str) -> Result<PrivateKey, JsError> {
+        let key = PrivKey::deserialize(key.to_string())?;
+        Network::try_from(key.network)?;
+        Ok(PrivateKey(key))
+    }
+
+    #[wasm_bindgen(js_name = toBase58)]
+    pub fn to_base58(&self) -> String {
+        self.0.serialize()
+    }
+
+    /// Derive a descendant key, `m` in path denotes this key, e.g. `m/0H/1`.
+    pub fn derive(&self, path: &str) -> Result<PrivateKey, JsError> {
+        Ok(PrivateKey(self.0.derive_path(path.into())?))
+    }
+
+    #[wasm_bindgen(js_name = publicKey)]
+    pub fn public_key(&self) -> Result<PublicKey, JsError> {
+        Ok(PublicKey(PubKey::from_private_key(&self.0)?))
+    }
+
+    /// Key fingerprint as hex string.
+    pub fn fingerprint(&self) -> String {
+        hex::encode(self.0.fingerprint())
+    }
+
+    #[wasm_bindgen(getter, js_name = parentFingerprint)]
+    pub fn parent_fingerprint(&self) -> String {
+        hex::encode(self.0.parent_fingerprint())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn depth(&self) -> u8 {
+        self.0.derivation.depth
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn network(&self) -> Result<Network, JsError> {
+        Ok(Network::try_from(self.0.network)?)
+    }
+}
+
+/// Extended public key, serialized as xpub/tpub.
+#[wasm_bindgen]
+pub struct PublicKey(PubKey);
+
+#[wasm_bindgen]
+impl PublicKey {
+    #[wasm_bindgen(js_name = fromBase58)]
+    pub fn from_base58(key: &str) -> Result<PublicKey, JsError> {
+        let key = PubKey::deserialize(key.to_string())?;
+        Network::try_from(key.network)?;
+        Ok(PublicKey(key))
+    }
+
+    #[wasm_bindgen(js_name = toBase58)]
+    pub fn to_base58(&self) -> String {
+        self.0.serialize()
+    }
+
+    /// Derive a descendant key, `m` in path denotes this key, hardened indexes are rejected.
+    pub fn derive(&self, path: &str) -> Result<PublicKey, JsError> {
+        Ok(PublicKey(self.0.derive_path(path.into())?))
+    }
+
+    /// Compressed SEC1 public key as hex string.
+    #[wasm_bindgen(getter, js_name = publicKeyHex)]
+    pub fn public_key_hex(&self) -> String {
+        hex::encode(self.0.extended_key.public_key.serialize())
+    }
+
+    /// Key fingerprint as hex string.
+    pub fn fingerprint(&self) -> String {
+        hex::encode(self.0.fingerprint())
+    }
+
+    #[wasm_bindgen(getter, js_name = parentFingerprint)]
+    pub fn parent_fingerprint(&self) -> String {
+        hex::encode(self.0.parent_fingerprint())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn depth(&self) -> u8 {
+        self.0.derivation.depth
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn network(&self) -> Result<Network, JsError> {
+        Ok(Network::try_from(self.0.network)?)
+    }
+}
@@ -0,0 +1,58 @@
+//! Run with `wasm-pack test --node hdwallet-wasm`.
+#![cfg(target_arch = "wasm32")]
+
+use hdwallet_wasm::{Network, PrivateKey, PublicKey};
+use wasm_bindgen_test::*;
+
+// BIP-32 test vector 1
+const SEED: &str = "000102030405060708090a0b0c0d0e0f";
+
+#[wasm_bindgen_test]
+fn master_key_from_seed() {
+    let master_key =
+        PrivateKey::from_seed(&hex::decode(SEED).unwrap(), Network::MainNet).expect("master key");
+    assert_eq!(master_key.to_base58(), "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi");
+    assert_eq!(master_key.public_key().expect("public key").to_base58(), "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8");
+    assert_eq!(master_key.fingerprint(), "3442193e");
+    assert_eq!(master_key.parent_fingerprint(), "00000000");
+    assert_eq!(master_key.depth(), 0);
+    assert_eq!(master_key.network().expect("network"), Network::MainNet);
+}
+
+#[wasm_bindgen_test]
+fn derive_chain_path() {
+    let master_key =
+        PrivateKey::from_seed(&hex::decode(SEED).unwrap(), Network::MainNet).expect("master key");
+    let key = master_key.derive("m/0H/1/2H").expect("derive");
+    assert_eq!(key.to_base58(), "xprv9z4pot5VBttmtdRTWfWQmoH1taj2axGVzFqSb8C9xaxKymcFzXBDptWmT7FwuEzG3ryjH4ktypQSAewRiNMjANTtpgP4mLTj34bhnZX7UiM");
+    assert_eq!(key.depth(), 3);
+    assert_eq!(key.parent_fingerprint(), "bef5a2f9");
+    let pub_key = key
+        .public_key()
+        .expect("public key")
+        .derive("m/2/1000000000")
+        .expect("derive");
+    assert_eq!(pub_key.to_base58(), "xpub6H1LXWLaKsWFhvm6RVpEL9P4KfRZSW7abD2ttkWP3SSQvnyA8FSVqNTEcYFgJS2UaFcxupHiYkro49S8yGasTvXEYBVPamhGW6cFJodrTHy");
+    assert_eq!(pub_key.depth(), 5);
+    assert!(key
+        .public_key()
+        .expect("public key")
+        .derive("m/0H")
+        .is_err());
+}
+
+#[wasm_bindgen_test]
+fn encode_decode() {
+    let xprv = "xprv9wTYmMFdV23N2TdNG573QoEsfRrWKQgWeibmLntzniatZvR9BmLnvSxqu53Kw1UmYPxLgboyZQaXwTCg8MSY3H2EU4pWcQDnRnrVA1xe8fs";
+    let xpub = "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ";
+    let priv_key = PrivateKey::from_base58(xprv).expect("decode");
+    assert_eq!(priv_key.to_base58(), xprv);
+    assert_eq!(priv_key.public_key().expect("public key").to_base58(), xpub);
+    let pub_key = PublicKey::from_base58(xpub).expect("decode");
+    assert_eq!(pub_key.to_base58(), xpub);
+    assert_eq!(
+        pub_key.public_key_hex(),
+        "03501e454bf00751f24b1b489aa925215d66af2234e3891c3b21a52bedb3cd711c"
+    );
+    assert!(PublicKey::from_base58("xpub6ASuArnXKPbf").is_err());
+}
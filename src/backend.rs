@@ -0,0 +1,223 @@
+//! Hash functions used by key derivation.
+//!
+//! The backend is selected by cargo feature:
+//!
+//! * `ring` (default) uses [ring](https://docs.rs/ring).
+//! * `rustcrypto` uses the pure-Rust [hmac](https://docs.rs/hmac) and [sha2](https://docs.rs/sha2)
+//!   crates, which also build for `no_std` targets.
+//!
+//! [`DefaultBackend`] is `ring` when both features are enabled.
+
+/// Length of HMAC-SHA512 output.
+pub const HMAC_SHA512_LEN: usize = 64;
+/// Length of SHA256 output.
+pub const SHA256_LEN: usize = 32;
+
+/// Hash functions required by BIP-32 derivation.
+pub trait Backend {
+    /// Keyed HMAC-SHA512 state, reusable for several messages under the same key.
+    type HmacKey: Clone + Send + Sync;
+
+    fn hmac_sha512_key(key: &[u8]) -> Self::HmacKey;
+
+    /// HMAC-SHA512 of the concatenation of `data`.
+    fn hmac_sha512(key: &Self::HmacKey, data: &[&[u8]]) -> [u8; HMAC_SHA512_LEN];
+
+    /// SHA256 of the concatenation of `data`.
+    fn sha256(data: &[&[u8]]) -> [u8; SHA256_LEN];
+}
+
+#[cfg(feature = "ring")]
+pub use self::ring_backend::Ring;
+
+#[cfg(feature = "ring")]
+mod ring_backend {
+    use super::{Backend, HMAC_SHA512_LEN, SHA256_LEN};
+    use ring::{digest, hmac};
+
+    /// Backend implemented with `ring`.
+    #[derive(Debug, Clone, Copy)]
+    pub struct Ring;
+
+    impl Backend for Ring {
+        type HmacKey = hmac::Key;
+
+        fn hmac_sha512_key(key: &[u8]) -> Self::HmacKey {
+            hmac::Key::new(hmac::HMAC_SHA512, key)
+        }
+
+        fn hmac_sha512(key: &Self::HmacKey, data: &[&[u8]]) -> [u8; HMAC_SHA512_LEN] {
+            let mut h = hmac::Context::with_key(key);
+            for part in data {
+                h.update(part);
+            }
+            let mut tag = [0u8; HMAC_SHA512_LEN];
+            tag.copy_from_slice(h.sign().as_ref());
+            tag
+        }
+
+        fn sha256(data: &[&[u8]]) -> [u8; SHA256_LEN] {
+            let mut h = digest::Context::new(&digest::SHA256);
+            for part in data {
+                h.update(part);
+            }
+            let mut hash = [0u8; SHA256_LEN];
+            hash.copy_from_slice(h.finish().as_ref());
+            hash
+        }
+    }
+}
+
+#[cfg(feature = "rustcrypto")]
+pub use self::rustcrypto_backend::RustCrypto;
+
+#[cfg(feature = "rustcrypto")]
+mod rustcrypto_backend {
+    use super::{Backend, HMAC_SHA512_LEN, SHA256_LEN};
+    use hmac::{Hmac, Mac};
+    use sha2::{Digest, Sha256, Sha512};
+
+    /// Backend implemented with the RustCrypto `hmac` and `sha2` crates.
+    #[derive(Debug, Clone, Copy)]
+    pub struct RustCrypto;
+
+    impl Backend for RustCrypto {
+        type HmacKey = Hmac<Sha512>;
+
+        fn hmac_sha512_key(key: &[u8]) -> Self::HmacKey {
+            Hmac::new_from_slice(key).expect("HMAC accepts keys of any length")
+        }
+
+        fn hmac_sha512(key: &Self::HmacKey, data: &[&[u8]]) -> [u8; HMAC_SHA512_LEN] {
+            let mut h = key.clone();
+            for part in data {
+                h.update(part);
+            }
+            h.finalize().into_bytes().into()
+        }
+
+        fn sha256(data: &[&[u8]]) -> [u8; SHA256_LEN] {
+            let mut h = Sha256::new();
+            for part in data {
+                h.update(part);
+            }
+            h.finalize().into()
+        }
+    }
+}
+
+#[cfg(feature = "ring")]
+pub type DefaultBackend = Ring;
+
+#[cfg(all(feature = "rustcrypto", not(feature = "ring")))]
+pub type DefaultBackend = RustCrypto;
+
+#[cfg(not(any(feature = "ring", feature = "rustcrypto")))]
+compile_error!(
+    "hdwallet requires a hash backend, enable either the `ring` or `rustcrypto` feature"
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 4231 test case 2
+    const HMAC_KEY: &[u8] = b"Jefe";
+    const HMAC_DATA: &[&[u8]] = &[b"what do ya want ", b"for nothing?"];
+    const HMAC_SHA512: &str = "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737";
+    // FIPS 180-2 "abc"
+    const SHA256_DATA: &[&[u8]] = &[b"a", b"bc"];
+    const SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    fn check_backend<B: Backend>() {
+        let key = B::hmac_sha512_key(HMAC_KEY);
+        assert_eq!(hex::encode(B::hmac_sha512(&key, HMAC_DATA)), HMAC_SHA512);
+        // keyed state is reusable
+        assert_eq!(hex::encode(B::hmac_sha512(&key, HMAC_DATA)), HMAC_SHA512);
+        assert_eq!(hex::encode(B::sha256(SHA256_DATA)), SHA256);
+    }
+
+    #[cfg(feature = "ring")]
+    #[test]
+    fn ring_backend() {
+        check_backend::<Ring>();
+    }
+
+    #[cfg(feature = "rustcrypto")]
+    #[test]
+    fn rustcrypto_backend() {
+        check_backend::<RustCrypto>();
+    }
+
+    /// First hardened child index.
+    #[cfg(all(feature = "ring", feature = "rustcrypto"))]
+    const H: u32 = 1 << 31;
+
+    /// BIP-32 private key and chain code at `path` from `seed`, hashed with backend `B`.
+    #[cfg(all(feature = "ring", feature = "rustcrypto"))]
+    fn derive<B: Backend>(seed: &[u8], path: &[u32]) -> (secp256k1::SecretKey, [u8; 32]) {
+        use secp256k1::{PublicKey, Scalar, Secp256k1, SecretKey};
+
+        let secp = Secp256k1::signing_only();
+        let i = B::hmac_sha512(&B::hmac_sha512_key(b"Bitcoin seed"), &[seed]);
+        let mut private_key = SecretKey::from_slice(&i[..32]).expect("private key");
+        let mut chain_code = [0; 32];
+        chain_code.copy_from_slice(&i[32..]);
+        for index in path {
+            let key = B::hmac_sha512_key(&chain_code);
+            let i = if *index >= H {
+                B::hmac_sha512(
+                    &key,
+                    &[&[0], &private_key.secret_bytes(), &index.to_be_bytes()],
+                )
+            } else {
+                let public_key = PublicKey::from_secret_key(&secp, &private_key);
+                B::hmac_sha512(&key, &[&public_key.serialize(), &index.to_be_bytes()])
+            };
+            let mut tweak = [0; 32];
+            tweak.copy_from_slice(&i[..32]);
+            private_key = private_key
+                .add_tweak(&Scalar::from_be_bytes(tweak).expect("tweak"))
+                .expect("child key");
+            chain_code.copy_from_slice(&i[32..]);
+        }
+        (private_key, chain_code)
+    }
+
+    #[cfg(all(feature = "ring", feature = "rustcrypto"))]
+    #[test]
+    fn backends_are_identical() {
+        use crate::{ExtendedPrivKey, KeyIndex};
+
+        // BIP-32 test vectors 1 to 3, every key on their paths
+        for (seed, path) in &[
+            (
+                "000102030405060708090a0b0c0d0e0f",
+                &[H, 1, H + 2, 2, 1_000_000_000][..],
+            ),
+            (
+                "fffcf9f6f3f0edeae7e4e1dedbd8d5d2cfccc9c6c3c0bdbab7b4b1aeaba8a5a29f9c999693908d8a8784817e7b7875726f6c696663605d5a5754514e4b484542",
+                &[0, H + 2_147_483_647, 1, H + 2_147_483_646, 2][..],
+            ),
+            (
+                "4b381541583be4423346c643850da4b320e46a87ae3d2a4e6da11eba819cd4acba45d239319ac14f863b8d5ab5a0d0c64d2e8a1e7d1457df2e5a3c51c73235be",
+                &[H][..],
+            ),
+        ] {
+            let seed = hex::decode(seed).expect("decode");
+            assert_eq!(Ring::sha256(&[&seed]), RustCrypto::sha256(&[&seed]));
+            let mut key = ExtendedPrivKey::with_seed(&seed).expect("master key");
+            for depth in 0..=path.len() {
+                let ring = derive::<Ring>(&seed, &path[..depth]);
+                assert_eq!(ring, derive::<RustCrypto>(&seed, &path[..depth]));
+                // and the default backend derives the same keys
+                assert_eq!(ring, (key.private_key(), key.chain_code().try_into().unwrap()));
+                if let Some(index) = path.get(depth) {
+                    key = key
+                        .derive_private_key(KeyIndex::from(*index))
+                        .expect("derive");
+                }
+            }
+        }
+    }
+}
@@ -1,20 +1,30 @@
 pub use crate::ChainPathError;
 
+use core::fmt;
 use rand_core;
-use thiserror::Error;
 
-#[derive(Error, Debug)]
+#[derive(Debug)]
 pub enum Error {
-    #[error("Key index out of range")]
     KeyIndexOutOfRange,
-    #[error("Chain path {0}")]
     ChainPath(ChainPathError),
-    #[error("Secp256k1 error {0}")]
     Secp(secp256k1::Error),
-    #[error("rand error {0}")]
     Rng(rand_core::Error),
 }
 
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::KeyIndexOutOfRange => write!(f, "Key index out of range"),
+            Error::ChainPath(err) => write!(f, "Chain path {}", err),
+            Error::Secp(err) => write!(f, "Secp256k1 error {}", err),
+            Error::Rng(err) => write!(f, "rand error {}", err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
 impl From<ChainPathError> for Error {
     fn from(err: ChainPathError) -> Error {
         Error::ChainPath(err)
@@ -1,17 +1,28 @@
 pub mod key_index;
 
 use crate::{
+    backend::{Backend, DefaultBackend, HMAC_SHA512_LEN},
     error::Error,
     traits::{Deserialize, Serialize},
 };
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::fmt;
 use key_index::KeyIndex;
+use once_cell::race::OnceBox;
 use rand_core::{CryptoRng, RngCore};
-use ring::hmac::{Context, Key, HMAC_SHA512};
-use secp256k1::{PublicKey, Secp256k1, SecretKey, SignOnly, VerifyOnly};
+use secp256k1::{PublicKey, Secp256k1, SecretKey, SignOnly, Signing, Verification, VerifyOnly};
+use zeroize::Zeroize;
 
-lazy_static! {
-    static ref SECP256K1_SIGN_ONLY: Secp256k1<SignOnly> = Secp256k1::signing_only();
-    static ref SECP256K1_VERIFY_ONLY: Secp256k1<VerifyOnly> = Secp256k1::verification_only();
+/// Global signing context, used when the caller doesn't provide one.
+fn secp256k1_sign_only() -> &'static Secp256k1<SignOnly> {
+    static CONTEXT: OnceBox<Secp256k1<SignOnly>> = OnceBox::new();
+    CONTEXT.get_or_init(|| Box::new(Secp256k1::signing_only()))
+}
+
+/// Global verification context, used when the caller doesn't provide one.
+fn secp256k1_verify_only() -> &'static Secp256k1<VerifyOnly> {
+    static CONTEXT: OnceBox<Secp256k1<VerifyOnly>> = OnceBox::new();
+    CONTEXT.get_or_init(|| Box::new(Secp256k1::verification_only()))
 }
 
 /// Random entropy, part of extended key.
@@ -34,10 +45,47 @@ type ChainCode = Vec<u8>;
 /// let normal_key_index = KeyIndex::Normal(0);
 /// let noamal_child_priv_key = master_key.derive_private_key(normal_key_index).unwrap();
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ExtendedPrivKey {
-    pub private_key: SecretKey,
-    pub chain_code: ChainCode,
+    private_key: SecretKey,
+    chain_code: ChainCode,
+    cache: OnceBox<DerivationCache>,
+}
+
+/// Parent key state shared by all child derivations, computed on first use.
+///
+/// Hardened children only need the HMAC key, the public key is computed on the first normal
+/// derivation. The key fields are private, so the cache can't go stale.
+struct DerivationCache {
+    public_key: OnceBox<PublicKey>,
+    signing_key: <DefaultBackend as Backend>::HmacKey,
+}
+
+impl DerivationCache {
+    fn new(chain_code: &[u8]) -> Self {
+        DerivationCache {
+            public_key: OnceBox::new(),
+            signing_key: DefaultBackend::hmac_sha512_key(chain_code),
+        }
+    }
+
+    fn public_key<C: Signing>(&self, secp: &Secp256k1<C>, private_key: &SecretKey) -> PublicKey {
+        *self
+            .public_key
+            .get_or_init(|| Box::new(PublicKey::from_secret_key(secp, private_key)))
+    }
+}
+
+impl Clone for DerivationCache {
+    fn clone(&self) -> Self {
+        let public_key = OnceBox::new();
+        if let Some(key) = self.public_key.get() {
+            let _ = public_key.set(Box::new(*key));
+        }
+        DerivationCache {
+            public_key,
+            signing_key: self.signing_key.clone(),
+        }
+    }
 }
 
 /// Indicate bits of random seed used to generate private key, 256 is recommended.
@@ -49,6 +97,15 @@ pub enum KeySeed {
 }
 
 impl ExtendedPrivKey {
+    /// Create an ExtendedPrivKey from private key and chain code.
+    pub fn new(private_key: SecretKey, chain_code: ChainCode) -> ExtendedPrivKey {
+        ExtendedPrivKey {
+            private_key,
+            chain_code,
+            cache: OnceBox::new(),
+        }
+    }
+
     /// Generate an ExtendedPrivKey, use 256 size random seed.
     pub fn random<R: RngCore + CryptoRng>(rng: &mut R) -> Result<ExtendedPrivKey, Error> {
         ExtendedPrivKey::random_with_seed_size(rng, KeySeed::S256)
@@ -69,56 +126,117 @@ impl ExtendedPrivKey {
 
     /// Generate an ExtendedPrivKey from seed
     pub fn with_seed(seed: &[u8]) -> Result<ExtendedPrivKey, Error> {
-        let signature = {
-            let signing_key = Key::new(HMAC_SHA512, b"Bitcoin seed");
-            let mut h = Context::with_key(&signing_key);
-            h.update(seed);
-            h.sign()
-        };
-        let sig_bytes = signature.as_ref();
-        let (key, chain_code) = sig_bytes.split_at(sig_bytes.len() / 2);
+        let signature =
+            DefaultBackend::hmac_sha512(&DefaultBackend::hmac_sha512_key(b"Bitcoin seed"), &[seed]);
+        let (key, chain_code) = signature.split_at(signature.len() / 2);
         let private_key = SecretKey::from_slice(key)?;
-        Ok(ExtendedPrivKey {
-            private_key,
-            chain_code: chain_code.to_vec(),
-        })
+        Ok(ExtendedPrivKey::new(private_key, chain_code.to_vec()))
+    }
+
+    /// Private key of this ExtendedPrivKey.
+    pub fn private_key(&self) -> SecretKey {
+        self.private_key
+    }
+
+    /// Chain code of this ExtendedPrivKey.
+    pub fn chain_code(&self) -> &[u8] {
+        &self.chain_code
+    }
+
+    fn cache(&self) -> &DerivationCache {
+        self.cache
+            .get_or_init(|| Box::new(DerivationCache::new(&self.chain_code)))
     }
 
-    fn sign_hardended_key(&self, index: u32) -> ring::hmac::Tag {
-        let signing_key = Key::new(HMAC_SHA512, &self.chain_code);
-        let mut h = Context::with_key(&signing_key);
-        h.update(&[0x00]);
-        h.update(&self.private_key[..]);
-        h.update(&index.to_be_bytes());
-        h.sign()
+    /// Public key of this ExtendedPrivKey, computed once and cached.
+    pub fn public_key(&self) -> PublicKey {
+        self.cache()
+            .public_key(secp256k1_sign_only(), &self.private_key)
+    }
+
+    fn sign_hardended_key(&self, index: u32) -> [u8; HMAC_SHA512_LEN] {
+        DefaultBackend::hmac_sha512(
+            &self.cache().signing_key,
+            &[&[0x00], &self.private_key[..], &index.to_be_bytes()],
+        )
     }
 
-    fn sign_normal_key(&self, index: u32) -> ring::hmac::Tag {
-        let signing_key = Key::new(HMAC_SHA512, &self.chain_code);
-        let mut h = Context::with_key(&signing_key);
-        let public_key = PublicKey::from_secret_key(&*SECP256K1_SIGN_ONLY, &self.private_key);
-        h.update(&public_key.serialize());
-        h.update(&index.to_be_bytes());
-        h.sign()
+    fn sign_normal_key<C: Signing>(
+        &self,
+        secp: &Secp256k1<C>,
+        index: u32,
+    ) -> [u8; HMAC_SHA512_LEN] {
+        let cache = self.cache();
+        DefaultBackend::hmac_sha512(
+            &cache.signing_key,
+            &[
+                &cache.public_key(secp, &self.private_key).serialize(),
+                &index.to_be_bytes(),
+            ],
+        )
     }
 
     /// Derive a child key from ExtendedPrivKey.
     pub fn derive_private_key(&self, key_index: KeyIndex) -> Result<ExtendedPrivKey, Error> {
+        self.derive_private_key_with_context(secp256k1_sign_only(), key_index)
+    }
+
+    /// Derive a child key from ExtendedPrivKey with a caller provided secp256k1 context.
+    pub fn derive_private_key_with_context<C: Signing>(
+        &self,
+        secp: &Secp256k1<C>,
+        key_index: KeyIndex,
+    ) -> Result<ExtendedPrivKey, Error> {
         if !key_index.is_valid() {
             return Err(Error::KeyIndexOutOfRange);
         }
         let signature = match key_index {
             KeyIndex::Hardened(index) => self.sign_hardended_key(index),
-            KeyIndex::Normal(index) => self.sign_normal_key(index),
+            KeyIndex::Normal(index) => self.sign_normal_key(secp, index),
         };
-        let sig_bytes = signature.as_ref();
-        let (key, chain_code) = sig_bytes.split_at(sig_bytes.len() / 2);
+        let (key, chain_code) = signature.split_at(signature.len() / 2);
         let private_key = SecretKey::from_slice(key)?;
         let private_key = private_key.add_tweak(&self.private_key.into())?;
-        Ok(ExtendedPrivKey {
-            private_key,
-            chain_code: chain_code.to_vec(),
-        })
+        Ok(ExtendedPrivKey::new(private_key, chain_code.to_vec()))
+    }
+}
+
+impl Clone for ExtendedPrivKey {
+    fn clone(&self) -> Self {
+        let key = ExtendedPrivKey::new(self.private_key, self.chain_code.clone());
+        if let Some(cache) = self.cache.get() {
+            let _ = key.cache.set(Box::new(cache.clone()));
+        }
+        key
+    }
+}
+
+impl fmt::Debug for ExtendedPrivKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ExtendedPrivKey")
+            .field("private_key", &self.private_key)
+            .field("chain_code", &self.chain_code)
+            .finish()
+    }
+}
+
+impl PartialEq for ExtendedPrivKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.private_key == other.private_key && self.chain_code == other.chain_code
+    }
+}
+
+impl Eq for ExtendedPrivKey {}
+
+/// Overwrite the private key, chain code and cached parent state.
+///
+/// The private key is replaced by a placeholder key, it must not be used afterwards.
+impl Zeroize for ExtendedPrivKey {
+    fn zeroize(&mut self) {
+        self.private_key.non_secure_erase();
+        self.chain_code.zeroize();
+        // dropping the cache erases it
+        self.cache = OnceBox::new();
     }
 }
 
@@ -154,6 +272,15 @@ impl ExtendedPubKey {
     /// Derive public normal child key from ExtendedPubKey,
     /// will return error if key_index is a hardened key.
     pub fn derive_public_key(&self, key_index: KeyIndex) -> Result<ExtendedPubKey, Error> {
+        self.derive_public_key_with_context(secp256k1_verify_only(), key_index)
+    }
+
+    /// Derive public normal child key from ExtendedPubKey with a caller provided secp256k1 context.
+    pub fn derive_public_key_with_context<C: Verification>(
+        &self,
+        secp: &Secp256k1<C>,
+        key_index: KeyIndex,
+    ) -> Result<ExtendedPubKey, Error> {
         if !key_index.is_valid() {
             return Err(Error::KeyIndexOutOfRange);
         }
@@ -163,18 +290,14 @@ impl ExtendedPubKey {
             KeyIndex::Hardened(_) => return Err(Error::KeyIndexOutOfRange),
         };
 
-        let signature = {
-            let signing_key = Key::new(HMAC_SHA512, &self.chain_code);
-            let mut h = Context::with_key(&signing_key);
-            h.update(&self.public_key.serialize());
-            h.update(&index.to_be_bytes());
-            h.sign()
-        };
-        let sig_bytes = signature.as_ref();
-        let (key, chain_code) = sig_bytes.split_at(sig_bytes.len() / 2);
+        let signature = DefaultBackend::hmac_sha512(
+            &DefaultBackend::hmac_sha512_key(&self.chain_code),
+            &[&self.public_key.serialize(), &index.to_be_bytes()],
+        );
+        let (key, chain_code) = signature.split_at(signature.len() / 2);
         let private_key = SecretKey::from_slice(key)?;
         let public_key = self.public_key;
-        let public_key = public_key.add_exp_tweak(&*SECP256K1_VERIFY_ONLY, &private_key.into())?;
+        let public_key = public_key.add_exp_tweak(secp, &private_key.into())?;
         Ok(ExtendedPubKey {
             public_key,
             chain_code: chain_code.to_vec(),
@@ -183,10 +306,18 @@ impl ExtendedPubKey {
 
     /// ExtendedPubKey from ExtendedPrivKey
     pub fn from_private_key(extended_key: &ExtendedPrivKey) -> Self {
-        let public_key =
-            PublicKey::from_secret_key(&*SECP256K1_SIGN_ONLY, &extended_key.private_key);
+        Self::from_private_key_with_context(secp256k1_sign_only(), extended_key)
+    }
+
+    /// ExtendedPubKey from ExtendedPrivKey with a caller provided secp256k1 context
+    pub fn from_private_key_with_context<C: Signing>(
+        secp: &Secp256k1<C>,
+        extended_key: &ExtendedPrivKey,
+    ) -> Self {
         ExtendedPubKey {
-            public_key,
+            public_key: extended_key
+                .cache()
+                .public_key(secp, &extended_key.private_key),
             chain_code: extended_key.chain_code.clone(),
         }
     }
@@ -203,10 +334,7 @@ impl Deserialize<&[u8], Error> for ExtendedPrivKey {
     fn deserialize(data: &[u8]) -> Result<Self, Error> {
         let private_key = SecretKey::from_slice(&data[..32])?;
         let chain_code = data[32..].to_vec();
-        Ok(ExtendedPrivKey {
-            private_key,
-            chain_code,
-        })
+        Ok(ExtendedPrivKey::new(private_key, chain_code))
     }
 }
 
@@ -233,6 +361,7 @@ mod tests {
     use super::{ExtendedPrivKey, ExtendedPubKey, KeyIndex};
     use crate::traits::{Deserialize, Serialize};
     use rand;
+    use secp256k1::Secp256k1;
 
     fn fetch_random_key() -> ExtendedPrivKey {
         let mut rng = rand::thread_rng();
@@ -294,6 +423,78 @@ mod tests {
         )
     }
 
+    #[test]
+    fn extended_priv_key_accessors() {
+        let key = fetch_random_key();
+        let rebuilt = ExtendedPrivKey::new(key.private_key(), key.chain_code().to_vec());
+        assert_eq!(rebuilt, key);
+        assert_eq!(rebuilt.public_key(), key.public_key());
+        assert_eq!(
+            rebuilt
+                .derive_private_key(KeyIndex::Normal(0))
+                .expect("child"),
+            key.derive_private_key(KeyIndex::Normal(0)).expect("child")
+        );
+    }
+
+    #[test]
+    fn hardened_derivation_skips_public_key() {
+        let key = fetch_random_key();
+        let hardened_key_index = KeyIndex::hardened_from_normalize_index(0).unwrap();
+        key.derive_private_key(hardened_key_index).expect("child");
+        assert!(key.cache.get().expect("cache").public_key.get().is_none());
+        key.derive_private_key(KeyIndex::Normal(0)).expect("child");
+        assert_eq!(
+            *key.cache
+                .get()
+                .expect("cache")
+                .public_key
+                .get()
+                .expect("public key"),
+            ExtendedPubKey::from_private_key(&key).public_key
+        );
+    }
+
+    #[test]
+    fn derive_with_caller_provided_context() {
+        let secp = Secp256k1::new();
+        let parent_priv_key = fetch_random_key();
+        let parent_pub_key = ExtendedPubKey::from_private_key_with_context(&secp, &parent_priv_key);
+        assert_eq!(
+            parent_pub_key,
+            ExtendedPubKey::from_private_key(&parent_priv_key)
+        );
+        let child_priv_key = parent_priv_key
+            .derive_private_key_with_context(&secp, KeyIndex::Normal(0))
+            .expect("private key");
+        assert_eq!(
+            child_priv_key,
+            parent_priv_key
+                .derive_private_key(KeyIndex::Normal(0))
+                .expect("private key")
+        );
+        assert_eq!(
+            parent_pub_key
+                .derive_public_key_with_context(&secp, KeyIndex::Normal(0))
+                .expect("public key"),
+            ExtendedPubKey::from_private_key_with_context(&secp, &child_priv_key)
+        );
+    }
+
+    #[test]
+    fn zeroize_extended_priv_key() {
+        use zeroize::Zeroize;
+
+        let mut key = fetch_random_key();
+        let original_key = key.clone();
+        // fill the cache before erasing
+        key.public_key();
+        key.zeroize();
+        assert_eq!(&key.private_key[..], &[1u8; 32]);
+        assert!(key.chain_code.is_empty());
+        assert_ne!(key.public_key(), original_key.public_key());
+    }
+
     #[test]
     fn priv_key_serialize_deserialize() {
         let key = fetch_random_key();
@@ -1,6 +1,7 @@
 pub mod chain_path;
 
 use crate::{error::Error, ChainPath, ChainPathError, ExtendedPrivKey, KeyIndex, SubPath};
+use zeroize::Zeroize;
 
 /// KeyChain derivation info
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -23,6 +24,16 @@ impl Derivation {
     }
 }
 
+/// Erase and drop the parent key.
+impl Zeroize for Derivation {
+    fn zeroize(&mut self) {
+        if let Some(parent_key) = self.parent_key.as_mut() {
+            parent_key.zeroize();
+        }
+        self.parent_key = None;
+    }
+}
+
 impl Default for Derivation {
     fn default() -> Self {
         Derivation::master()
@@ -102,9 +113,12 @@ impl KeyChain for DefaultKeyChain {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{traits::Serialize, ExtendedPubKey};
+    use crate::{
+        backend::{Backend, DefaultBackend},
+        traits::Serialize,
+        ExtendedPubKey,
+    };
     use base58::ToBase58;
-    use ring::digest;
     use ripemd160::{Digest, Ripemd160};
 
     #[derive(Debug, Clone, PartialEq, Eq)]
@@ -148,9 +162,9 @@ mod tests {
             match self.parent_key {
                 Some(ref key) => {
                     let pubkey = ExtendedPubKey::from_private_key(key);
-                    let buf = digest::digest(&digest::SHA256, &pubkey.public_key.serialize());
+                    let buf = sha256(&pubkey.public_key.serialize());
                     let mut hasher = Ripemd160::new();
-                    hasher.input(&buf.as_ref());
+                    hasher.input(&buf);
                     hasher.result()[0..4].to_vec()
                 }
                 None => vec![0; 4],
@@ -184,9 +198,9 @@ mod tests {
             }
             match self.key {
                 ExtendedKey::PrivKey(ref key) => {
-                    buf.extend_from_slice(&key.chain_code);
+                    buf.extend_from_slice(&key.chain_code());
                     buf.extend_from_slice(&[0]);
-                    buf.extend_from_slice(&key.private_key[..]);
+                    buf.extend_from_slice(&key.private_key()[..]);
                 }
                 ExtendedKey::PubKey(ref key) => {
                     buf.extend_from_slice(&key.chain_code);
@@ -195,16 +209,17 @@ mod tests {
             }
             assert_eq!(buf.len(), 78);
 
-            let check_sum = {
-                let buf = digest::digest(&digest::SHA256, &buf);
-                digest::digest(&digest::SHA256, buf.as_ref())
-            };
+            let check_sum = sha256(&sha256(&buf));
 
-            buf.extend_from_slice(&check_sum.as_ref()[0..4]);
+            buf.extend_from_slice(&check_sum[0..4]);
             (&buf).to_base58()
         }
     }
 
+    fn sha256(data: &[u8]) -> Vec<u8> {
+        DefaultBackend::sha256(&[data]).to_vec()
+    }
+
     fn from_hex(hex_string: &str) -> Vec<u8> {
         if let Some(hex_string) = hex_string.strip_prefix("0x") {
             hex::decode(hex_string).expect("decode")
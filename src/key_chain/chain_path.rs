@@ -1,23 +1,31 @@
-use thiserror::Error;
-
 use crate::KeyIndex;
-use std::borrow::Cow;
-use std::fmt;
+use alloc::{borrow::Cow, string::String};
+use core::fmt;
 
 const MASTER_SYMBOL: &str = "m";
 const HARDENED_SYMBOLS: [&str; 2] = ["H", "'"];
 const SEPARATOR: char = '/';
 
-#[derive(Error, Clone, Debug, Copy, PartialEq, Eq)]
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
 pub enum Error {
-    #[error("Invalid key path")]
     Invalid,
-    #[error("blank")]
     Blank,
-    #[error("Key index is out of range")]
     KeyIndexOutOfRange,
 }
 
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Invalid => write!(f, "Invalid key path"),
+            Error::Blank => write!(f, "blank"),
+            Error::KeyIndexOutOfRange => write!(f, "Key index is out of range"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
 /// ChainPath is used to describe BIP-32 KeyChain path.
 ///
 /// # Examples
@@ -14,10 +14,18 @@
 //! Check `hdwallet-bitcoin` if you want to derive bitcoin keys, and you can find or submit other crypto
 //! currencies support on [hdwallet homepage](https://github.com/jjyr/hdwallet).
 //!
+//! # `no_std`
+//!
+//! Disable default features and enable `rustcrypto` to build with `core` and `alloc` only, see
+//! [`backend`] for the hash backends. Pass your own secp256k1 context to the `*_with_context`
+//! methods, or let the crate create one on first use.
+//!
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
-#[macro_use]
-extern crate lazy_static;
+extern crate alloc;
 
+pub mod backend;
 pub mod error;
 pub mod extended_key;
 pub mod key_chain;
@@ -31,5 +39,7 @@ pub use crate::key_chain::{
 
 // re-exports
 pub use rand_core;
+#[cfg(feature = "ring")]
 pub use ring;
 pub use secp256k1;
+pub use zeroize;